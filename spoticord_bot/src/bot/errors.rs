@@ -0,0 +1,101 @@
+//! Turns errors that escape a command into replies the user can act on.
+
+use log::{error, warn};
+use poise::CreateReply;
+use serenity::{all::CreateEmbed, http::HttpError, model::ModelError};
+use songbird::error::JoinError;
+use spoticord_session::error::Error as SessionError;
+use spoticord_utils::discord::Colors;
+
+use super::{metrics, Context, FrameworkError};
+use crate::i18n::tr;
+
+/// Discord's JSON error code for a channel or resource the bot can't access
+const MISSING_ACCESS: isize = 50001;
+
+/// Discord's JSON error code for an action the bot lacks a permission for
+const MISSING_PERMISSIONS: isize = 50013;
+
+pub async fn on_error(error: FrameworkError<'_>) {
+    if let Some(ctx) = error.ctx() {
+        metrics::record_failure(ctx);
+    }
+
+    if let FrameworkError::Command {
+        error: why, ctx, ..
+    } = &error
+    {
+        if let Some(hint) = permission_hint(*ctx, why) {
+            warn!(
+                "Command '{}' failed because of missing permissions: {why}",
+                ctx.command().qualified_name
+            );
+
+            _ = ctx
+                .send(
+                    CreateReply::default()
+                        .embed(
+                            CreateEmbed::new()
+                                .title(tr(*ctx, "errors.permissions.title", &[]))
+                                .description(hint)
+                                .color(Colors::Error),
+                        )
+                        .ephemeral(true),
+                )
+                .await;
+
+            return;
+        }
+    }
+
+    if let Err(why) = poise::builtins::on_error(error).await {
+        error!("Failed to handle framework error: {why}");
+    }
+}
+
+/// Explain how to fix an error, if Discord refused the action because the bot lacks a permission
+fn permission_hint(ctx: Context<'_>, error: &anyhow::Error) -> Option<String> {
+    error.chain().find_map(|cause| {
+        if let Some(why) = cause.downcast_ref::<serenity::Error>() {
+            return serenity_hint(ctx, why);
+        }
+
+        if let Some(why) = cause.downcast_ref::<JoinError>() {
+            return join_hint(ctx, why);
+        }
+
+        // Session errors wrap these transparently, so they don't show up as a cause of their own
+        match cause.downcast_ref::<SessionError>()? {
+            SessionError::Serenity(why) => serenity_hint(ctx, why),
+            SessionError::JoinError(why) => join_hint(ctx, why),
+            _ => None,
+        }
+    })
+}
+
+fn serenity_hint(ctx: Context<'_>, error: &serenity::Error) -> Option<String> {
+    match error {
+        serenity::Error::Model(ModelError::InvalidPermissions { required, present }) => Some(tr(
+            ctx,
+            "errors.permissions.missing",
+            &[(
+                "permissions",
+                &required
+                    .difference(*present)
+                    .get_permission_names()
+                    .join(", "),
+            )],
+        )),
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+            if matches!(response.error.code, MISSING_ACCESS | MISSING_PERMISSIONS) =>
+        {
+            Some(tr(ctx, "errors.permissions.channel", &[]))
+        }
+        _ => None,
+    }
+}
+
+fn join_hint(ctx: Context<'_>, error: &JoinError) -> Option<String> {
+    // Discord doesn't answer a voice join that isn't allowed, so a missing permission shows up as a timeout
+    matches!(error, JoinError::TimedOut).then(|| tr(ctx, "errors.permissions.voice", &[]))
+}
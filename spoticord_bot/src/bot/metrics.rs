@@ -0,0 +1,208 @@
+//! Command usage and database size metrics, and the HTTP endpoint that exposes all metrics to Prometheus and a readiness probe
+
+use std::net::SocketAddr;
+
+use log::{error, info, warn};
+use spoticord_database::Database;
+use spoticord_utils::metrics;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use super::Context;
+
+pub fn describe() {
+    metrics::describe(
+        "spoticord_command_invocations_total",
+        "Commands that passed their checks and started executing",
+    );
+    metrics::describe(
+        "spoticord_command_results_total",
+        "Finished commands, by outcome",
+    );
+    metrics::describe(
+        "spoticord_database_connection_wait_seconds",
+        "Time spent waiting for a connection from the database pool",
+    );
+    metrics::describe(
+        "spoticord_database_health_check_seconds",
+        "Latency of the database health check served on /health",
+    );
+    metrics::describe(
+        "spoticord_database_connection_hold_seconds",
+        "Time the database connection was held, by database operation",
+    );
+    metrics::describe(
+        "spoticord_spotify_requests_total",
+        "Spotify Web API requests (including retries), by operation and outcome",
+    );
+    metrics::describe(
+        "spoticord_database_rows",
+        "Amount of rows in the user, account and link_request tables",
+    );
+    metrics::describe(
+        "spoticord_link_requests",
+        "Link requests that are still pending or have expired without being pruned",
+    );
+}
+
+pub async fn pre_command(ctx: Context<'_>) {
+    metrics::increment_counter(
+        "spoticord_command_invocations_total",
+        &[("command", ctx.command().qualified_name.as_str())],
+    );
+}
+
+pub async fn post_command(ctx: Context<'_>) {
+    record_outcome(ctx, "success");
+}
+
+/// Record a failed command. Commands with their own `on_error` handler must call this themselves.
+pub fn record_failure(ctx: Context<'_>) {
+    record_outcome(ctx, "failure");
+}
+
+fn record_outcome(ctx: Context<'_>, outcome: &str) {
+    metrics::increment_counter(
+        "spoticord_command_results_total",
+        &[
+            ("command", ctx.command().qualified_name.as_str()),
+            ("outcome", outcome),
+        ],
+    );
+}
+
+/// Periodically update the table size gauges, every [`spoticord_config::metrics_refresh_interval`].
+///
+/// Refreshes are skipped while the database can't be reached, so the gauges keep their last known values.
+pub async fn refresh_table_counts(database: Database) {
+    let mut interval = tokio::time::interval(spoticord_config::metrics_refresh_interval());
+
+    loop {
+        interval.tick().await;
+
+        let stats = match database.stats().await {
+            Ok(stats) => stats,
+            Err(why) => {
+                warn!("Database is unavailable, skipping table size metrics refresh: {why}");
+                continue;
+            }
+        };
+
+        let rows = [
+            ("user", stats.users),
+            ("account", stats.linked_accounts),
+            (
+                "link_request",
+                stats.pending_requests + stats.expired_requests,
+            ),
+        ];
+
+        for (table, count) in rows {
+            metrics::set_gauge("spoticord_database_rows", &[("table", table)], count as f64);
+        }
+
+        for (state, count) in [
+            ("pending", stats.pending_requests),
+            ("expired", stats.expired_requests),
+        ] {
+            metrics::set_gauge("spoticord_link_requests", &[("state", state)], count as f64);
+        }
+    }
+}
+
+/// Serve the Prometheus metrics on `GET /metrics`, a readiness probe on `GET /ready` and a health check on
+/// `GET /health`.
+///
+/// The readiness probe fails while the database has pending migrations, so instances that don't run migrations
+/// themselves only receive traffic once the database schema is up to date. The health check fails while the
+/// database can't be reached, and otherwise reports its latency and the state of the connection pool.
+pub async fn serve(addr: SocketAddr, database: Database) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(why) => {
+            error!("Failed to bind metrics endpoint to {addr}: {why}");
+            return;
+        }
+    };
+
+    info!("Serving metrics on http://{addr}/metrics");
+
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+
+        let database = database.clone();
+
+        tokio::spawn(async move {
+            let mut buffer = [0; 1024];
+            let Ok(read) = stream.read(&mut buffer).await else {
+                return;
+            };
+
+            let request = String::from_utf8_lossy(&buffer[..read]);
+            let response = if request.starts_with("GET /metrics ") {
+                let body = metrics::render();
+
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+            } else if request.starts_with("GET /ready ") {
+                match database.pending_migration_count().await {
+                    Ok(0) => "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string(),
+                    Ok(pending) => {
+                        let body = format!("{pending} pending migration(s)");
+
+                        format!(
+                            "HTTP/1.1 503 Service Unavailable\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                            body.len()
+                        )
+                    }
+                    Err(why) => {
+                        error!("Failed to check for pending migrations: {why}");
+
+                        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                }
+            } else if request.starts_with("GET /health ") {
+                match database.health_check().await {
+                    Ok(status) => {
+                        metrics::observe_histogram(
+                            "spoticord_database_health_check_seconds",
+                            &[],
+                            status.latency.as_secs_f64(),
+                        );
+
+                        let body = format!(
+                            "latency_ms {}\nidle_connections {}\nin_use_connections {}\n",
+                            status.latency.as_millis(),
+                            status.idle_connections,
+                            status.in_use_connections
+                        );
+
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                            body.len()
+                        )
+                    }
+                    Err(why) => {
+                        error!("Database health check failed: {why}");
+
+                        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                }
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            };
+
+            _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
@@ -1,37 +1,114 @@
+mod errors;
+pub mod metrics;
+pub mod permissions;
+pub mod welcome;
+
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use log::{debug, info};
+use log::{debug, info, warn};
 use poise::{serenity_prelude, Framework, FrameworkContext, FrameworkOptions};
-use serenity::all::{ActivityData, FullEvent, Ready, ShardManager};
+use serenity::all::{FullEvent, Ready, ShardManager};
 use spoticord_database::Database;
-use spoticord_session::manager::SessionManager;
+use spoticord_session::manager::{SessionManager, SessionQuery};
 
-use crate::commands;
+use crate::{commands, i18n};
 
 pub type Context<'a> = poise::Context<'a, Data, anyhow::Error>;
 pub type FrameworkError<'a> = poise::FrameworkError<'a, Data, anyhow::Error>;
 
 type Data = SessionManager;
 
+/// Commands that are only registered when their feature is enabled, as (command, feature)
+const EXPERIMENTAL_COMMANDS: &[(&str, &str)] = &[("lyrics", "lyrics")];
+
+type CommandFn = fn() -> poise::Command<Data, anyhow::Error>;
+
+/// Alternative names for commands, as (alias, canonical command).
+///
+/// Slash commands don't support aliases, so every alias is registered as a copy of the canonical command
+/// that only differs in name. The qualified name is kept, so permissions, metrics and localization follow the
+/// canonical command.
+const COMMAND_ALIASES: &[(&str, CommandFn)] = &[
+    ("leave", commands::music::disconnect),
+    ("np", commands::music::nowplaying),
+];
+
 pub fn framework_opts() -> FrameworkOptions<Data, anyhow::Error> {
+    let mut commands = vec![
+        #[cfg(debug_assertions)]
+        commands::debug::token(),
+        commands::core::help(),
+        commands::core::version(),
+        commands::core::ping(),
+        commands::core::rename(),
+        commands::core::device(),
+        commands::core::normalize(),
+        commands::core::explicit(),
+        commands::core::shareactivity(),
+        commands::core::link(),
+        commands::core::unlink(),
+        commands::core::refresh(),
+        commands::core::whoami(),
+        commands::core::export(),
+        commands::music::join(),
+        commands::music::disconnect(),
+        commands::music::move_channel(),
+        commands::music::stop(),
+        commands::music::transfer(),
+        commands::music::play(),
+        commands::music::search(),
+        commands::music::pause(),
+        commands::music::resume(),
+        commands::music::playpause(),
+        commands::music::playing(),
+        commands::music::nowplaying(),
+        commands::music::listeners(),
+        commands::music::skipto(),
+        commands::music::queue(),
+        commands::music::replay(),
+        commands::music::previous(),
+        commands::music::lyrics(),
+        commands::music::favorite(),
+        commands::music::favorites(),
+        commands::admin::maintenance(),
+        commands::admin::prune(),
+        commands::admin::linkstatus(),
+        commands::admin::forcedisconnect(),
+        commands::admin::devices(),
+        commands::admin::config(),
+        commands::admin::audit(),
+        commands::admin::selftest(),
+    ];
+
+    commands.extend(COMMAND_ALIASES.iter().map(|(alias, command)| {
+        let mut command = command();
+        command.name = alias.to_string();
+
+        command
+    }));
+
+    commands.retain(|command| {
+        EXPERIMENTAL_COMMANDS
+            .iter()
+            .find(|(name, _)| *name == command.qualified_name)
+            .map_or(true, |(_, feature)| {
+                spoticord_config::feature_enabled(feature)
+            })
+    });
+
+    i18n::localize_commands(&mut commands);
+
     poise::FrameworkOptions {
-        commands: vec![
-            #[cfg(debug_assertions)]
-            commands::debug::ping(),
-            #[cfg(debug_assertions)]
-            commands::debug::token(),
-            commands::core::help(),
-            commands::core::version(),
-            commands::core::rename(),
-            commands::core::link(),
-            commands::core::unlink(),
-            commands::music::join(),
-            commands::music::disconnect(),
-            commands::music::stop(),
-            commands::music::playing(),
-            commands::music::lyrics(),
-        ],
+        commands,
+        command_check: Some(|ctx| Box::pin(permissions::command_check(ctx))),
+        pre_command: |ctx| Box::pin(pre_command(ctx)),
+        post_command: |ctx| Box::pin(metrics::post_command(ctx)),
+        on_error: |error| Box::pin(errors::on_error(error)),
+        prefix_options: poise::PrefixFrameworkOptions {
+            prefix: spoticord_config::command_prefix().map(Into::into),
+            ..Default::default()
+        },
         event_handler: |ctx, event, framework, data| {
             Box::pin(event_handler(ctx, event, framework, data))
         },
@@ -39,6 +116,20 @@ pub fn framework_opts() -> FrameworkOptions<Data, anyhow::Error> {
     }
 }
 
+async fn pre_command(ctx: Context<'_>) {
+    metrics::pre_command(ctx).await;
+
+    // Recording activity must never hold up the command itself
+    let database = ctx.data().database();
+    let user_id = ctx.author().id.to_string();
+
+    tokio::spawn(async move {
+        if let Err(why) = database.touch_user(user_id).await {
+            warn!("Failed to record user activity: {why}");
+        }
+    });
+}
+
 pub async fn setup(
     ctx: &serenity_prelude::Context,
     ready: &Ready,
@@ -62,10 +153,19 @@ pub async fn setup(
         .await
         .ok_or_else(|| anyhow!("Songbird was not registered during setup"))?;
 
+    if let Some(addr) = spoticord_config::metrics_addr() {
+        metrics::describe();
+        tokio::spawn(metrics::serve(addr, database.clone()));
+        tokio::spawn(metrics::refresh_table_counts(database.clone()));
+    }
+
+    tokio::spawn(database.clone().watch_availability());
+    tokio::spawn(database.clone().cleanup_expired_requests_periodically());
+
     let manager = SessionManager::new(songbird, database);
 
     // #[cfg(feature = "stats")]
-    // let stats = StatsManager::new(spoticord_config::kv_url())?;
+    // let stats = StatsManager::new(spoticord_config::kv_url().ok_or_else(|| anyhow!("missing KV_URL"))?)?;
 
     tokio::spawn(background_loop(
         manager.clone(),
@@ -81,17 +181,46 @@ async fn event_handler(
     ctx: &serenity_prelude::Context,
     event: &FullEvent,
     _framework: FrameworkContext<'_, Data, anyhow::Error>,
-    _data: &Data,
+    data: &Data,
 ) -> Result<()> {
-    if let FullEvent::Ready { data_about_bot } = event {
-        if let Some(shard) = data_about_bot.shard {
-            debug!(
-                "Shard {} logged in (total shards: {})",
-                shard.id.0, shard.total
-            );
+    match event {
+        FullEvent::Ready { data_about_bot } => {
+            if let Some(shard) = data_about_bot.shard {
+                debug!(
+                    "Shard {} logged in (total shards: {})",
+                    shard.id.0, shard.total
+                );
+            }
+
+            ctx.set_activity(Some(spoticord_config::activity()));
+
+            // A new gateway session after a reconnect can leave voice connections behind
+            data.verify_sessions(ctx).await;
         }
+        FullEvent::VoiceStateUpdate { old, new } => {
+            let guild = new
+                .guild_id
+                .or_else(|| old.as_ref().and_then(|old| old.guild_id));
 
-        ctx.set_activity(Some(ActivityData::listening(spoticord_config::MOTD)));
+            if let Some(session) =
+                guild.and_then(|guild| data.get_session(SessionQuery::Guild(guild)))
+            {
+                session.check_alone().await;
+            }
+        }
+        // Guilds that are merely becoming available again (e.g. at startup) are not new
+        FullEvent::GuildCreate {
+            guild,
+            is_new: Some(true),
+        } if spoticord_config::post_welcome() => {
+            welcome::greet(ctx, guild, &data.database()).await;
+        }
+        FullEvent::Resume { .. } => {
+            debug!("Gateway connection resumed, verifying voice connections");
+
+            data.verify_sessions(ctx).await;
+        }
+        _ => {}
     }
 
     Ok(())
@@ -102,7 +231,6 @@ async fn background_loop(
     shard_manager: Arc<ShardManager>,
     // #[cfg(feature = "stats")] mut stats_manager: spoticord_stats::StatsManager,
 ) {
-
     loop {
         tokio::select! {
             _ = tokio::time::sleep(std::time::Duration::from_secs(60)) => {
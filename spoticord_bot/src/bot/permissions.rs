@@ -0,0 +1,312 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+};
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, GuildId};
+use spoticord_utils::discord::Colors;
+
+use super::Context;
+
+/// The permission tiers a command can require, from least to most privileged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Tier {
+    /// Anyone who can see the command
+    User,
+
+    /// Members with the Administrator or Manage Server permission
+    Admin,
+
+    /// Users listed in `OWNER_IDS`
+    Owner,
+}
+
+/// Commands that require more than [`Tier::User`], by qualified name
+const COMMAND_TIERS: &[(&str, Tier)] = &[
+    ("maintenance", Tier::Owner),
+    ("prune", Tier::Owner),
+    ("linkstatus", Tier::Owner),
+    ("forcedisconnect", Tier::Admin),
+    ("devices", Tier::Owner),
+    ("audit", Tier::Owner),
+    ("selftest", Tier::Owner),
+    ("config enable", Tier::Admin),
+    ("config disable", Tier::Admin),
+    ("config list", Tier::Admin),
+];
+
+/// Commands that can't be disabled in a guild, as that would lock admins out of re-enabling commands
+const ALWAYS_ENABLED: &[&str] = &["config enable", "config disable", "config list"];
+
+/// Commands that keep working while the database is unavailable
+const WITHOUT_DATABASE: &[&str] = &[
+    "help",
+    "version",
+    "ping",
+    "selftest",
+    "pause",
+    "resume",
+    "playpause",
+    "playing",
+    "nowplaying",
+    "lyrics",
+    "stop",
+    "disconnect",
+];
+
+/// The commands that are disabled per guild, so the database isn't queried for every command
+static DISABLED_COMMANDS: LazyLock<Mutex<HashMap<GuildId, Vec<String>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Playback controls that only the session owner and admins may use if `OWNER_ONLY_CONTROLS` is enabled
+const CONTROL_COMMANDS: &[&str] = &[
+    "play",
+    "search",
+    "favorites",
+    "pause",
+    "resume",
+    "playpause",
+    "skipto",
+    "queue clear",
+    "queue remove",
+    "replay",
+    "previous",
+    "stop",
+    "disconnect",
+    "move",
+    "transfer",
+];
+
+pub fn required_tier(command: &str) -> Tier {
+    COMMAND_TIERS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map(|(_, tier)| *tier)
+        .unwrap_or(Tier::User)
+}
+
+/// Determine the highest tier the author of a command invocation has
+pub async fn caller_tier(ctx: Context<'_>) -> Tier {
+    if spoticord_config::is_owner(ctx.author().id) {
+        return Tier::Owner;
+    }
+
+    let is_admin = ctx.author_member().await.is_some_and(|member| {
+        member
+            .permissions
+            .is_some_and(|permissions| permissions.administrator() || permissions.manage_guild())
+    });
+
+    if is_admin {
+        Tier::Admin
+    } else {
+        Tier::User
+    }
+}
+
+/// Whether the caller may use a playback control command on the session in the current guild
+async fn may_control(ctx: Context<'_>) -> bool {
+    if !spoticord_config::owner_only_controls()
+        || !CONTROL_COMMANDS.contains(&ctx.command().qualified_name.as_str())
+    {
+        return true;
+    }
+
+    let Some(guild) = ctx.guild_id() else {
+        return true;
+    };
+
+    match ctx.data().session_owner(guild).await {
+        Some(owner) if owner != ctx.author().id => caller_tier(ctx).await >= Tier::Admin,
+        _ => true,
+    }
+}
+
+/// Remember the commands that are disabled in a guild, after they were changed
+pub fn set_disabled_commands(guild: GuildId, commands: Vec<String>) {
+    DISABLED_COMMANDS
+        .lock()
+        .expect("mutex poisoned")
+        .insert(guild, commands);
+}
+
+/// Whether the invoked command has been disabled in the current guild
+async fn is_disabled(ctx: Context<'_>) -> bool {
+    let command = ctx.command().qualified_name.as_str();
+
+    let Some(guild) = ctx.guild_id() else {
+        return false;
+    };
+
+    if ALWAYS_ENABLED.contains(&command) || !ctx.data().database().is_available() {
+        return false;
+    }
+
+    let cached = DISABLED_COMMANDS
+        .lock()
+        .expect("mutex poisoned")
+        .get(&guild)
+        .map(|commands| commands.iter().any(|name| name == command));
+
+    if let Some(disabled) = cached {
+        return disabled;
+    }
+
+    match ctx
+        .data()
+        .database()
+        .get_disabled_commands(guild.get())
+        .await
+    {
+        Ok(commands) => {
+            let disabled = commands.iter().any(|name| name == command);
+            set_disabled_commands(guild, commands);
+
+            disabled
+        }
+        Err(why) => {
+            // Don't cache the failure, so the next command tries again
+            error!("Failed to retrieve disabled commands: {why}");
+            false
+        }
+    }
+}
+
+/// Global command check, rejects invocations of commands that require a higher tier than the caller has,
+/// commands that need the database while it is unavailable, commands that are disabled in the guild, and
+/// playback controls by anyone but the session owner if `OWNER_ONLY_CONTROLS` is enabled
+pub async fn command_check(ctx: Context<'_>) -> Result<bool> {
+    let required = required_tier(&ctx.command().qualified_name);
+
+    if required != Tier::User && caller_tier(ctx).await < required {
+        deny(
+            ctx,
+            "Insufficient permissions",
+            "You do not have permission to use this command.",
+        )
+        .await?;
+        return Ok(false);
+    }
+
+    if !ctx.data().database().is_available()
+        && !WITHOUT_DATABASE.contains(&ctx.command().qualified_name.as_str())
+    {
+        deny(
+            ctx,
+            "Temporarily unavailable",
+            "Spoticord can't reach its database right now. Please try again in a few minutes.",
+        )
+        .await?;
+        return Ok(false);
+    }
+
+    if is_disabled(ctx).await {
+        deny(
+            ctx,
+            "Command disabled",
+            "This command is disabled in this server.",
+        )
+        .await?;
+        return Ok(false);
+    }
+
+    if !may_control(ctx).await {
+        deny(
+            ctx,
+            "Insufficient permissions",
+            "Only the session owner can control playback.",
+        )
+        .await?;
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+async fn deny(ctx: Context<'_>, title: &str, description: &str) -> Result<()> {
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .title(title)
+                    .description(description)
+                    .color(Colors::Error),
+            )
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Commands that don't change what a session plays, so `OWNER_ONLY_CONTROLS` doesn't apply to them
+    const NOT_CONTROLS: &[&str] = &[
+        "token",
+        "help",
+        "version",
+        "ping",
+        "rename",
+        "device",
+        "normalize",
+        "explicit",
+        "shareactivity",
+        "link",
+        "unlink",
+        "refresh",
+        "whoami",
+        "export",
+        "join",
+        "playing",
+        "nowplaying",
+        "listeners",
+        "lyrics",
+        "favorite",
+        "maintenance",
+        "prune",
+        "linkstatus",
+        "forcedisconnect",
+        "devices",
+        "config enable",
+        "config disable",
+        "config list",
+        "audit",
+        "selftest",
+    ];
+
+    #[test]
+    fn playback_commands_are_controls() {
+        // Only the subcommands of a command with subcommands can be invoked. Qualified names are only filled in once
+        // the framework is built, so they are put together here.
+        let names = crate::bot::framework_opts()
+            .commands
+            .iter()
+            .flat_map(|command| {
+                if command.subcommands.is_empty() {
+                    vec![command.qualified_name.clone()]
+                } else {
+                    command
+                        .subcommands
+                        .iter()
+                        .map(|subcommand| format!("{} {}", command.name, subcommand.name))
+                        .collect()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for name in &names {
+            let name = name.as_str();
+
+            assert!(
+                CONTROL_COMMANDS.contains(&name) || NOT_CONTROLS.contains(&name),
+                "'{name}' must be listed in CONTROL_COMMANDS if it changes playback, or in NOT_CONTROLS otherwise"
+            );
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Posts a setup message when the bot is added to a new server, if `POST_WELCOME` is enabled.
+
+use log::{error, warn};
+use serenity::all::{ChannelType, Context, CreateEmbed, CreateMessage, Guild};
+use spoticord_database::Database;
+use spoticord_utils::discord::Colors;
+
+use crate::i18n::t;
+
+/// Greet a guild the bot was just added to, unless it has been greeted before
+pub async fn greet(ctx: &Context, guild: &Guild, database: &Database) {
+    // Claim the guild first, so a greeting is never posted twice, even if posting it fails
+    match database.mark_guild_greeted(guild.id.get()).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(why) => {
+            error!(
+                "Failed to check whether guild {} was greeted: {why}",
+                guild.id
+            );
+            return;
+        }
+    }
+
+    let mut channels = guild
+        .channels
+        .values()
+        .filter(|channel| channel.kind == ChannelType::Text)
+        .collect::<Vec<_>>();
+    channels.sort_by_key(|channel| channel.position);
+
+    // The system channel is preferred, otherwise the first channel that accepts the message is used
+    let candidates = guild
+        .system_channel_id
+        .into_iter()
+        .chain(channels.into_iter().map(|channel| channel.id));
+
+    let message = CreateMessage::new().embed(
+        CreateEmbed::new()
+            .title(t(&guild.preferred_locale, "welcome.title", &[]))
+            .description(t(&guild.preferred_locale, "welcome.description", &[]))
+            .color(Colors::Info),
+    );
+
+    for channel in candidates {
+        if channel.send_message(ctx, message.clone()).await.is_ok() {
+            return;
+        }
+    }
+
+    warn!("Could not find a channel to greet guild {} in", guild.id);
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_utils::discord::{escape, Colors};
+
+use crate::{bot::Context, i18n::tr};
+
+/// The amount of entries shown if no limit is given
+const DEFAULT_LIMIT: i64 = 20;
+
+/// Show the most recent changes to linked Spotify accounts
+#[poise::command(slash_command)]
+pub async fn audit(
+    ctx: Context<'_>,
+
+    #[description = "The amount of entries to show, defaults to 20"]
+    #[min = 1]
+    #[max = 50]
+    limit: Option<i64>,
+) -> Result<()> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    let embed = match ctx.data().database().recent_audit(limit).await {
+        Ok(entries) if entries.is_empty() => CreateEmbed::new()
+            .title(tr(ctx, "audit.title", &[]))
+            .description(tr(ctx, "audit.empty", &[]))
+            .color(Colors::Info),
+        Ok(entries) => CreateEmbed::new()
+            .title(tr(ctx, "audit.title", &[]))
+            .description(
+                entries
+                    .iter()
+                    .map(|entry| {
+                        let mut line = format!(
+                            "<t:{}:f> <@{}> **{}**",
+                            entry.created_at().timestamp(),
+                            entry.user_id,
+                            entry.action
+                        );
+
+                        if let Some(detail) = &entry.detail {
+                            line += &format!(": {}", escape(detail));
+                        }
+
+                        line
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+            .color(Colors::Info),
+        Err(why) => {
+            error!("Failed to retrieve the audit log: {why}");
+
+            CreateEmbed::new()
+                .description(tr(ctx, "audit.error", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
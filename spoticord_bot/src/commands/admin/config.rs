@@ -0,0 +1,139 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_utils::discord::Colors;
+
+use crate::{
+    bot::{permissions, Context},
+    i18n::tr,
+};
+
+/// Configure Spoticord for this server
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("disable", "enable", "list"),
+    subcommand_required
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Disable a command in this server
+#[poise::command(slash_command, guild_only)]
+async fn disable(
+    ctx: Context<'_>,
+
+    #[description = "The name of the command, e.g. lyrics"] command: String,
+) -> Result<()> {
+    update(ctx, &command, true).await
+}
+
+/// Enable a command that was disabled in this server
+#[poise::command(slash_command, guild_only)]
+async fn enable(
+    ctx: Context<'_>,
+
+    #[description = "The name of the command, e.g. lyrics"] command: String,
+) -> Result<()> {
+    update(ctx, &command, false).await
+}
+
+/// List the commands that are disabled in this server
+#[poise::command(slash_command, guild_only)]
+async fn list(ctx: Context<'_>) -> Result<()> {
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    let embed = match ctx
+        .data()
+        .database()
+        .get_disabled_commands(guild.get())
+        .await
+    {
+        Ok(commands) if commands.is_empty() => CreateEmbed::new()
+            .title(tr(ctx, "config.list.title", &[]))
+            .description(tr(ctx, "config.list.empty", &[]))
+            .color(Colors::Info),
+        Ok(commands) => CreateEmbed::new()
+            .title(tr(ctx, "config.list.title", &[]))
+            .description(
+                commands
+                    .iter()
+                    .map(|command| format!("- `/{command}`"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+            .color(Colors::Info),
+        Err(why) => {
+            error!("Failed to retrieve disabled commands: {why}");
+
+            CreateEmbed::new()
+                .description(tr(ctx, "config.list.error", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+async fn update(ctx: Context<'_>, command: &str, disabled: bool) -> Result<()> {
+    let guild = ctx.guild_id().expect("poise lied to me");
+    let name = command.trim().trim_start_matches('/');
+
+    // Aliases resolve to the command they belong to, which is what the command check looks at
+    let qualified_name = ctx
+        .framework()
+        .options()
+        .commands
+        .iter()
+        .find(|command| command.name == name)
+        .map(|command| command.qualified_name.clone());
+
+    let embed = match qualified_name {
+        None => CreateEmbed::new()
+            .title(tr(ctx, "config.unknown.title", &[]))
+            .description(tr(ctx, "config.unknown.description", &[("command", name)]))
+            .color(Colors::Error),
+        Some(command) if command == "config" => CreateEmbed::new()
+            .title(tr(ctx, "config.protected.title", &[]))
+            .description(tr(ctx, "config.protected.description", &[]))
+            .color(Colors::Error),
+        Some(command) => match ctx
+            .data()
+            .database()
+            .set_command_disabled(guild.get(), &command, disabled)
+            .await
+        {
+            Ok(commands) => {
+                permissions::set_disabled_commands(guild, commands);
+
+                let state = if disabled { "disabled" } else { "enabled" };
+
+                CreateEmbed::new()
+                    .title(tr(ctx, &format!("config.{state}.title"), &[]))
+                    .description(tr(
+                        ctx,
+                        &format!("config.{state}.description"),
+                        &[("command", &command)],
+                    ))
+                    .color(Colors::Success)
+            }
+            Err(why) => {
+                error!("Failed to update disabled commands: {why}");
+
+                CreateEmbed::new()
+                    .description(tr(ctx, "config.update.error", &[]))
+                    .color(Colors::Error)
+            }
+        },
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
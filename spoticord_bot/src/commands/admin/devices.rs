@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{
+    ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton, CreateEmbed,
+    CreateEmbedFooter, User,
+};
+use spoticord_database::error::DatabaseError;
+use spoticord_session::error::Error;
+use spoticord_utils::discord::{escape, Colors};
+
+use crate::{bot::Context, i18n::tr};
+
+/// How long the refresh button keeps working
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// List the Spotify devices that are visible to a user's Spotify account
+#[poise::command(slash_command)]
+pub async fn devices(
+    ctx: Context<'_>,
+
+    #[description = "The user whose devices to list"] user: User,
+) -> Result<()> {
+    ctx.defer_ephemeral().await?;
+
+    let button_id = format!("devices:{}", ctx.id());
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .embed(devices_embed(ctx, &user).await)
+                .components(refresh_button(ctx, &button_id))
+                .ephemeral(true),
+        )
+        .await?;
+
+    while let Some(press) = ComponentInteractionCollector::new(ctx.serenity_context())
+        .author_id(ctx.author().id)
+        .custom_ids(vec![button_id.clone()])
+        .timeout(REFRESH_TIMEOUT)
+        .await
+    {
+        press.defer(ctx.serenity_context()).await?;
+
+        reply
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .embed(devices_embed(ctx, &user).await)
+                    .components(refresh_button(ctx, &button_id)),
+            )
+            .await?;
+    }
+
+    reply
+        .edit(ctx, CreateReply::default().components(vec![]))
+        .await?;
+
+    Ok(())
+}
+
+async fn devices_embed(ctx: Context<'_>, user: &User) -> CreateEmbed {
+    let user_id = user.id.to_string();
+    let devices = match ctx.data().devices(user.id).await {
+        Ok(devices) => devices,
+        Err(Error::Database(DatabaseError::NotFound)) => {
+            return CreateEmbed::new()
+                .title(tr(ctx, "devices.title", &[]))
+                .description(tr(ctx, "devices.not_linked", &[("user", &user_id)]))
+                .color(Colors::Info);
+        }
+        Err(Error::Database(DatabaseError::RefreshTokenFailure)) => {
+            return CreateEmbed::new()
+                .title(tr(ctx, "devices.title", &[]))
+                .description(tr(ctx, "devices.refresh_failed", &[("user", &user_id)]))
+                .color(Colors::Error);
+        }
+        Err(Error::Database(DatabaseError::RefreshSuspended)) => {
+            return CreateEmbed::new()
+                .title(tr(ctx, "devices.title", &[]))
+                .description(tr(ctx, "devices.refresh_suspended", &[("user", &user_id)]))
+                .color(Colors::Error);
+        }
+        Err(why) => {
+            error!("Failed to list Spotify devices: {why}");
+
+            return CreateEmbed::new()
+                .description(tr(ctx, "devices.error", &[]))
+                .color(Colors::Error);
+        }
+    };
+
+    let description = if devices.is_empty() {
+        tr(ctx, "devices.empty", &[])
+    } else {
+        devices
+            .iter()
+            .map(|device| {
+                let mut tags = vec![device.kind.clone()];
+
+                if device.spoticord {
+                    tags.push(tr(ctx, "devices.tag.spoticord", &[]));
+                }
+
+                if device.active {
+                    tags.push(tr(ctx, "devices.tag.active", &[]));
+                }
+
+                if device.restricted {
+                    tags.push(tr(ctx, "devices.tag.restricted", &[]));
+                }
+
+                format!(
+                    "- **{}** (`{}`)\n  {}",
+                    escape(&device.name),
+                    device
+                        .id
+                        .clone()
+                        .unwrap_or_else(|| tr(ctx, "devices.no_id", &[])),
+                    tags.join(", ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    CreateEmbed::new()
+        .title(tr(ctx, "devices.title", &[]))
+        .description(tr(
+            ctx,
+            "devices.description",
+            &[("user", &user_id), ("devices", &description)],
+        ))
+        .footer(CreateEmbedFooter::new(tr(ctx, "devices.footer", &[])))
+        .color(Colors::Info)
+}
+
+fn refresh_button(ctx: Context<'_>, id: &str) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![CreateButton::new(id)
+        .label(tr(ctx, "devices.refresh", &[]))
+        .style(ButtonStyle::Secondary)])]
+}
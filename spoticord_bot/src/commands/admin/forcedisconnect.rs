@@ -0,0 +1,81 @@
+use anyhow::Result;
+use log::info;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, GuildId};
+use spoticord_utils::discord::Colors;
+
+use crate::{
+    bot::{
+        permissions::{caller_tier, Tier},
+        Context,
+    },
+    i18n::tr,
+};
+
+/// Forcibly remove a stuck session and leave its voice channel
+#[poise::command(slash_command)]
+pub async fn forcedisconnect(
+    ctx: Context<'_>,
+
+    #[description = "The ID of the server, defaults to this server"] guild: Option<String>,
+) -> Result<()> {
+    let target = match guild {
+        Some(guild) => guild.trim().parse().ok().map(GuildId::new),
+        None => ctx.guild_id(),
+    };
+
+    let Some(target) = target else {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "forcedisconnect.invalid.title", &[]))
+                        .description(tr(ctx, "forcedisconnect.invalid.description", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    };
+
+    // Admins may only clear sessions in their own server
+    if Some(target) != ctx.guild_id() && caller_tier(ctx).await < Tier::Owner {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "forcedisconnect.permissions.title", &[]))
+                        .description(tr(ctx, "forcedisconnect.permissions.description", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    let embed = if ctx.data().force_disconnect(target).await {
+        info!(
+            "Session in guild {target} was force disconnected by {}",
+            ctx.author().id
+        );
+
+        CreateEmbed::new()
+            .title(tr(ctx, "forcedisconnect.success.title", &[]))
+            .description(tr(ctx, "forcedisconnect.success.description", &[]))
+            .color(Colors::Success)
+    } else {
+        CreateEmbed::new()
+            .title(tr(ctx, "forcedisconnect.nothing.title", &[]))
+            .description(tr(ctx, "forcedisconnect.nothing.description", &[]))
+            .color(Colors::Info)
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, User};
+use spoticord_database::error::{DatabaseError, DatabaseResultExt};
+use spoticord_utils::discord::{escape, Colors};
+
+use crate::{bot::Context, i18n::tr};
+
+/// Inspect the Spotify link state of any user
+#[poise::command(slash_command)]
+pub async fn linkstatus(
+    ctx: Context<'_>,
+
+    #[description = "The user to inspect"] user: User,
+) -> Result<()> {
+    let db = ctx.data().database();
+    let user_id = user.id.to_string();
+
+    let lookup = async {
+        let stored = db.get_user(&user_id).await?;
+        let account = db.get_account(&user_id).await.optional()?;
+        let request = db.get_request(&user_id).await.optional()?;
+
+        Ok::<_, DatabaseError>((stored, account, request))
+    };
+
+    let embed = match lookup.await {
+        Ok((stored, account, request)) => {
+            let account = match account {
+                Some(account) if account.disabled => tr(
+                    ctx,
+                    "linkstatus.account.disabled",
+                    &[("username", &escape(&account.username))],
+                ),
+                Some(account) => tr(
+                    ctx,
+                    if account.expired() {
+                        "linkstatus.account.expired"
+                    } else {
+                        "linkstatus.account.expires"
+                    },
+                    &[
+                        ("username", &escape(&account.username)),
+                        (
+                            "time",
+                            &format!("<t:{}:R>", account.expires_at().timestamp()),
+                        ),
+                    ],
+                ),
+                None => tr(ctx, "linkstatus.account.none", &[]),
+            };
+
+            let request = match request {
+                Some(request) => tr(
+                    ctx,
+                    if request.expired() {
+                        "linkstatus.request.expired"
+                    } else {
+                        "linkstatus.request.expires"
+                    },
+                    &[(
+                        "time",
+                        &format!("<t:{}:R>", request.expires_at().timestamp()),
+                    )],
+                ),
+                None => tr(ctx, "linkstatus.request.none", &[]),
+            };
+
+            CreateEmbed::new()
+                .title(tr(ctx, "linkstatus.title", &[]))
+                .description(tr(
+                    ctx,
+                    "linkstatus.description",
+                    &[
+                        ("user", &user_id),
+                        ("device", &escape(&stored.device_name)),
+                        ("account", &account),
+                        ("request", &request),
+                    ],
+                ))
+                .color(Colors::Info)
+        }
+        Err(DatabaseError::NotFound) => CreateEmbed::new()
+            .title(tr(ctx, "linkstatus.title", &[]))
+            .description(tr(ctx, "linkstatus.unknown", &[("user", &user_id)]))
+            .color(Colors::Info),
+        Err(why) => {
+            error!("Failed to fetch link status: {why}");
+
+            CreateEmbed::new()
+                .description(tr(ctx, "linkstatus.error", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Run database maintenance (VACUUM ANALYZE) on the busiest tables
+#[poise::command(slash_command)]
+pub async fn maintenance(ctx: Context<'_>) -> Result<()> {
+    ctx.defer_ephemeral().await?;
+
+    let embed = match ctx.data().database().run_maintenance().await {
+        Ok(()) => CreateEmbed::new()
+            .title(tr(ctx, "maintenance.success.title", &[]))
+            .description(tr(ctx, "maintenance.success.description", &[]))
+            .color(Colors::Success),
+        Err(why) => {
+            error!("Failed to run database maintenance: {why}");
+
+            CreateEmbed::new()
+                .title(tr(ctx, "maintenance.error.title", &[]))
+                .description(tr(ctx, "maintenance.error.description", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
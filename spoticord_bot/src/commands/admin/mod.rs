@@ -0,0 +1,17 @@
+mod audit;
+mod config;
+mod devices;
+mod forcedisconnect;
+mod linkstatus;
+mod maintenance;
+mod prune;
+mod selftest;
+
+pub use audit::*;
+pub use config::*;
+pub use devices::*;
+pub use forcedisconnect::*;
+pub use linkstatus::*;
+pub use maintenance::*;
+pub use prune::*;
+pub use selftest::*;
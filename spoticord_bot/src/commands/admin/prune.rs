@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{error, info};
+use poise::{ChoiceParameter, CreateReply};
+use serenity::all::{
+    ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use spoticord_database::{error::DatabaseError, Database};
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+#[derive(Debug, Clone, Copy, ChoiceParameter)]
+pub enum PruneTarget {
+    #[name = "Inactive users"]
+    InactiveUsers,
+
+    #[name = "Expired link requests"]
+    ExpiredRequests,
+
+    #[name = "Orphaned accounts"]
+    OrphanedAccounts,
+}
+
+impl PruneTarget {
+    /// The locale key of the lowercase description of the rows that are deleted
+    fn key(self) -> &'static str {
+        match self {
+            Self::InactiveUsers => "prune.target.inactive_users",
+            Self::ExpiredRequests => "prune.target.expired_requests",
+            Self::OrphanedAccounts => "prune.target.orphaned_accounts",
+        }
+    }
+
+    async fn run(self, database: &Database, dry_run: bool) -> Result<usize, DatabaseError> {
+        match self {
+            Self::InactiveUsers => database.delete_inactive_users(dry_run).await,
+            Self::ExpiredRequests => database.prune_expired_requests(dry_run).await,
+            Self::OrphanedAccounts if dry_run => database
+                .find_orphaned_accounts()
+                .await
+                .map(|orphaned| orphaned.len()),
+            Self::OrphanedAccounts => database.cleanup_orphaned_accounts().await,
+        }
+    }
+}
+
+/// Delete stale rows from the database, after confirming how many rows will be affected
+#[poise::command(slash_command)]
+pub async fn prune(
+    ctx: Context<'_>,
+
+    #[description = "What should be deleted"] target: PruneTarget,
+) -> Result<()> {
+    let database = ctx.data().database();
+
+    ctx.defer_ephemeral().await?;
+
+    let count = match target.run(&database, true).await {
+        Ok(count) => count,
+        Err(why) => {
+            error!("Failed to count rows to prune: {why}");
+
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .title(tr(ctx, "prune.error.title", &[]))
+                            .description(tr(ctx, "prune.error.count", &[]))
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    let target_name = tr(ctx, target.key(), &[]);
+
+    if count == 0 {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "prune.nothing.title", &[]))
+                        .description(tr(
+                            ctx,
+                            "prune.nothing.description",
+                            &[("target", &target_name)],
+                        ))
+                        .color(Colors::Info),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    let confirm_id = format!("prune:confirm:{}", ctx.id());
+    let cancel_id = format!("prune:cancel:{}", ctx.id());
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .title(tr(ctx, "prune.confirm.title", &[]))
+                    .description(tr(
+                        ctx,
+                        "prune.confirm.description",
+                        &[("count", &count.to_string()), ("target", &target_name)],
+                    ))
+                    .color(Colors::Warning),
+            )
+            .components(vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(&confirm_id)
+                    .label(tr(ctx, "prune.confirm.delete", &[]))
+                    .style(ButtonStyle::Danger),
+                CreateButton::new(&cancel_id)
+                    .label(tr(ctx, "prune.confirm.cancel", &[]))
+                    .style(ButtonStyle::Secondary),
+            ])])
+            .ephemeral(true),
+    )
+    .await?;
+
+    let id = ctx.id().to_string();
+    let Some(press) = ComponentInteractionCollector::new(ctx.serenity_context())
+        .author_id(ctx.author().id)
+        .filter(move |press| press.data.custom_id.ends_with(&id))
+        .timeout(Duration::from_secs(60))
+        .await
+    else {
+        return Ok(());
+    };
+
+    let embed = if press.data.custom_id != confirm_id {
+        CreateEmbed::new()
+            .title(tr(ctx, "prune.cancelled.title", &[]))
+            .description(tr(ctx, "prune.cancelled.description", &[]))
+            .color(Colors::Info)
+    } else {
+        match target.run(&database, false).await {
+            Ok(deleted) => {
+                info!("Pruned {deleted} row(s) ({target:?})");
+
+                CreateEmbed::new()
+                    .title(tr(ctx, "prune.success.title", &[]))
+                    .description(tr(
+                        ctx,
+                        "prune.success.description",
+                        &[("count", &deleted.to_string())],
+                    ))
+                    .color(Colors::Success)
+            }
+            Err(why) => {
+                error!("Failed to prune rows: {why}");
+
+                CreateEmbed::new()
+                    .title(tr(ctx, "prune.error.title", &[]))
+                    .description(tr(ctx, "prune.error.delete", &[]))
+                    .color(Colors::Error)
+            }
+        }
+    };
+
+    press
+        .create_response(
+            ctx.serenity_context(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use std::{
+    fmt::Display,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use log::warn;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// How long a single step may take before it is reported as failed
+const STEP_TIMEOUT: Duration = Duration::from_secs(15);
+
+enum Outcome {
+    Pass(Duration),
+    Fail(String),
+    TimedOut,
+    /// The step was skipped, with the locale key that explains why
+    Skipped(&'static str),
+}
+
+/// Verify that the database, Spotify and voice connections work, using your own Spotify account
+#[poise::command(slash_command)]
+pub async fn selftest(ctx: Context<'_>) -> Result<()> {
+    ctx.defer_ephemeral().await?;
+
+    let manager = ctx.data();
+    let database = manager.database();
+    let user = ctx.author().id;
+
+    let mut steps = vec![];
+
+    steps.push(("database", run_step(database.ping()).await));
+    steps.push((
+        "token_refresh",
+        run_step(database.force_refresh_token(user.to_string())).await,
+    ));
+    steps.push((
+        "spotify",
+        run_step(manager.spotify_profile_name(user)).await,
+    ));
+
+    let voice_channel = ctx.guild_id().and_then(|guild| {
+        let channel = ctx
+            .guild()?
+            .voice_states
+            .get(&user)
+            .and_then(|state| state.channel_id)?;
+
+        Some((guild, channel))
+    });
+
+    let voice = match voice_channel {
+        Some((guild, channel)) => run_step(manager.voice_dry_run(guild, channel)).await,
+        None => Outcome::Skipped("selftest.skipped.voice"),
+    };
+    steps.push(("voice", voice));
+
+    let passed = steps
+        .iter()
+        .all(|(_, outcome)| !matches!(outcome, Outcome::Fail(_)));
+
+    let description = steps
+        .iter()
+        .map(|(step, outcome)| {
+            let name = tr(ctx, &format!("selftest.step.{step}"), &[]);
+
+            match outcome {
+                Outcome::Pass(elapsed) => tr(
+                    ctx,
+                    "selftest.passed",
+                    &[("name", &name), ("ms", &elapsed.as_millis().to_string())],
+                ),
+                Outcome::Fail(why) => {
+                    warn!("Self-test step '{step}' failed: {why}");
+
+                    tr(ctx, "selftest.failed", &[("name", &name), ("reason", why)])
+                }
+                Outcome::TimedOut => {
+                    warn!("Self-test step '{step}' timed out");
+
+                    tr(
+                        ctx,
+                        "selftest.timed_out",
+                        &[
+                            ("name", &name),
+                            ("seconds", &STEP_TIMEOUT.as_secs().to_string()),
+                        ],
+                    )
+                }
+                Outcome::Skipped(key) => tr(ctx, key, &[("name", &name)]),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .title(tr(
+                        ctx,
+                        if passed {
+                            "selftest.title.passed"
+                        } else {
+                            "selftest.title.failed"
+                        },
+                        &[],
+                    ))
+                    .description(description)
+                    .color(if passed {
+                        Colors::Success
+                    } else {
+                        Colors::Error
+                    }),
+            )
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Run a single step of the self-test, failing it if it doesn't finish within [`STEP_TIMEOUT`]
+async fn run_step<T, E: Display>(step: impl Future<Output = std::result::Result<T, E>>) -> Outcome {
+    let start = Instant::now();
+
+    match tokio::time::timeout(STEP_TIMEOUT, step).await {
+        Ok(Ok(_)) => Outcome::Pass(start.elapsed()),
+        Ok(Err(why)) => Outcome::Fail(why.to_string()),
+        Err(_) => Outcome::TimedOut,
+    }
+}
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{
+    ComponentInteractionCollector, ComponentInteractionDataKind, CreateActionRow, CreateEmbed,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use spoticord_database::error::{DatabaseError, DatabaseResultExt};
+use spoticord_session::error::Error;
+use spoticord_utils::discord::{escape, Colors};
+
+use crate::{bot::Context, i18n::tr};
+
+/// How long the user has to pick a device
+const SELECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The select menu value that clears the preferred device
+const SPOTICORD_VALUE: &str = "spoticord";
+
+/// Choose the Spotify device that queued items are sent to, instead of Spoticord itself
+#[poise::command(slash_command)]
+pub async fn device(ctx: Context<'_>) -> Result<()> {
+    let manager = ctx.data();
+    let db = manager.database();
+    let user_id = ctx.author().id.to_string();
+
+    ctx.defer_ephemeral().await?;
+
+    let current = db
+        .get_preferred_device(&user_id)
+        .await
+        .optional()
+        .map(Option::flatten);
+
+    let devices = match (&current, manager.devices(ctx.author().id).await) {
+        (Ok(_), Ok(devices)) => devices,
+        (_, Err(Error::Database(DatabaseError::NotFound))) => {
+            return reply(ctx, error_embed(ctx, "device.error.not_linked")).await;
+        }
+        (_, Err(Error::Database(DatabaseError::RefreshSuspended))) => {
+            return reply(ctx, error_embed(ctx, "errors.refresh_suspended")).await;
+        }
+        (Err(why), _) => {
+            error!("Failed to retrieve preferred device: {why}");
+
+            return reply(ctx, error_embed(ctx, "device.error.generic")).await;
+        }
+        (_, Err(why)) => {
+            error!("Failed to list Spotify devices: {why}");
+
+            return reply(ctx, error_embed(ctx, "device.error.generic")).await;
+        }
+    };
+    let current = current.ok().flatten();
+
+    // Spoticord's own device is the default option, restricted devices can't be sent items
+    let devices = devices
+        .into_iter()
+        .filter(|device| !device.spoticord && !device.restricted)
+        .filter_map(|device| Some((device.id?, device.name, device.kind)))
+        .collect::<Vec<_>>();
+
+    let current_name = match &current {
+        None => tr(ctx, "device.spoticord", &[]),
+        Some(current) => devices
+            .iter()
+            .find(|(id, _, _)| id == current)
+            .map(|(_, name, _)| escape(name))
+            .unwrap_or_else(|| tr(ctx, "device.unavailable", &[])),
+    };
+
+    let mut options =
+        vec![
+            CreateSelectMenuOption::new(tr(ctx, "device.spoticord", &[]), SPOTICORD_VALUE)
+                .description(tr(ctx, "device.spoticord_description", &[]))
+                .default_selection(current.is_none()),
+        ];
+
+    options.extend(devices.iter().map(|(id, name, kind)| {
+        CreateSelectMenuOption::new(name, id)
+            .description(kind)
+            .default_selection(current.as_ref() == Some(id))
+    }));
+
+    let menu_id = format!("device:{}", ctx.id());
+    let message = ctx
+        .send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "device.title", &[]))
+                        .description(tr(ctx, "device.description", &[("device", &current_name)]))
+                        .color(Colors::Info),
+                )
+                .components(vec![CreateActionRow::SelectMenu(
+                    CreateSelectMenu::new(&menu_id, CreateSelectMenuKind::String { options })
+                        .placeholder(tr(ctx, "device.placeholder", &[])),
+                )])
+                .ephemeral(true),
+        )
+        .await?;
+
+    let Some(selection) = ComponentInteractionCollector::new(ctx.serenity_context())
+        .author_id(ctx.author().id)
+        .custom_ids(vec![menu_id])
+        .timeout(SELECT_TIMEOUT)
+        .await
+    else {
+        message
+            .edit(ctx, CreateReply::default().components(vec![]))
+            .await?;
+
+        return Ok(());
+    };
+
+    selection.defer(ctx.serenity_context()).await?;
+
+    let Some(value) = (match &selection.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values.first().cloned(),
+        _ => None,
+    }) else {
+        return Ok(());
+    };
+
+    let device = devices.into_iter().find(|(id, _, _)| *id == value);
+    let embed = match db
+        .update_preferred_device(&user_id, device.as_ref().map(|(id, _, _)| id.clone()))
+        .await
+    {
+        Ok(()) => CreateEmbed::new()
+            .title(tr(ctx, "device.success.title", &[]))
+            .description(match &device {
+                Some((_, name, _)) => tr(
+                    ctx,
+                    "device.success.description",
+                    &[("device", &escape(name))],
+                ),
+                None => tr(ctx, "device.success.spoticord", &[]),
+            })
+            .color(Colors::Success),
+        Err(why) => {
+            error!("Failed to update preferred device: {why}");
+
+            error_embed(ctx, "device.error.save")
+        }
+    };
+
+    message
+        .edit(ctx, CreateReply::default().embed(embed).components(vec![]))
+        .await?;
+
+    Ok(())
+}
+
+fn error_embed(ctx: Context<'_>, key: &str) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(tr(ctx, "device.error.title", &[]))
+        .description(tr(ctx, key, &[]))
+        .color(Colors::Error)
+}
+
+async fn reply(ctx: Context<'_>, embed: CreateEmbed) -> Result<()> {
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Allow or block explicit tracks from being queued in your sessions
+#[poise::command(slash_command)]
+pub async fn explicit(
+    ctx: Context<'_>,
+
+    #[description = "Whether explicit tracks may be added to the queue"] allowed: bool,
+) -> Result<()> {
+    let db = ctx.data().database();
+
+    let user = match db.get_or_create_user(ctx.author().id.to_string()).await {
+        Ok(user) => user,
+        Err(why) => {
+            error!("Error fetching user: {why}");
+
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .description(tr(ctx, "explicit.error.generic", &[]))
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    if let Err(why) = db.update_allow_explicit(user.id, allowed).await {
+        error!("Error updating explicit content setting: {why}");
+
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .description(tr(ctx, "explicit.error.generic", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .description(if allowed {
+                        tr(ctx, "explicit.allowed", &[])
+                    } else {
+                        tr(ctx, "explicit.blocked", &[])
+                    })
+                    .color(Colors::Success),
+            )
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
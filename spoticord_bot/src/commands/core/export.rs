@@ -0,0 +1,125 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serde_json::json;
+use serenity::all::{CreateAttachment, CreateEmbed, CreateEmbedFooter, CreateMessage};
+use spoticord_database::{error::DatabaseError, models::UserExport};
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Receive a copy of all data Spoticord has stored about you
+#[poise::command(slash_command)]
+pub async fn export(ctx: Context<'_>) -> Result<()> {
+    let db = ctx.data().database();
+
+    ctx.defer_ephemeral().await?;
+
+    let export = match db.export_user_data(ctx.author().id.to_string()).await {
+        Ok(export) => export,
+        Err(DatabaseError::NotFound) => {
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .title(tr(ctx, "export.empty.title", &[]))
+                            .description(tr(ctx, "export.empty.description", &[]))
+                            .color(Colors::Info),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+        Err(why) => {
+            error!("Failed to export user data: {why}");
+
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .description(tr(ctx, "export.error.generic", &[]))
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    let content = serde_json::to_vec_pretty(&to_json(export))?;
+    let message = CreateMessage::new()
+        .embed(
+            CreateEmbed::new()
+                .title(tr(ctx, "export.message.title", &[]))
+                .description(tr(ctx, "export.message.description", &[]))
+                .footer(CreateEmbedFooter::new(tr(
+                    ctx,
+                    "export.message.footer",
+                    &[],
+                )))
+                .color(Colors::Info),
+        )
+        .add_file(CreateAttachment::bytes(content, "spoticord-export.json"));
+
+    let embed = match ctx.author().dm(ctx, message).await {
+        Ok(_) => CreateEmbed::new()
+            .title(tr(ctx, "export.success.title", &[]))
+            .description(tr(ctx, "export.success.description", &[]))
+            .color(Colors::Success),
+        Err(why) => {
+            error!("Failed to send data export: {why}");
+
+            CreateEmbed::new()
+                .title(tr(ctx, "export.error.dm.title", &[]))
+                .description(tr(ctx, "export.error.dm.description", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+fn to_json(export: UserExport) -> serde_json::Value {
+    json!({
+        "user": {
+            "id": export.user.id,
+            "device_name": export.user.device_name,
+            "normalize": export.user.normalize,
+            "allow_explicit": export.user.allow_explicit,
+            "share_activity": export.user.share_activity,
+            "preferred_device_id": export.user.preferred_device_id,
+        },
+        "account": export.account.map(|account| json!({
+            "username": account.username,
+            "expires": account.expires_at().to_rfc3339(),
+            "last_updated": account.last_updated_at().to_rfc3339(),
+            "disabled": account.disabled,
+        })),
+        "link_request": export.link_request_expires.map(|expires| json!({
+            "expires": expires.and_utc().to_rfc3339(),
+        })),
+        "play_history": export
+            .play_history
+            .into_iter()
+            .map(|play| json!({
+                "track_uri": play.track_uri,
+                "played_at": play.played_at.and_utc().to_rfc3339(),
+            }))
+            .collect::<Vec<_>>(),
+        "favorites": export
+            .favorites
+            .into_iter()
+            .map(|favorite| json!({
+                "track_uri": favorite.track_uri,
+                "added_at": favorite.added_at.and_utc().to_rfc3339(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
@@ -3,21 +3,19 @@ use poise::CreateReply;
 use serenity::all::{CreateEmbed, CreateEmbedAuthor};
 use spoticord_utils::discord::Colors;
 
-use crate::bot::Context;
-
-const HELP_MESSAGE: &str = include_str!("help.md");
+use crate::{bot::Context, i18n::tr};
 
 /// Displays the help message
-#[poise::command(slash_command)]
+#[poise::command(slash_command, prefix_command)]
 pub async fn help(ctx: Context<'_>) -> Result<()> {
     ctx.send(
         CreateReply::default().embed(
             CreateEmbed::new()
                 .author(
-                    CreateEmbedAuthor::new("Spoticord Help")
+                    CreateEmbedAuthor::new(tr(ctx, "help.title", &[]))
                         .icon_url("https://spoticord.com/logo-standard.webp"),
                 )
-                .description(HELP_MESSAGE)
+                .description(tr(ctx, "help.message", &[]))
                 .color(Colors::Info),
         ),
     )
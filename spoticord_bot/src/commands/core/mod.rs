@@ -1,11 +1,27 @@
+mod device;
+mod explicit;
+mod export;
 mod help;
 mod link;
+mod normalize;
+mod ping;
+mod refresh;
 mod rename;
+mod shareactivity;
 mod unlink;
 mod version;
+mod whoami;
 
+pub use device::*;
+pub use explicit::*;
+pub use export::*;
 pub use help::*;
 pub use link::*;
+pub use normalize::*;
+pub use ping::*;
+pub use refresh::*;
 pub use rename::*;
+pub use shareactivity::*;
 pub use unlink::*;
 pub use version::*;
+pub use whoami::*;
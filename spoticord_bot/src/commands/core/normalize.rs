@@ -0,0 +1,84 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+use spoticord_session::manager::SessionQuery;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Toggle loudness normalization for your Spoticord device
+#[poise::command(slash_command)]
+pub async fn normalize(
+    ctx: Context<'_>,
+
+    #[description = "Whether tracks should be played at a consistent volume"] enabled: bool,
+) -> Result<()> {
+    let db = ctx.data().database();
+
+    let user = match db.get_or_create_user(ctx.author().id.to_string()).await {
+        Ok(user) => user,
+        Err(why) => {
+            error!("Error fetching user: {why}");
+
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .description(tr(ctx, "normalize.error.generic", &[]))
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    if let Err(why) = db.update_normalize(user.id, enabled).await {
+        error!("Error updating user normalization: {why}");
+
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .description(tr(ctx, "normalize.error.generic", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    let has_session = ctx
+        .data()
+        .get_session(SessionQuery::Owner(ctx.author().id))
+        .is_some();
+
+    ctx.send(
+        CreateReply::default()
+            .embed({
+                let mut embed = CreateEmbed::new()
+                    .description(if enabled {
+                        tr(ctx, "normalize.enabled", &[])
+                    } else {
+                        tr(ctx, "normalize.disabled", &[])
+                    })
+                    .color(Colors::Success);
+
+                if has_session {
+                    embed =
+                        embed.footer(CreateEmbedFooter::new(tr(ctx, "normalize.reconnect", &[])));
+                }
+
+                embed
+            })
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
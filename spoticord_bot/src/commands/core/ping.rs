@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Check the latency to Discord and the database
+#[poise::command(slash_command)]
+pub async fn ping(ctx: Context<'_>) -> Result<()> {
+    // Serenity reports no latency until the first heartbeat has been acknowledged
+    let gateway = match ctx.ping().await {
+        Duration::ZERO => tr(ctx, "ping.unknown", &[]),
+        latency => tr(
+            ctx,
+            "ping.latency",
+            &[("ms", &latency.as_millis().to_string())],
+        ),
+    };
+
+    let start = Instant::now();
+    let database = match ctx.data().database().ping().await {
+        Ok(()) => tr(
+            ctx,
+            "ping.latency",
+            &[("ms", &start.elapsed().as_millis().to_string())],
+        ),
+        Err(why) => {
+            error!("Failed to ping the database: {why}");
+
+            tr(ctx, "ping.unreachable", &[])
+        }
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .title(tr(ctx, "ping.title", &[]))
+                    .description(tr(
+                        ctx,
+                        "ping.description",
+                        &[("gateway", &gateway), ("database", &database)],
+                    ))
+                    .color(Colors::Info),
+            )
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateEmbedFooter, User};
+use spoticord_database::error::DatabaseError;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Force a refresh of a Spotify access token, to troubleshoot playback issues
+#[poise::command(slash_command)]
+pub async fn refresh(
+    ctx: Context<'_>,
+
+    #[description = "The user whose token should be refreshed (bot owners only)"] user: Option<
+        User,
+    >,
+) -> Result<()> {
+    let target = user.as_ref().unwrap_or(ctx.author());
+
+    if target.id != ctx.author().id && !spoticord_config::is_owner(ctx.author().id) {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "refresh.error.permissions.title", &[]))
+                        .description(tr(ctx, "refresh.error.permissions.description", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    ctx.defer_ephemeral().await?;
+
+    let embed = match ctx
+        .data()
+        .database()
+        .force_refresh_token(target.id.to_string())
+        .await
+    {
+        Ok(expires) => CreateEmbed::new()
+            .title(tr(ctx, "refresh.success.title", &[]))
+            .description(tr(
+                ctx,
+                "refresh.success.description",
+                &[
+                    ("user", &target.id.to_string()),
+                    ("expires", &expires.timestamp().to_string()),
+                ],
+            ))
+            .color(Colors::Success),
+        Err(DatabaseError::NotFound) => CreateEmbed::new()
+            .title(tr(ctx, "refresh.error.not_linked.title", &[]))
+            .description(tr(
+                ctx,
+                "refresh.error.not_linked.description",
+                &[("user", &target.id.to_string())],
+            ))
+            .footer(CreateEmbedFooter::new(tr(
+                ctx,
+                "refresh.error.not_linked.footer",
+                &[],
+            )))
+            .color(Colors::Error),
+        Err(DatabaseError::RefreshTokenFailure) => CreateEmbed::new()
+            .title(tr(ctx, "refresh.error.title", &[]))
+            .description(tr(ctx, "refresh.error.rejected", &[]))
+            .footer(CreateEmbedFooter::new(tr(
+                ctx,
+                "refresh.error.rejected_footer",
+                &[],
+            )))
+            .color(Colors::Error),
+        Err(DatabaseError::RefreshSuspended) => CreateEmbed::new()
+            .title(tr(ctx, "refresh.error.title", &[]))
+            .description(tr(ctx, "refresh.error.suspended", &[]))
+            .color(Colors::Error),
+        Err(why) => {
+            error!("Failed to force refresh token: {why}");
+
+            CreateEmbed::new()
+                .title(tr(ctx, "refresh.error.title", &[]))
+                .description(tr(ctx, "refresh.error.generic", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
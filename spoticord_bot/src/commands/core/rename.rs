@@ -1,11 +1,12 @@
 use anyhow::Result;
-use log::error;
+use log::{error, info};
 use poise::CreateReply;
 use serenity::all::{CreateEmbed, CreateEmbedFooter};
+use spoticord_database::error::DatabaseError;
 use spoticord_session::manager::SessionQuery;
 use spoticord_utils::discord::Colors;
 
-use crate::bot::Context;
+use crate::{bot::Context, i18n::tr};
 
 #[poise::command(slash_command)]
 pub async fn rename(
@@ -27,7 +28,7 @@ pub async fn rename(
                 CreateReply::default()
                     .embed(
                         CreateEmbed::new()
-                            .description("Something went wrong whilst trying to rename your Spoticord device.")
+                            .description(tr(ctx, "rename.error.generic", &[]))
                             .color(Colors::Error),
                     )
                     .ephemeral(true),
@@ -38,23 +39,42 @@ pub async fn rename(
         }
     };
 
-    if let Err(why) = db.update_device_name(user.id, &name).await {
-        error!("Error updating user device name: {why}");
+    match db.update_device_name(&user.id, &name).await {
+        Ok(Some(previous)) => info!(
+            "User {} renamed their device from {previous:?} to {name:?}",
+            user.id
+        ),
+        Ok(None) => {}
+        Err(DatabaseError::InvalidDeviceName) => {
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .description(tr(ctx, "rename.error.invalid_name", &[]))
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+        Err(why) => {
+            error!("Error updating user device name: {why}");
 
-        ctx.send(
-            CreateReply::default()
-                .embed(
-                    CreateEmbed::new()
-                        .description(
-                            "Something went wrong while trying to rename your Spoticord device.",
-                        )
-                        .color(Colors::Error),
-                )
-                .ephemeral(true),
-        )
-        .await?;
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .description(tr(ctx, "rename.error.generic", &[]))
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
 
-        return Ok(());
+            return Ok(());
+        }
     }
 
     let has_session = ctx
@@ -66,16 +86,15 @@ pub async fn rename(
         CreateReply::default()
             .embed({
                 let mut embed = CreateEmbed::new()
-                    .description(format!(
-                        "Successfully changed the Spotify device name to **{}**",
-                        spoticord_utils::discord::escape(name)
+                    .description(tr(
+                        ctx,
+                        "rename.success",
+                        &[("name", &spoticord_utils::discord::escape(name.trim()))],
                     ))
                     .color(Colors::Success);
 
                 if has_session {
-                    embed = embed.footer(CreateEmbedFooter::new(
-                        "You must reconnect the player for the new name to show up",
-                    ));
+                    embed = embed.footer(CreateEmbedFooter::new(tr(ctx, "rename.reconnect", &[])));
                 }
 
                 embed
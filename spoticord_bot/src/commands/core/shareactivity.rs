@@ -0,0 +1,71 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Choose whether other members can see what you're listening to with /listeners
+#[poise::command(slash_command)]
+pub async fn shareactivity(
+    ctx: Context<'_>,
+
+    #[description = "Whether your sessions are shown in /listeners"] enabled: bool,
+) -> Result<()> {
+    let db = ctx.data().database();
+
+    let user = match db.get_or_create_user(ctx.author().id.to_string()).await {
+        Ok(user) => user,
+        Err(why) => {
+            error!("Error fetching user: {why}");
+
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .description(tr(ctx, "shareactivity.error.generic", &[]))
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    if let Err(why) = db.update_share_activity(user.id, enabled).await {
+        error!("Error updating activity sharing setting: {why}");
+
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .description(tr(ctx, "shareactivity.error.generic", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .description(if enabled {
+                        tr(ctx, "shareactivity.enabled", &[])
+                    } else {
+                        tr(ctx, "shareactivity.disabled", &[])
+                    })
+                    .color(Colors::Success),
+            )
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
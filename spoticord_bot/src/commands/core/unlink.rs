@@ -5,7 +5,10 @@ use serenity::all::{CreateEmbed, CreateEmbedFooter};
 use spoticord_session::manager::SessionQuery;
 use spoticord_utils::discord::Colors;
 
-use crate::bot::{Context, FrameworkError};
+use crate::{
+    bot::{metrics, Context, FrameworkError},
+    i18n::tr,
+};
 
 /// Unlink your Spotify account from Spoticord
 #[poise::command(slash_command, on_error = on_error)]
@@ -35,13 +38,13 @@ pub async fn unlink(
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("No Spotify account linked")
-                        .description(
-                            "You cannot unlink your Spotify account if you haven't linked one.",
-                        )
-                        .footer(CreateEmbedFooter::new(
-                            "You can use /link to link a new Spotify account.",
-                        ))
+                        .title(tr(ctx, "unlink.error.not_linked.title", &[]))
+                        .description(tr(ctx, "unlink.error.not_linked.description", &[]))
+                        .footer(CreateEmbedFooter::new(tr(
+                            ctx,
+                            "unlink.error.not_linked.footer",
+                            &[],
+                        )))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -55,11 +58,13 @@ pub async fn unlink(
         CreateReply::default()
             .embed(
                 CreateEmbed::new()
-                    .title("Account unlinked")
-                    .description("You have unlinked your Spotify account from Spoticord.")
-                    .footer(CreateEmbedFooter::new(
-                        "Changed your mind? You can use /link to link a new Spotify account.",
-                    ))
+                    .title(tr(ctx, "unlink.success.title", &[]))
+                    .description(tr(ctx, "unlink.success.description", &[]))
+                    .footer(CreateEmbedFooter::new(tr(
+                        ctx,
+                        "unlink.success.footer",
+                        &[],
+                    )))
                     .color(Colors::Success),
             )
             .ephemeral(true),
@@ -72,13 +77,14 @@ pub async fn unlink(
 async fn on_error(error: FrameworkError<'_>) {
     if let FrameworkError::Command { error, ctx, .. } = error {
         error!("An error occured during linking of new account: {error}");
+        metrics::record_failure(ctx);
 
         _ = ctx
             .send(
                 CreateReply::default()
                     .embed(
                         CreateEmbed::new()
-                            .description("An error occured whilst trying to unlink your account.")
+                            .description(tr(ctx, "unlink.error.generic", &[]))
                             .color(Colors::Error),
                     )
                     .ephemeral(true),
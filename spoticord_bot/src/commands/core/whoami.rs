@@ -0,0 +1,64 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+use spoticord_database::error::DatabaseError;
+use spoticord_utils::discord::{escape, Colors};
+
+use crate::{bot::Context, i18n::tr};
+
+/// Show which Spotify account is linked to Spoticord
+#[poise::command(slash_command)]
+pub async fn whoami(ctx: Context<'_>) -> Result<()> {
+    let db = ctx.data().database();
+
+    let embed = match db.get_account_meta(ctx.author().id.to_string()).await {
+        Ok(meta) if meta.disabled => CreateEmbed::new()
+            .title(tr(ctx, "whoami.disabled.title", &[]))
+            .description(tr(
+                ctx,
+                "whoami.disabled.description",
+                &[("username", &escape(meta.username))],
+            ))
+            .footer(CreateEmbedFooter::new(tr(
+                ctx,
+                "whoami.disabled.footer",
+                &[],
+            )))
+            .color(Colors::Error),
+        Ok(meta) => CreateEmbed::new()
+            .title(tr(ctx, "whoami.linked.title", &[]))
+            .description(tr(
+                ctx,
+                "whoami.linked.description",
+                &[
+                    ("username", &escape(&meta.username)),
+                    ("device_name", &escape(&meta.device_name)),
+                    ("expires", &meta.expires_at().timestamp().to_string()),
+                    ("refreshed", &meta.last_updated_at().timestamp().to_string()),
+                ],
+            ))
+            .color(Colors::Info),
+        Err(DatabaseError::NotFound) => CreateEmbed::new()
+            .title(tr(ctx, "whoami.not_linked.title", &[]))
+            .description(tr(ctx, "whoami.not_linked.description", &[]))
+            .footer(CreateEmbedFooter::new(tr(
+                ctx,
+                "whoami.not_linked.footer",
+                &[],
+            )))
+            .color(Colors::Error),
+        Err(why) => {
+            error!("Failed to fetch account details: {why}");
+
+            CreateEmbed::new()
+                .description(tr(ctx, "whoami.error.generic", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -1,5 +1,3 @@
-mod ping;
 mod token;
 
-pub use ping::*;
 pub use token::*;
@@ -2,7 +2,7 @@ use anyhow::Result;
 use poise::CreateReply;
 use spoticord_database::error::DatabaseError;
 
-use crate::bot::Context;
+use crate::{bot::Context, i18n::tr};
 
 /// Retrieve the Spotify access token. For debugging purposes.
 #[poise::command(slash_command)]
@@ -14,11 +14,9 @@ pub async fn token(ctx: Context<'_>) -> Result<()> {
         .await;
 
     let content = match token {
-        Ok(token) => format!("Your token is:\n```\n{token}\n```"),
-        Err(DatabaseError::NotFound) => {
-            "You must authenticate first before requesting a token".to_string()
-        }
-        Err(why) => format!("Failed to retrieve access token: {why}"),
+        Ok(token) => tr(ctx, "token.success", &[("token", &token)]),
+        Err(DatabaseError::NotFound) => tr(ctx, "token.not_linked", &[]),
+        Err(why) => tr(ctx, "token.error", &[("error", &why.to_string())]),
     };
 
     ctx.send(CreateReply::default().content(content).ephemeral(true))
@@ -4,7 +4,7 @@ use serenity::all::CreateEmbed;
 use spoticord_session::manager::SessionQuery;
 use spoticord_utils::discord::Colors;
 
-use crate::bot::Context;
+use crate::{bot::Context, i18n::tr};
 
 #[poise::command(slash_command, guild_only)]
 pub async fn disconnect(ctx: Context<'_>) -> Result<(), Error> {
@@ -16,8 +16,8 @@ pub async fn disconnect(ctx: Context<'_>) -> Result<(), Error> {
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Cannot disconnect bot")
-                        .description("I'm currently not connected to any voice channel.")
+                        .title(tr(ctx, "disconnect.error.title", &[]))
+                        .description(tr(ctx, "disconnect.error.not_connected", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -32,8 +32,8 @@ pub async fn disconnect(ctx: Context<'_>) -> Result<(), Error> {
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Cannot disconnect bot")
-                        .description("Only the host may disconnect the bot.")
+                        .title(tr(ctx, "disconnect.error.title", &[]))
+                        .description(tr(ctx, "disconnect.error.not_host", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -48,8 +48,8 @@ pub async fn disconnect(ctx: Context<'_>) -> Result<(), Error> {
     ctx.send(
         CreateReply::default().embed(
             CreateEmbed::new()
-                .title("Goodbye, for now!")
-                .description("I have left the voice channel, goodbye for now.")
+                .title(tr(ctx, "disconnect.success.title", &[]))
+                .description(tr(ctx, "disconnect.success.description", &[]))
                 .color(Colors::Info),
         ),
     )
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_database::{error::DatabaseError, FAVORITES_LIMIT};
+use spoticord_session::{error::Error, playback_uri};
+use spoticord_utils::discord::{escape, Colors};
+
+use crate::{bot::Context, i18n::tr};
+
+/// Save the track that is currently playing to your favorites
+#[poise::command(slash_command, guild_only)]
+pub async fn favorite(ctx: Context<'_>) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    let info = match manager.session_state(guild).await {
+        Ok(state) => state.playback.filter(|info| info.is_track()),
+        Err(Error::NoSession) => None,
+        Err(why) => {
+            error!("Failed to retrieve session state: {why}");
+
+            reply(
+                ctx,
+                CreateEmbed::new()
+                    .title(tr(ctx, "favorite.error.title", &[]))
+                    .description(tr(ctx, "favorite.error.state", &[]))
+                    .color(Colors::Error),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    let Some(info) = info else {
+        reply(
+            ctx,
+            CreateEmbed::new()
+                .title(tr(ctx, "favorite.error.title", &[]))
+                .description(tr(ctx, "favorite.error.nothing_playing", &[]))
+                .color(Colors::Error),
+        )
+        .await?;
+
+        return Ok(());
+    };
+
+    let db = manager.database();
+    let user_id = ctx.author().id.to_string();
+    let name = escape(info.name());
+
+    let result = match db.get_or_create_user(&user_id).await {
+        Ok(_) => db.add_favorite(&user_id, playback_uri(&info)).await,
+        Err(why) => Err(why),
+    };
+
+    let embed = match result {
+        Ok(true) => CreateEmbed::new()
+            .title(tr(ctx, "favorite.saved.title", &[]))
+            .description(tr(ctx, "favorite.saved.description", &[("name", &name)]))
+            .color(Colors::Success),
+        Ok(false) => CreateEmbed::new()
+            .title(tr(ctx, "favorite.existing.title", &[]))
+            .description(tr(ctx, "favorite.existing.description", &[("name", &name)]))
+            .color(Colors::Info),
+        Err(DatabaseError::FavoritesFull) => CreateEmbed::new()
+            .title(tr(ctx, "favorite.error.title", &[]))
+            .description(tr(
+                ctx,
+                "favorite.error.full",
+                &[("limit", &FAVORITES_LIMIT.to_string())],
+            ))
+            .color(Colors::Error),
+        Err(why) => {
+            error!("Failed to save favorite: {why}");
+
+            CreateEmbed::new()
+                .title(tr(ctx, "favorite.error.title", &[]))
+                .description(tr(ctx, "favorite.error.generic", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    reply(ctx, embed).await?;
+
+    Ok(())
+}
+
+async fn reply(ctx: Context<'_>, embed: CreateEmbed) -> Result<()> {
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{
+    ComponentInteractionCollector, ComponentInteractionDataKind, CreateActionRow, CreateEmbed,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use spoticord_database::error::DatabaseError;
+use spoticord_session::{error::Error, queue::QueueItem, uri::SpotifyUri};
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// How long the user has to pick a favorite
+const SELECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Discord's maximum length for select menu option labels and descriptions
+const OPTION_TEXT_LIMIT: usize = 100;
+
+/// List your favorite tracks, to add one to the queue or remove it
+#[poise::command(slash_command, guild_only)]
+pub async fn favorites(ctx: Context<'_>) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+    let db = manager.database();
+
+    ctx.defer_ephemeral().await?;
+
+    let uris = match db.get_favorites(ctx.author().id.to_string()).await {
+        Ok(favorites) => favorites
+            .into_iter()
+            .map(|favorite| favorite.track_uri)
+            .collect::<Vec<_>>(),
+        Err(why) => {
+            error!("Failed to retrieve favorites: {why}");
+
+            return reply(
+                ctx,
+                CreateEmbed::new()
+                    .title(tr(ctx, "favorites.error.title", &[]))
+                    .description(tr(ctx, "favorites.error.generic", &[]))
+                    .color(Colors::Error),
+            )
+            .await;
+        }
+    };
+
+    if uris.is_empty() {
+        return reply(
+            ctx,
+            CreateEmbed::new()
+                .title(tr(ctx, "favorites.empty.title", &[]))
+                .description(tr(ctx, "favorites.empty.description", &[]))
+                .color(Colors::Info),
+        )
+        .await;
+    }
+
+    let tracks = match manager.lookup_tracks(ctx.author().id, &uris).await {
+        Ok(tracks) => tracks,
+        Err(why) => {
+            let key = match why {
+                Error::Database(DatabaseError::NotFound) => "favorites.error.not_linked",
+                Error::Database(DatabaseError::RefreshSuspended) => "errors.refresh_suspended",
+                why => {
+                    error!("Failed to look up favorite tracks: {why}");
+
+                    "favorites.error.spotify"
+                }
+            };
+
+            return reply(
+                ctx,
+                CreateEmbed::new()
+                    .title(tr(ctx, "favorites.error.title", &[]))
+                    .description(tr(ctx, key, &[]))
+                    .color(Colors::Error),
+            )
+            .await;
+        }
+    };
+
+    // Keep the order of the favorites, tracks that no longer exist can only be removed
+    let favorites = uris
+        .iter()
+        .map(|uri| {
+            let track = tracks
+                .iter()
+                .find(|track| track.uri.as_deref() == Some(uri.as_str()));
+
+            (uri, track)
+        })
+        .collect::<Vec<_>>();
+
+    let description = favorites
+        .iter()
+        .enumerate()
+        .map(|(index, (uri, track))| match track {
+            Some(track) => format!("{}. {}", index + 1, track.display()),
+            None => format!(
+                "{}. {}",
+                index + 1,
+                tr(ctx, "favorites.list.unavailable", &[("uri", uri.as_str())])
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let queue_options = favorites
+        .iter()
+        .filter_map(|(uri, track)| Some(option(uri, (*track)?)))
+        .collect::<Vec<_>>();
+
+    let remove_options = favorites
+        .iter()
+        .map(|(uri, track)| match track {
+            Some(track) => option(uri, track),
+            None => CreateSelectMenuOption::new(truncate(uri), *uri),
+        })
+        .collect();
+
+    let queue_id = format!("favorites:queue:{}", ctx.id());
+    let remove_id = format!("favorites:remove:{}", ctx.id());
+
+    let mut components = vec![];
+    if !queue_options.is_empty() {
+        components.push(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                &queue_id,
+                CreateSelectMenuKind::String {
+                    options: queue_options,
+                },
+            )
+            .placeholder(tr(ctx, "favorites.list.queue_placeholder", &[])),
+        ));
+    }
+    components.push(CreateActionRow::SelectMenu(
+        CreateSelectMenu::new(
+            &remove_id,
+            CreateSelectMenuKind::String {
+                options: remove_options,
+            },
+        )
+        .placeholder(tr(ctx, "favorites.list.remove_placeholder", &[])),
+    ));
+
+    let message = ctx
+        .send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "favorites.list.title", &[]))
+                        .description(description)
+                        .color(Colors::Info),
+                )
+                .components(components)
+                .ephemeral(true),
+        )
+        .await?;
+
+    let Some(selection) = ComponentInteractionCollector::new(ctx.serenity_context())
+        .author_id(ctx.author().id)
+        .custom_ids(vec![queue_id.clone(), remove_id])
+        .timeout(SELECT_TIMEOUT)
+        .await
+    else {
+        message
+            .edit(ctx, CreateReply::default().components(vec![]))
+            .await?;
+
+        return Ok(());
+    };
+
+    // Adding to the queue may take a while if the device is still connecting
+    selection.defer(ctx.serenity_context()).await?;
+
+    let Some(uri) = (match &selection.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values.first().cloned(),
+        _ => None,
+    }) else {
+        return Ok(());
+    };
+
+    let name = favorites
+        .iter()
+        .find(|(favorite, _)| **favorite == uri)
+        .and_then(|(_, track)| track.map(QueueItem::display))
+        .unwrap_or_else(|| tr(ctx, "favorites.fallback_name", &[]));
+
+    let embed = if selection.data.custom_id == queue_id {
+        match SpotifyUri::parse(&uri).and_then(|uri| uri.playable()) {
+            Some(playable) => match manager.enqueue(guild, playable).await {
+                Ok(()) => CreateEmbed::new()
+                    .title(tr(ctx, "play.added.title", &[]))
+                    .description(tr(ctx, "favorites.added.description", &[("name", &name)]))
+                    .color(Colors::Success),
+                Err(why) => super::play::error_embed(ctx, why),
+            },
+            None => CreateEmbed::new()
+                .title(tr(ctx, "play.error.title", &[]))
+                .description(tr(ctx, "search.not_found", &[]))
+                .color(Colors::Error),
+        }
+    } else {
+        match db.remove_favorite(ctx.author().id.to_string(), &uri).await {
+            Ok(_) => CreateEmbed::new()
+                .title(tr(ctx, "favorites.removed.title", &[]))
+                .description(tr(ctx, "favorites.removed.description", &[("name", &name)]))
+                .color(Colors::Success),
+            Err(why) => {
+                error!("Failed to remove favorite: {why}");
+
+                CreateEmbed::new()
+                    .title(tr(ctx, "favorites.remove_error.title", &[]))
+                    .description(tr(ctx, "favorites.remove_error.description", &[]))
+                    .color(Colors::Error)
+            }
+        }
+    };
+
+    message
+        .edit(ctx, CreateReply::default().embed(embed).components(vec![]))
+        .await?;
+
+    Ok(())
+}
+
+fn option(uri: &str, track: &QueueItem) -> CreateSelectMenuOption {
+    CreateSelectMenuOption::new(truncate(&track.name), uri)
+        .description(truncate(&track.artists.join(", ")))
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= OPTION_TEXT_LIMIT {
+        return text.to_string();
+    }
+
+    let mut truncated = text.chars().take(OPTION_TEXT_LIMIT - 1).collect::<String>();
+    truncated.push('…');
+
+    truncated
+}
+
+async fn reply(ctx: Context<'_>, embed: CreateEmbed) -> Result<()> {
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -4,16 +4,20 @@ use anyhow::Result;
 use log::error;
 use poise::CreateReply;
 use serenity::all::{
-    Channel, ChannelId, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, UserId,
+    ButtonStyle, Channel, ChannelId, ComponentInteractionCollector, CreateActionRow, CreateButton,
+    CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, UserId,
 };
 use spoticord_config;
 use spoticord_database::error::DatabaseError;
-use spoticord_session::manager::SessionQuery;
+use spoticord_session::{error::Error, manager::SessionQuery};
 use spoticord_utils::discord::Colors;
 
-use crate::bot::Context;
+use crate::{bot::Context, i18n::tr};
 
-/// Join the current voice channel
+/// How long the offer to resume saved playback stays available
+const RESUME_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Join your current voice channel, without starting playback
 #[poise::command(slash_command, guild_only)]
 pub async fn join(ctx: Context<'_>) -> Result<()> {
     let guild: serenity::all::GuildId = ctx.guild_id().expect("poise lied to me");
@@ -30,8 +34,8 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("An error occured")
-                        .description("This server hasn't been cached yet?")
+                        .title(tr(ctx, "join.error.generic.title", &[]))
+                        .description(tr(ctx, "join.error.not_cached", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -41,51 +45,73 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
         return Ok(());
     };
 
-    let channel: ChannelId = spoticord_config::voice_channel_id();
-
-    // Validate the channel exists in the guild
-    if let Ok(Channel::Guild(guild_channel)) = channel.to_channel(ctx).await {
-        if guild_channel.guild_id != guild.id {
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("Invalid configuration")
-                            .description(
-                                "Configured voice channel ID does not belong to this server.",
+    // Prefer the voice channel the user is currently in, and fall back on the configured channel
+    let user_channel = guild
+        .voice_states
+        .get(&ctx.author().id)
+        .and_then(|state| state.channel_id);
+
+    let channel: ChannelId = match user_channel {
+        Some(channel) => channel,
+        None => {
+            let Some(channel) = spoticord_config::voice_channel_id() else {
+                ctx.send(
+                    CreateReply::default()
+                        .embed(
+                            CreateEmbed::new()
+                                .title(tr(ctx, "join.error.not_in_voice.title", &[]))
+                                .description(tr(ctx, "join.error.not_in_voice.description", &[]))
+                                .color(Colors::Error),
+                        )
+                        .ephemeral(true),
+                )
+                .await?;
+
+                return Ok(());
+            };
+
+            // Validate the configured channel exists in the guild
+            if let Ok(Channel::Guild(guild_channel)) = channel.to_channel(ctx).await {
+                if guild_channel.guild_id != guild.id {
+                    ctx.send(
+                        CreateReply::default()
+                            .embed(
+                                CreateEmbed::new()
+                                    .title(tr(ctx, "join.error.config.title", &[]))
+                                    .description(tr(ctx, "join.error.config.other_server", &[]))
+                                    .color(Colors::Error),
                             )
-                            .color(Colors::Error),
+                            .ephemeral(true),
                     )
-                    .ephemeral(true),
-            )
-            .await?;
-            return Ok(());
-        }
-    } else {
-        ctx.send(
-            CreateReply::default()
-                .embed(
-                    CreateEmbed::new()
-                        .title("Invalid configuration")
-                        .description("Configured voice channel ID is not a valid voice channel.")
-                        .color(Colors::Error),
+                    .await?;
+                    return Ok(());
+                }
+            } else {
+                ctx.send(
+                    CreateReply::default()
+                        .embed(
+                            CreateEmbed::new()
+                                .title(tr(ctx, "join.error.config.title", &[]))
+                                .description(tr(ctx, "join.error.config.not_voice", &[]))
+                                .color(Colors::Error),
+                        )
+                        .ephemeral(true),
                 )
-                .ephemeral(true),
-        )
-        .await?;
-        return Ok(());
-    }
+                .await?;
+                return Ok(());
+            }
+
+            channel
+        }
+    };
 
     if !has_voice_permissions(ctx, channel).await? {
         ctx.send(
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Cannot join voice channel")
-                        .description(
-                            "The voice channel you are in is not available.
-                                    I might not have the permissions to see this channel.",
-                        )
+                        .title(tr(ctx, "join.error.cannot_join.title", &[]))
+                        .description(tr(ctx, "join.error.cannot_join.voice", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -100,10 +126,8 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Cannot join voice channel")
-                        .description(
-                            "I do not have permissions to send messages / links in this text channel.",
-                        )
+                        .title(tr(ctx, "join.error.cannot_join.title", &[]))
+                        .description(tr(ctx, "join.error.cannot_join.text", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -113,20 +137,31 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
         return Ok(());
     }
 
-    // Check whether the user has linked their Spotify account
-    if let Err(DatabaseError::NotFound) = manager
+    // Check whether the user has linked their Spotify account, and don't continue if that can't be checked
+    let account_error = match manager
         .database()
-        .get_account(ctx.author().id.to_string())
+        .account_exists(ctx.author().id.to_string())
         .await
     {
+        Ok(true) => None,
+        Ok(false) => Some((
+            "join.error.no_account.title",
+            "join.error.no_account.description",
+        )),
+        Err(why) => {
+            error!("Failed to check whether the user has linked an account: {why}");
+
+            Some(("join.error.create.title", "join.error.create.generic"))
+        }
+    };
+
+    if let Some((title, description)) = account_error {
         ctx.send(
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("No Spotify account")
-                        .description(
-                            "You need to link your Spotify account to Spoticord before being able to use it.\nUse the `/link` command to link your account.",
-                        )
+                        .title(tr(ctx, title, &[]))
+                        .description(tr(ctx, description, &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -146,8 +181,8 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
                 CreateReply::default()
                     .embed(
                         CreateEmbed::new()
-                            .title("Spoticord is busy")
-                            .description("Spoticord is already being used in this server.")
+                            .title(tr(ctx, "join.error.busy.title", &[]))
+                            .description(tr(ctx, "join.error.busy.description", &[]))
                             .color(Colors::Error),
                     )
                     .ephemeral(true),
@@ -166,11 +201,11 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("You are already using Spoticord")
-                        .description(format!(
-                            "You are already using Spoticord in `{}`\n\n\
-                            Stop playing in that server first before starting a new session.",
-                            spoticord_utils::discord::escape(server_name)
+                        .title(tr(ctx, "join.error.other_server.title", &[]))
+                        .description(tr(
+                            ctx,
+                            "join.error.other_server.description",
+                            &[("server", &spoticord_utils::discord::escape(server_name))],
                         ))
                         .color(Colors::Error),
                 )
@@ -197,14 +232,22 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
         if let Err(why) = session.reactivate(ctx.author().id).await {
             error!("Failed to reactivate session: {why}");
 
+            let description = match why.downcast_ref::<Error>() {
+                Some(Error::Database(DatabaseError::RefreshSuspended)) => {
+                    tr(ctx, "errors.refresh_suspended", &[])
+                }
+                Some(Error::Database(DatabaseError::NotFound)) => {
+                    tr(ctx, "join.error.no_account.description", &[])
+                }
+                _ => tr(ctx, "join.error.reactivate.description", &[]),
+            };
+
             ctx.send(
                 CreateReply::default()
                     .embed(
                         CreateEmbed::new()
-                            .title("Failed to reactivate session")
-                            .description(
-                                "An error occured whilst trying to reactivate the session. Please try again.",
-                            )
+                            .title(tr(ctx, "join.error.reactivate.title", &[]))
+                            .description(description)
                             .color(Colors::Error),
                     )
                     .ephemeral(true),
@@ -225,17 +268,22 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
     {
         error!("Failed to create session: {why}");
 
-        let description = if matches!(why, spoticord_session::error::Error::AuthenticationFailed) {
-            "Unable to authenticate with Spotify. Did you change your password?\n\nThe broken credentials used have been deleted.\n\nYou might need to relink your account using `/link`."
-        } else {
-            "An error occured whilst trying to create a session. Please try again."
+        let description = match why {
+            Error::AuthenticationFailed => tr(ctx, "join.error.create.authentication", &[]),
+            Error::Database(DatabaseError::RefreshSuspended) => {
+                tr(ctx, "errors.refresh_suspended", &[])
+            }
+            Error::Database(DatabaseError::NotFound) => {
+                tr(ctx, "join.error.no_account.description", &[])
+            }
+            _ => tr(ctx, "join.error.create.generic", &[]),
         };
 
         ctx.send(
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Failed to create session")
+                        .title(tr(ctx, "join.error.create.title", &[]))
                         .description(description)
                         .color(Colors::Error),
                 )
@@ -246,21 +294,92 @@ pub async fn join(ctx: Context<'_>) -> Result<()> {
         return Ok(());
     }
 
-    ctx.send(
-        CreateReply::default().embed(
+    // Playback that was lost when the owner's previous session ended, e.g. because their token expired
+    let saved = match manager
+        .database()
+        .take_playback(guild.id.get(), ctx.author().id.get())
+        .await
+    {
+        Ok(saved) => saved,
+        Err(why) => {
+            error!("Failed to retrieve saved playback: {why}");
+            None
+        }
+    };
+
+    let offer_resume = saved.is_some() && !spoticord_config::auto_resume();
+    let button_id = format!("resume:{}", ctx.id());
+
+    let mut reply = CreateReply::default().embed(
+        CreateEmbed::new()
+            .author(
+                CreateEmbedAuthor::new(tr(ctx, "join.success.title", &[]))
+                    .icon_url("https://spoticord.com/speaker.png"),
+            )
+            .description(tr(
+                ctx,
+                "join.success.description",
+                &[("channel", &channel.to_string())],
+            ))
+            .footer(CreateEmbedFooter::new(tr(ctx, "join.success.footer", &[])))
+            .color(Colors::Info),
+    );
+
+    if offer_resume {
+        reply = reply.components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
+            &button_id,
+        )
+        .label(tr(ctx, "join.resume.button", &[]))
+        .style(ButtonStyle::Primary)])]);
+    }
+
+    let reply = ctx.send(reply).await?;
+
+    let Some((uri, position)) = saved else {
+        return Ok(());
+    };
+
+    if offer_resume {
+        let press = ComponentInteractionCollector::new(ctx.serenity_context())
+            .author_id(ctx.author().id)
+            .custom_ids(vec![button_id])
+            .timeout(RESUME_TIMEOUT)
+            .await;
+
+        reply
+            .edit(ctx, CreateReply::default().components(vec![]))
+            .await?;
+
+        let Some(press) = press else {
+            return Ok(());
+        };
+
+        press.defer(ctx.serenity_context()).await?;
+    }
+
+    let embed = match manager
+        .resume_saved_playback(guild.id, &uri, position)
+        .await
+    {
+        Ok(()) => CreateEmbed::new()
+            .title(tr(ctx, "join.resume.success.title", &[]))
+            .description(tr(ctx, "join.resume.success.description", &[]))
+            .color(Colors::Success),
+        Err(Error::TrackUnavailable | Error::EpisodeUnavailable) => CreateEmbed::new()
+            .title(tr(ctx, "join.resume.error.title", &[]))
+            .description(tr(ctx, "join.resume.error.unavailable", &[]))
+            .color(Colors::Warning),
+        Err(why) => {
+            error!("Failed to resume playback: {why}");
+
             CreateEmbed::new()
-                .author(
-                    CreateEmbedAuthor::new("Connected to voice channel")
-                        .icon_url("https://spoticord.com/speaker.png"),
-                )
-                .description(format!("Come listen along in <#{}>", channel))
-                .footer(CreateEmbedFooter::new(
-                    "You must manually select your device in Spotify",
-                ))
-                .color(Colors::Info),
-        ),
-    )
-    .await?;
+                .title(tr(ctx, "join.resume.error.title", &[]))
+                .description(tr(ctx, "join.resume.error.generic", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
 
     Ok(())
 }
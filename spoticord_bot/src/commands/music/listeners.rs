@@ -0,0 +1,115 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_utils::discord::{escape, Colors};
+
+use crate::{bot::Context, i18n::tr};
+
+/// The maximum amount of listeners shown, to stay within the embed description limit
+const MAX_LISTENERS: usize = 20;
+
+/// See what other members of this server are listening to with Spoticord
+#[poise::command(slash_command, guild_only)]
+pub async fn listeners(ctx: Context<'_>) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    ctx.defer_ephemeral().await?;
+
+    // Only members of this server are shown, wherever they are listening
+    let mut states = vec![];
+    for state in manager.session_states().await {
+        if guild.member(ctx, state.owner).await.is_ok() {
+            states.push(state);
+        }
+    }
+
+    let owners = states
+        .iter()
+        .map(|state| state.owner.to_string())
+        .collect::<Vec<_>>();
+
+    let sharing = if owners.is_empty() {
+        vec![]
+    } else {
+        match manager.database().filter_sharing_users(owners).await {
+            Ok(sharing) => sharing,
+            Err(why) => {
+                error!("Failed to retrieve activity sharing settings: {why}");
+
+                ctx.send(
+                    CreateReply::default()
+                        .embed(
+                            CreateEmbed::new()
+                                .title(tr(ctx, "listeners.error.title", &[]))
+                                .description(tr(ctx, "listeners.error.generic", &[]))
+                                .color(Colors::Error),
+                        )
+                        .ephemeral(true),
+                )
+                .await?;
+
+                return Ok(());
+            }
+        }
+    };
+
+    let lines = states
+        .into_iter()
+        .filter(|state| sharing.contains(&state.owner.to_string()))
+        .take(MAX_LISTENERS)
+        .map(|state| match state.playback {
+            Some(info) => {
+                let by = info
+                    .artists()
+                    .map(|artists| {
+                        let artists = artists
+                            .iter()
+                            .map(|artist| escape(&artist.name))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        tr(ctx, "listeners.by", &[("artists", &artists)])
+                    })
+                    .or_else(|| {
+                        info.show_name()
+                            .map(|show| tr(ctx, "listeners.on", &[("show", &escape(show))]))
+                    })
+                    .unwrap_or_default();
+
+                tr(
+                    ctx,
+                    "listeners.listening",
+                    &[
+                        ("user", &state.owner.to_string()),
+                        ("name", &escape(info.name())),
+                        ("url", &info.url()),
+                        ("by", &by),
+                    ],
+                )
+            }
+            None => tr(ctx, "listeners.idle", &[("user", &state.owner.to_string())]),
+        })
+        .collect::<Vec<_>>();
+
+    let description = if lines.is_empty() {
+        tr(ctx, "listeners.empty", &[])
+    } else {
+        lines.join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .title(tr(ctx, "listeners.title", &[]))
+                    .description(description)
+                    .color(Colors::Info),
+            )
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -4,7 +4,7 @@ use serenity::all::CreateEmbed;
 use spoticord_session::manager::SessionQuery;
 use spoticord_utils::discord::Colors;
 
-use crate::bot::Context;
+use crate::{bot::Context, i18n::tr};
 
 /// Show the lyrics of the current song that is being played
 #[poise::command(slash_command, guild_only)]
@@ -17,8 +17,8 @@ pub async fn lyrics(ctx: Context<'_>) -> Result<()> {
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Cannot get lyrics")
-                        .description("I'm currently not playing any music in this server.")
+                        .title(tr(ctx, "lyrics.error.title", &[]))
+                        .description(tr(ctx, "lyrics.error.no_session", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
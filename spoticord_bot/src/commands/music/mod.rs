@@ -1,11 +1,37 @@
 mod disconnect;
+mod favorite;
+mod favorites;
 mod join;
+mod listeners;
 mod lyrics;
+mod move_channel;
+mod nowplaying;
+mod pause;
+mod play;
 mod playing;
+mod previous;
+mod queue;
+mod replay;
+mod search;
+mod skipto;
 mod stop;
+mod transfer;
 
 pub use disconnect::*;
+pub use favorite::*;
+pub use favorites::*;
 pub use join::*;
+pub use listeners::*;
 pub use lyrics::*;
+pub use move_channel::*;
+pub use nowplaying::*;
+pub use pause::*;
+pub use play::*;
 pub use playing::*;
+pub use previous::*;
+pub use queue::*;
+pub use replay::*;
+pub use search::*;
+pub use skipto::*;
 pub use stop::*;
+pub use transfer::*;
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{ChannelType, CreateEmbed, GuildChannel};
+use spoticord_session::{error::Error, manager::SessionQuery};
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Move the bot to another voice channel, without interrupting playback
+#[poise::command(slash_command, guild_only, rename = "move")]
+pub async fn move_channel(
+    ctx: Context<'_>,
+
+    #[description = "The voice channel to move to"]
+    #[channel_types("Voice", "Stage")]
+    channel: GuildChannel,
+) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    let Some(session) = manager.get_session(SessionQuery::Guild(guild)) else {
+        return reply_error(ctx, &tr(ctx, "move.error.not_connected", &[])).await;
+    };
+
+    if session.active().await? && session.owner().await? != ctx.author().id {
+        return reply_error(ctx, &tr(ctx, "move.error.not_host", &[])).await;
+    }
+
+    if channel.guild_id != guild || !matches!(channel.kind, ChannelType::Voice | ChannelType::Stage)
+    {
+        return reply_error(ctx, &tr(ctx, "move.error.invalid_channel", &[])).await;
+    }
+
+    let bot_id = ctx.cache().current_user().id;
+    let can_join = channel
+        .permissions_for_user(ctx.cache(), bot_id)
+        .is_ok_and(|permissions| permissions.connect() && permissions.speak());
+
+    if !can_join {
+        return reply_error(
+            ctx,
+            &tr(
+                ctx,
+                "move.error.permissions",
+                &[("channel", &channel.id.to_string())],
+            ),
+        )
+        .await;
+    }
+
+    ctx.defer().await?;
+
+    match manager.move_to(guild, channel.id).await {
+        Ok(()) => {
+            ctx.send(
+                CreateReply::default().embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "move.success.title", &[]))
+                        .description(tr(
+                            ctx,
+                            "move.success.description",
+                            &[("channel", &channel.id.to_string())],
+                        ))
+                        .color(Colors::Success),
+                ),
+            )
+            .await?;
+        }
+        Err(Error::NoSession) => {
+            reply_error(ctx, &tr(ctx, "move.error.not_connected", &[])).await?;
+        }
+        Err(why) => {
+            error!("Failed to move session to another channel: {why}");
+
+            reply_error(ctx, &tr(ctx, "move.error.generic", &[])).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn reply_error(ctx: Context<'_>, description: &str) -> Result<()> {
+    ctx.send(
+        CreateReply::default()
+            .embed(
+                CreateEmbed::new()
+                    .title(tr(ctx, "move.error.title", &[]))
+                    .description(description)
+                    .color(Colors::Error),
+            )
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
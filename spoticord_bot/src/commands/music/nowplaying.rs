@@ -0,0 +1,125 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{
+    CreateActionRow, CreateButton, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter,
+};
+use spoticord_session::{error::Error, playback_embed::progress_bar};
+use spoticord_utils::discord::{escape, Colors};
+
+use crate::{bot::Context, i18n::tr};
+
+/// Show the track that is currently playing, with its album art and a link to Spotify
+#[poise::command(slash_command, guild_only)]
+pub async fn nowplaying(ctx: Context<'_>) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    let state = match manager.session_state(guild).await {
+        Ok(state) => state,
+        Err(why) => {
+            let description = match why {
+                Error::NoSession => tr(ctx, "nowplaying.error.no_session", &[]),
+                why => {
+                    error!("Failed to retrieve session state: {why}");
+
+                    tr(ctx, "nowplaying.error.generic", &[])
+                }
+            };
+
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .title(tr(ctx, "nowplaying.error.title", &[]))
+                            .description(description)
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    let Some(info) = state.playback else {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "nowplaying.nothing_playing.title", &[]))
+                        .description(tr(ctx, "nowplaying.nothing_playing.description", &[]))
+                        .color(Colors::Info),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    };
+
+    let mut description = String::new();
+
+    if let Some(artists) = info.artists() {
+        let artists = artists
+            .iter()
+            .map(|artist| escape(&artist.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        description += &tr(ctx, "nowplaying.artists", &[("artists", &artists)]);
+        description += "\n";
+    }
+
+    if let Some(album_name) = info.album_name() {
+        description += &tr(ctx, "nowplaying.album", &[("album", &escape(album_name))]);
+        description += "\n";
+    }
+
+    if let Some(show_name) = info.show_name() {
+        description += &tr(ctx, "nowplaying.show", &[("show", &escape(show_name))]);
+        description += "\n";
+    }
+
+    description += "\n";
+    description += &progress_bar(&info);
+
+    if let Some(bitrate) = state.bitrate {
+        description += "\n";
+        description += &tr(
+            ctx,
+            "nowplaying.bitrate",
+            &[("kbps", &(bitrate / 1000).to_string())],
+        );
+    }
+
+    let mut embed = CreateEmbed::new()
+        .author(
+            CreateEmbedAuthor::new(tr(ctx, "nowplaying.title", &[]))
+                .icon_url("https://spoticord.com/spotify-logo.png"),
+        )
+        .title(info.name())
+        .url(info.url())
+        .description(description)
+        .thumbnail(info.thumbnail())
+        .color(Colors::Info);
+
+    if let Ok(owner) = state.owner.to_user(ctx).await {
+        embed = embed.footer(
+            CreateEmbedFooter::new(owner.global_name.as_ref().unwrap_or(&owner.name))
+                .icon_url(owner.face()),
+        );
+    }
+
+    ctx.send(
+        CreateReply::default()
+            .embed(embed)
+            .components(vec![CreateActionRow::Buttons(vec![
+                CreateButton::new_link(info.url()).label(tr(ctx, "nowplaying.open", &[])),
+            ])]),
+    )
+    .await?;
+
+    Ok(())
+}
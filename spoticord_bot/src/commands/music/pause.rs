@@ -0,0 +1,77 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_session::error::Error;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Pause the music that is currently playing
+#[poise::command(slash_command, guild_only)]
+pub async fn pause(ctx: Context<'_>) -> Result<()> {
+    let guild = ctx.guild_id().expect("poise lied to me");
+    let result = ctx.data().pause(guild).await;
+
+    reply(ctx, result.map(|_| true)).await
+}
+
+/// Resume the music that is currently paused
+#[poise::command(slash_command, guild_only)]
+pub async fn resume(ctx: Context<'_>) -> Result<()> {
+    let guild = ctx.guild_id().expect("poise lied to me");
+    let result = ctx.data().resume(guild).await;
+
+    reply(ctx, result.map(|_| false)).await
+}
+
+/// Pause the music if it is playing, or resume it if it is paused
+#[poise::command(slash_command, guild_only)]
+pub async fn playpause(ctx: Context<'_>) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    let result = match manager.is_paused(guild).await {
+        Ok(true) => manager.resume(guild).await.map(|_| false),
+        Ok(false) => manager.pause(guild).await.map(|_| true),
+        Err(why) => Err(why),
+    };
+
+    reply(ctx, result).await
+}
+
+/// Reply with the resulting playback state, where `Ok(true)` means playback is now paused
+async fn reply(ctx: Context<'_>, result: Result<bool, Error>) -> Result<()> {
+    let ephemeral = result.is_err();
+    let embed = match result {
+        Ok(true) => CreateEmbed::new()
+            .title(tr(ctx, "pause.paused.title", &[]))
+            .description(tr(ctx, "pause.paused.description", &[]))
+            .color(Colors::Info),
+        Ok(false) => CreateEmbed::new()
+            .title(tr(ctx, "pause.resumed.title", &[]))
+            .description(tr(ctx, "pause.resumed.description", &[]))
+            .color(Colors::Success),
+        Err(Error::NoSession) => CreateEmbed::new()
+            .title(tr(ctx, "pause.error.title", &[]))
+            .description(tr(ctx, "pause.error.no_session", &[]))
+            .color(Colors::Error),
+        Err(Error::NothingPlaying) => CreateEmbed::new()
+            .title(tr(ctx, "pause.error.title", &[]))
+            .description(tr(ctx, "pause.error.nothing_playing", &[]))
+            .color(Colors::Error),
+        Err(why) => {
+            error!("Failed to change playback state: {why}");
+
+            CreateEmbed::new()
+                .title(tr(ctx, "pause.error.title", &[]))
+                .description(tr(ctx, "pause.error.generic", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(ephemeral))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use anyhow::Result;
+use log::error;
+use poise::{ChoiceParameter, CreateReply};
+use serenity::all::CreateEmbed;
+use spoticord_config::PlayBehavior;
+use spoticord_database::error::DatabaseError;
+use spoticord_session::{
+    error::{Error, PlaybackError},
+    uri::SpotifyUri,
+};
+use spoticord_utils::discord::Colors;
+use tokio::sync::mpsc;
+
+use crate::{bot::Context, i18n::tr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum PlayMode {
+    #[name = "Add to the end of the queue"]
+    Append,
+
+    #[name = "Play next"]
+    Next,
+
+    #[name = "Play now"]
+    Now,
+}
+
+/// Add a Spotify track, podcast episode or playlist to the queue
+#[poise::command(slash_command, guild_only)]
+pub async fn play(
+    ctx: Context<'_>,
+
+    #[description = "A Spotify track, episode, podcast or playlist link"] url: String,
+    #[description = "Where the item should be added, defaults to the end of the queue unless configured otherwise"]
+    mode: Option<PlayMode>,
+) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    let Some(uri) = SpotifyUri::parse(&url) else {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "play.invalid.title", &[]))
+                        .description(tr(ctx, "play.invalid.description", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    };
+
+    let mode = mode.unwrap_or_else(|| match spoticord_config::play_default_behavior() {
+        PlayBehavior::Replace if !matches!(uri, SpotifyUri::Playlist(_)) => PlayMode::Now,
+        _ => PlayMode::Append,
+    });
+
+    if matches!(uri, SpotifyUri::Playlist(_)) && mode != PlayMode::Append {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "play.error.title", &[]))
+                        .description(tr(ctx, "play.playlist_mode", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+
+    let (item, kind) = match &uri {
+        SpotifyUri::Playlist(playlist) => {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let reply = ctx
+                .send(CreateReply::default().embed(progress_embed(ctx, 0, None)))
+                .await?;
+
+            let import = manager.enqueue_playlist(guild, playlist.clone(), move |done, total| {
+                _ = tx.send((done, total));
+            });
+            tokio::pin!(import);
+
+            let result = loop {
+                tokio::select! {
+                    result = &mut import => break result,
+                    Some((done, total)) = rx.recv() => {
+                        _ = reply
+                            .edit(ctx, CreateReply::default().embed(progress_embed(ctx, done, Some(total))))
+                            .await;
+                    }
+                }
+            };
+
+            let embed = match result {
+                Ok(import) => {
+                    let mut lines = vec![tr(
+                        ctx,
+                        "play.playlist.added",
+                        &[("count", &import.added.to_string())],
+                    )];
+
+                    if import.skipped != 0 {
+                        lines.push(tr(
+                            ctx,
+                            "play.playlist.skipped",
+                            &[("count", &import.skipped.to_string())],
+                        ));
+                    }
+
+                    if import.duplicates != 0 {
+                        lines.push(tr(
+                            ctx,
+                            "play.playlist.duplicates",
+                            &[("count", &import.duplicates.to_string())],
+                        ));
+                    }
+
+                    if import.queue_full {
+                        lines.push(tr(ctx, "play.playlist.queue_full", &[]));
+                    } else if import.truncated {
+                        lines.push(tr(
+                            ctx,
+                            "play.playlist.truncated",
+                            &[(
+                                "limit",
+                                &spoticord_config::playlist_import_limit().to_string(),
+                            )],
+                        ));
+                    }
+
+                    CreateEmbed::new()
+                        .title(tr(ctx, "play.playlist.title", &[]))
+                        .description(lines.join("\n"))
+                        .color(Colors::Success)
+                }
+                Err(why) => error_embed(ctx, why),
+            };
+
+            reply.edit(ctx, CreateReply::default().embed(embed)).await?;
+
+            return Ok(());
+        }
+        SpotifyUri::Show(show) => match manager.latest_episode(guild, show.clone()).await {
+            Ok(episode) => (episode, "show"),
+            Err(why) => {
+                ctx.send(CreateReply::default().embed(error_embed(ctx, why)))
+                    .await?;
+
+                return Ok(());
+            }
+        },
+        SpotifyUri::Episode(_) => (uri.playable().expect("episodes are playable"), "episode"),
+        SpotifyUri::Track(_) => (uri.playable().expect("tracks are playable"), "track"),
+    };
+
+    let embed = match mode {
+        PlayMode::Append => match manager.enqueue(guild, item).await {
+            Ok(()) => CreateEmbed::new()
+                .title(tr(ctx, "play.added.title", &[]))
+                .description(tr(ctx, &format!("play.{kind}.appended"), &[]))
+                .color(Colors::Success),
+            Err(why) => error_embed(ctx, why),
+        },
+        PlayMode::Next => match manager.enqueue_next(guild, item).await {
+            Ok(position) => CreateEmbed::new()
+                .title(tr(ctx, "play.added.title", &[]))
+                .description(match position {
+                    0 => tr(ctx, &format!("play.{kind}.now_idle"), &[]),
+                    1 => tr(ctx, &format!("play.{kind}.next"), &[]),
+                    position => tr(
+                        ctx,
+                        &format!("play.{kind}.position"),
+                        &[("position", &position.to_string())],
+                    ),
+                })
+                .color(Colors::Success),
+            Err(why) => error_embed(ctx, why),
+        },
+        PlayMode::Now => match manager.play_now(guild, item).await {
+            Ok(()) => CreateEmbed::new()
+                .title(tr(ctx, "play.playing_now.title", &[]))
+                .description(tr(ctx, &format!("play.{kind}.now"), &[]))
+                .color(Colors::Success),
+            Err(why) => error_embed(ctx, why),
+        },
+    };
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+fn progress_embed(ctx: Context<'_>, done: usize, total: Option<usize>) -> CreateEmbed {
+    let description = match total {
+        Some(total) => tr(
+            ctx,
+            "play.progress.adding",
+            &[("done", &done.to_string()), ("total", &total.to_string())],
+        ),
+        None => tr(ctx, "play.progress.retrieving", &[]),
+    };
+
+    CreateEmbed::new()
+        .title(tr(ctx, "play.progress.title", &[]))
+        .description(description)
+        .color(Colors::Info)
+}
+
+pub(super) fn error_embed(ctx: Context<'_>, why: Error) -> CreateEmbed {
+    let key = match why {
+        Error::NoSession => "play.error.no_session",
+        Error::DeviceNotReady => "play.error.device_not_ready",
+        Error::ExplicitContent => "play.error.explicit",
+        Error::EpisodeUnavailable => "play.error.episode_unavailable",
+        Error::AlreadyQueued => "play.error.already_queued",
+        Error::QueueFull => "play.error.queue_full",
+        Error::Database(DatabaseError::RefreshSuspended) => "errors.refresh_suspended",
+        Error::Playback(PlaybackError::NoActiveDevice) => "play.error.no_active_device",
+        Error::Playback(PlaybackError::PremiumRequired) => "play.error.premium_required",
+        Error::Playback(PlaybackError::MarketRestricted) => "play.error.market_restricted",
+        Error::Playback(PlaybackError::DeviceNotControllable) => "play.error.not_controllable",
+        why => {
+            error!("Failed to add to queue: {why}");
+
+            "play.error.generic"
+        }
+    };
+
+    CreateEmbed::new()
+        .title(tr(ctx, "play.error.title", &[]))
+        .description(tr(ctx, key, &[]))
+        .color(Colors::Error)
+}
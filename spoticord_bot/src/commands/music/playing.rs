@@ -4,7 +4,7 @@ use serenity::all::CreateEmbed;
 use spoticord_session::{manager::SessionQuery, playback_embed::UpdateBehavior};
 use spoticord_utils::discord::Colors;
 
-use crate::bot::Context;
+use crate::{bot::Context, i18n::tr};
 
 /// Show details of the current song that is being played
 #[poise::command(slash_command, guild_only)]
@@ -22,8 +22,8 @@ pub async fn playing(
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Cannot display song details")
-                        .description("I'm currently not playing any music in this server.")
+                        .title(tr(ctx, "playing.error.title", &[]))
+                        .description(tr(ctx, "playing.error.no_session", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_session::error::Error;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Go back to the track that was played before the current one
+#[poise::command(slash_command, guild_only)]
+pub async fn previous(ctx: Context<'_>) -> Result<()> {
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    ctx.defer().await?;
+
+    let result = ctx.data().play_previous(guild).await;
+    let ephemeral = result.is_err();
+
+    let embed = match result {
+        Ok(()) => CreateEmbed::new()
+            .title(tr(ctx, "previous.success.title", &[]))
+            .description(tr(ctx, "previous.success.description", &[]))
+            .color(Colors::Success),
+        Err(Error::NoSession) => CreateEmbed::new()
+            .title(tr(ctx, "previous.error.title", &[]))
+            .description(tr(ctx, "previous.error.no_session", &[]))
+            .color(Colors::Error),
+        Err(Error::NoPreviousTrack) => CreateEmbed::new()
+            .title(tr(ctx, "previous.error.title", &[]))
+            .description(tr(ctx, "previous.error.no_previous", &[]))
+            .color(Colors::Error),
+        Err(why) => {
+            error!("Failed to play the previous track: {why}");
+
+            CreateEmbed::new()
+                .title(tr(ctx, "previous.error.title", &[]))
+                .description(tr(ctx, "previous.error.generic", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(ephemeral))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_session::error::Error;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Manage the upcoming items in the queue
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("clear", "remove"),
+    subcommand_required
+)]
+pub async fn queue(_ctx: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Remove every upcoming item from the queue, the current track keeps playing
+#[poise::command(slash_command, guild_only)]
+async fn clear(ctx: Context<'_>) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    ctx.defer().await?;
+
+    let embed = match manager.clear_queue(guild).await {
+        Ok(removed) => CreateEmbed::new()
+            .title(tr(ctx, "queue.clear.success.title", &[]))
+            .description(tr(
+                ctx,
+                "queue.clear.success.description",
+                &[("count", &removed.to_string())],
+            ))
+            .color(Colors::Success),
+        Err(why) => error_embed(ctx, "queue.clear.error.title", why, 0),
+    };
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Remove an item from the queue
+#[poise::command(slash_command, guild_only)]
+async fn remove(
+    ctx: Context<'_>,
+
+    #[description = "The position in the queue of the item to remove"]
+    #[min = 1]
+    position: usize,
+) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    ctx.defer().await?;
+
+    let embed = match manager.remove_from_queue(guild, position - 1).await {
+        Ok(item) => CreateEmbed::new()
+            .title(tr(ctx, "queue.remove.success.title", &[]))
+            .description(tr(
+                ctx,
+                "queue.remove.success.description",
+                &[("item", &item.display())],
+            ))
+            .color(Colors::Success),
+        Err(why) => error_embed(ctx, "queue.remove.error.title", why, position),
+    };
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+fn error_embed(ctx: Context<'_>, title: &str, why: Error, position: usize) -> CreateEmbed {
+    let description = match why {
+        Error::NoSession => tr(ctx, "queue.error.no_session", &[]),
+        Error::NothingPlaying => tr(ctx, "queue.error.nothing_playing", &[]),
+        Error::InvalidQueuePosition => tr(
+            ctx,
+            "queue.error.invalid_position",
+            &[("position", &position.to_string())],
+        ),
+        why => {
+            error!("Failed to edit the queue: {why}");
+
+            tr(ctx, "queue.error.generic", &[])
+        }
+    };
+
+    CreateEmbed::new()
+        .title(tr(ctx, title, &[]))
+        .description(description)
+        .color(Colors::Error)
+}
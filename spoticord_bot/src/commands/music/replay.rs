@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_session::error::Error;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Restart the current track from the beginning
+#[poise::command(slash_command, guild_only)]
+pub async fn replay(ctx: Context<'_>) -> Result<()> {
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    ctx.defer().await?;
+
+    let result = ctx.data().seek(guild, Duration::ZERO).await;
+    let ephemeral = result.is_err();
+
+    let embed = match result {
+        Ok(()) => CreateEmbed::new()
+            .title(tr(ctx, "replay.success.title", &[]))
+            .description(tr(ctx, "replay.success.description", &[]))
+            .color(Colors::Success),
+        Err(Error::NoSession) => CreateEmbed::new()
+            .title(tr(ctx, "replay.error.title", &[]))
+            .description(tr(ctx, "replay.error.no_session", &[]))
+            .color(Colors::Error),
+        Err(Error::NothingPlaying) => CreateEmbed::new()
+            .title(tr(ctx, "replay.error.title", &[]))
+            .description(tr(ctx, "replay.error.nothing_playing", &[]))
+            .color(Colors::Error),
+        Err(why) => {
+            error!("Failed to restart track: {why}");
+
+            CreateEmbed::new()
+                .title(tr(ctx, "replay.error.title", &[]))
+                .description(tr(ctx, "replay.error.generic", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(ephemeral))
+        .await?;
+
+    Ok(())
+}
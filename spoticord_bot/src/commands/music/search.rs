@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{
+    ComponentInteractionCollector, ComponentInteractionDataKind, CreateActionRow, CreateEmbed,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use spoticord_database::error::DatabaseError;
+use spoticord_session::{error::Error, uri::SpotifyUri};
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// The maximum amount of results that are shown in the select menu
+const SEARCH_LIMIT: u32 = 10;
+
+/// How long the user has to pick one of the results
+const SELECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Discord's maximum length for select menu option labels and descriptions
+const OPTION_TEXT_LIMIT: usize = 100;
+
+/// Search Spotify for a track and add it to the queue
+#[poise::command(slash_command, guild_only)]
+pub async fn search(
+    ctx: Context<'_>,
+
+    #[description = "What to search for"] query: String,
+) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    ctx.defer_ephemeral().await?;
+
+    let results = match manager
+        .search_tracks(ctx.author().id, &query, SEARCH_LIMIT)
+        .await
+    {
+        Ok(results) => results,
+        Err(why) => {
+            let key = match why {
+                Error::Database(DatabaseError::NotFound) => "search.error.not_linked",
+                Error::Database(DatabaseError::RefreshSuspended) => "errors.refresh_suspended",
+                why => {
+                    error!("Failed to search Spotify: {why}");
+
+                    "search.error.generic"
+                }
+            };
+
+            ctx.send(
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .title(tr(ctx, "search.error.title", &[]))
+                            .description(tr(ctx, key, &[]))
+                            .color(Colors::Error),
+                    )
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    if results.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "search.empty.title", &[]))
+                        .description(tr(ctx, "search.empty.description", &[]))
+                        .color(Colors::Info),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    let options = results
+        .iter()
+        .filter_map(|item| {
+            let uri = item.uri.as_ref()?;
+
+            Some(
+                CreateSelectMenuOption::new(truncate(&item.name), uri)
+                    .description(truncate(&item.artists.join(", "))),
+            )
+        })
+        .collect();
+
+    let listing = results
+        .iter()
+        .enumerate()
+        .map(|(index, item)| format!("{}. {}", index + 1, item.display()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let menu_id = format!("search:{}", ctx.id());
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "search.results.title", &[]))
+                        .description(tr(
+                            ctx,
+                            "search.results.description",
+                            &[("results", &listing)],
+                        ))
+                        .color(Colors::Info),
+                )
+                .components(vec![CreateActionRow::SelectMenu(
+                    CreateSelectMenu::new(&menu_id, CreateSelectMenuKind::String { options })
+                        .placeholder(tr(ctx, "search.results.placeholder", &[])),
+                )])
+                .ephemeral(true),
+        )
+        .await?;
+
+    let Some(selection) = ComponentInteractionCollector::new(ctx.serenity_context())
+        .author_id(ctx.author().id)
+        .custom_ids(vec![menu_id])
+        .timeout(SELECT_TIMEOUT)
+        .await
+    else {
+        reply
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .embed(
+                        CreateEmbed::new()
+                            .title(tr(ctx, "search.expired.title", &[]))
+                            .description(tr(ctx, "search.expired.description", &[]))
+                            .color(Colors::Info),
+                    )
+                    .components(vec![]),
+            )
+            .await?;
+
+        return Ok(());
+    };
+
+    // Adding to the queue may take a while if the device is still connecting
+    selection.defer(ctx.serenity_context()).await?;
+
+    let uri = match &selection.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values.first().cloned(),
+        _ => None,
+    };
+
+    let item = uri
+        .as_deref()
+        .and_then(|uri| results.iter().find(|item| item.uri.as_deref() == Some(uri)));
+
+    let playable = uri
+        .as_deref()
+        .and_then(SpotifyUri::parse)
+        .and_then(|uri| uri.playable());
+
+    let embed = match (item, playable) {
+        (Some(item), Some(playable)) => match manager.enqueue(guild, playable).await {
+            Ok(()) => CreateEmbed::new()
+                .title(tr(ctx, "play.added.title", &[]))
+                .description(tr(
+                    ctx,
+                    "search.added.description",
+                    &[("name", &item.display())],
+                ))
+                .color(Colors::Success),
+            Err(why) => super::play::error_embed(ctx, why),
+        },
+        _ => CreateEmbed::new()
+            .title(tr(ctx, "play.error.title", &[]))
+            .description(tr(ctx, "search.not_found", &[]))
+            .color(Colors::Error),
+    };
+
+    reply
+        .edit(ctx, CreateReply::default().embed(embed).components(vec![]))
+        .await?;
+
+    Ok(())
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= OPTION_TEXT_LIMIT {
+        return text.to_string();
+    }
+
+    let mut truncated = text.chars().take(OPTION_TEXT_LIMIT - 1).collect::<String>();
+    truncated.push('…');
+
+    truncated
+}
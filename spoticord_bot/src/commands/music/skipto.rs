@@ -0,0 +1,58 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use spoticord_session::error::Error;
+use spoticord_utils::discord::Colors;
+
+use crate::{bot::Context, i18n::tr};
+
+/// Skip ahead to a specific position in the queue
+#[poise::command(slash_command, guild_only)]
+pub async fn skipto(
+    ctx: Context<'_>,
+
+    #[description = "The position in the queue to skip to"]
+    #[min = 1]
+    position: usize,
+) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    ctx.defer().await?;
+
+    let embed = match manager.skip_to(guild, position - 1).await {
+        Ok(item) => CreateEmbed::new()
+            .title(tr(ctx, "skipto.success.title", &[]))
+            .description(tr(
+                ctx,
+                "skipto.success.description",
+                &[("item", &item.display())],
+            ))
+            .color(Colors::Success),
+        Err(Error::NoSession) => CreateEmbed::new()
+            .title(tr(ctx, "skipto.error.title", &[]))
+            .description(tr(ctx, "skipto.error.no_session", &[]))
+            .color(Colors::Error),
+        Err(Error::InvalidQueuePosition) => CreateEmbed::new()
+            .title(tr(ctx, "skipto.error.title", &[]))
+            .description(tr(
+                ctx,
+                "skipto.error.invalid_position",
+                &[("position", &position.to_string())],
+            ))
+            .color(Colors::Error),
+        Err(why) => {
+            error!("Failed to skip ahead in queue: {why}");
+
+            CreateEmbed::new()
+                .title(tr(ctx, "skipto.error.title", &[]))
+                .description(tr(ctx, "skipto.error.generic", &[]))
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
@@ -1,11 +1,13 @@
 use anyhow::Error;
+use log::error;
 use poise::CreateReply;
 use serenity::all::CreateEmbed;
-use spoticord_session::manager::SessionQuery;
+use spoticord_session::{error::Error as SessionError, manager::SessionQuery};
 use spoticord_utils::discord::Colors;
 
-use crate::bot::Context;
+use crate::{bot::Context, i18n::tr};
 
+/// Stop playing music, but stay in the voice channel
 #[poise::command(slash_command, guild_only)]
 pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
     let manager = ctx.data();
@@ -16,8 +18,8 @@ pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Cannot stop playback")
-                        .description("I'm currently not connected to any voice channel.")
+                        .title(tr(ctx, "stop.error.title", &[]))
+                        .description(tr(ctx, "stop.error.not_connected", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -32,8 +34,8 @@ pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
             CreateReply::default()
                 .embed(
                     CreateEmbed::new()
-                        .title("Cannot stop playback")
-                        .description("Only the host may stop playback.")
+                        .title(tr(ctx, "stop.error.title", &[]))
+                        .description(tr(ctx, "stop.error.not_host", &[]))
                         .color(Colors::Error),
                 )
                 .ephemeral(true),
@@ -43,13 +45,36 @@ pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
         return Ok(());
     }
 
-    session.shutdown_player().await;
+    if let Err(why) = manager.stop(guild).await {
+        let description = match why {
+            SessionError::NoSession => tr(ctx, "stop.error.not_playing", &[]),
+            why => {
+                error!("Failed to stop playback: {why}");
+
+                tr(ctx, "stop.error.generic", &[])
+            }
+        };
+
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "stop.error.title", &[]))
+                        .description(description)
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
 
     ctx.send(
         CreateReply::default().embed(
             CreateEmbed::new()
-                .title("Stopped playback")
-                .description("I have stopped playing for now. To resume playback, please run the /join command again.")
+                .title(tr(ctx, "stop.success.title", &[]))
+                .description(tr(ctx, "stop.success.description", &[]))
                 .color(Colors::Info),
         ),
     )
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, User};
+use spoticord_database::error::DatabaseError;
+use spoticord_session::error::Error;
+use spoticord_utils::discord::Colors;
+
+use crate::{
+    bot::{
+        permissions::{caller_tier, Tier},
+        Context,
+    },
+    i18n::tr,
+};
+
+/// Make another user the owner of the current session
+#[poise::command(slash_command, guild_only)]
+pub async fn transfer(
+    ctx: Context<'_>,
+
+    #[description = "The user that should become the owner"] user: User,
+) -> Result<()> {
+    let manager = ctx.data();
+    let guild = ctx.guild_id().expect("poise lied to me");
+
+    let Some(owner) = manager.session_owner(guild).await else {
+        reply(ctx, Err(Error::NoSession), &user).await?;
+        return Ok(());
+    };
+
+    if owner != ctx.author().id && caller_tier(ctx).await < Tier::Admin {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "transfer.error.title", &[]))
+                        .description(tr(ctx, "transfer.error.not_allowed", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    if user.bot || user.id == owner {
+        ctx.send(
+            CreateReply::default()
+                .embed(
+                    CreateEmbed::new()
+                        .title(tr(ctx, "transfer.error.title", &[]))
+                        .description(tr(ctx, "transfer.error.invalid_user", &[]))
+                        .color(Colors::Error),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+
+    let result = manager.transfer_ownership(guild, user.id).await;
+    reply(ctx, result, &user).await
+}
+
+async fn reply(ctx: Context<'_>, result: Result<(), Error>, user: &User) -> Result<()> {
+    let ephemeral = result.is_err();
+    let user_id = user.id.to_string();
+    let embed = match result {
+        Ok(()) => CreateEmbed::new()
+            .title(tr(ctx, "transfer.success.title", &[]))
+            .description(tr(
+                ctx,
+                "transfer.success.description",
+                &[("user", &user_id)],
+            ))
+            .color(Colors::Success),
+        Err(why) => {
+            let description = match why {
+                Error::NoSession => tr(ctx, "transfer.error.no_session", &[]),
+                Error::OwnerBusy => tr(ctx, "transfer.error.owner_busy", &[("user", &user_id)]),
+                Error::Database(DatabaseError::NotFound) => {
+                    tr(ctx, "transfer.error.not_linked", &[("user", &user_id)])
+                }
+                why => {
+                    error!("Failed to transfer session ownership: {why}");
+
+                    tr(ctx, "transfer.error.generic", &[])
+                }
+            };
+
+            CreateEmbed::new()
+                .title(tr(ctx, "transfer.error.title", &[]))
+                .description(description)
+                .color(Colors::Error)
+        }
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(ephemeral))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! Localized strings for command descriptions and replies.
+//!
+//! Every locale is a flat JSON map of keys to strings inside of the `locales` directory.
+//! Strings may contain `{name}` placeholders, which are filled in by [`t`].
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use crate::bot::Context;
+
+/// The locale that is used when a key is missing in the requested locale
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// All supported locales, using Discord's locale codes
+const LOCALE_FILES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US.json")),
+    ("nl", include_str!("../locales/nl.json")),
+];
+
+static LOCALES: LazyLock<HashMap<&'static str, HashMap<String, String>>> = LazyLock::new(|| {
+    LOCALE_FILES
+        .iter()
+        .map(|(locale, source)| {
+            let strings = serde_json::from_str(source)
+                .unwrap_or_else(|why| panic!("locale file {locale}.json is invalid: {why}"));
+
+            (*locale, strings)
+        })
+        .collect()
+});
+
+/// Parse all locale files, so that an invalid file is caught on startup instead of on first use
+pub fn load() {
+    LazyLock::force(&LOCALES);
+}
+
+/// Look up a localized string, falling back to English and finally the key itself
+pub fn t(locale: &str, key: &str, args: &[(&str, &str)]) -> String {
+    let language = locale.split('-').next().unwrap_or(locale);
+
+    let template = [locale, language, DEFAULT_LOCALE]
+        .into_iter()
+        .find_map(|locale| LOCALES.get(locale)?.get(key))
+        .map(String::as_str)
+        .unwrap_or(key);
+
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+/// Look up a localized string in the locale of the user that invoked a command
+pub fn tr(ctx: Context<'_>, key: &str, args: &[(&str, &str)]) -> String {
+    t(ctx.locale().unwrap_or(DEFAULT_LOCALE), key, args)
+}
+
+/// Add the description localizations of every locale to the commands and their parameters.
+///
+/// Commands use their doc comments as the English description, so only other locales are applied.
+pub fn localize_commands<U, E>(commands: &mut [poise::Command<U, E>]) {
+    for (locale, strings) in LOCALES.iter() {
+        if *locale == DEFAULT_LOCALE {
+            continue;
+        }
+
+        for command in commands.iter_mut() {
+            let prefix = format!("commands.{}", command.qualified_name);
+
+            if let Some(description) = strings.get(&format!("{prefix}.description")) {
+                command
+                    .description_localizations
+                    .insert(locale.to_string(), description.clone());
+            }
+
+            for parameter in &mut command.parameters {
+                if let Some(description) =
+                    strings.get(&format!("{prefix}.{}.description", parameter.name))
+                {
+                    parameter
+                        .description_localizations
+                        .insert(locale.to_string(), description.clone());
+                }
+            }
+        }
+    }
+}
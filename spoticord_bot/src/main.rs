@@ -1,25 +1,91 @@
 mod bot;
 mod commands;
+mod i18n;
 
 use anyhow::Context as _;
-use log::{error, info};
+use log::{error, info, warn};
 use poise::Framework;
 use serenity::all::ClientBuilder;
 use shuttle_runtime::SecretStore;
 use songbird::SerenityInit;
+use spoticord_config::TlsProvider;
 use spoticord_database::Database;
 use std::env;
 use std::result::Result::Ok;
 
+/// Secrets that are forwarded to `spoticord_config` when present, but are not required to run
+const OPTIONAL_SECRETS: &[&str] = &[
+    "GUILD_ID",
+    "DISCORD_VOICE_CHANNEL_ID",
+    "OWNER_IDS",
+    "TOKEN_ENCRYPTION_KEY",
+    "AUDIO_SOFTCLIP",
+    "PLAYLIST_IMPORT_LIMIT",
+    "MAX_QUEUE_LENGTH",
+    "PLAY_DEFAULT_BEHAVIOR",
+    "DEDUP_QUEUE",
+    "OWNER_ONLY_CONTROLS",
+    "POST_WELCOME",
+    "AUTO_RESUME",
+    "ALONE_ACTION",
+    "ALONE_GRACE_SECS",
+    "TOKEN_REFRESH_MARGIN_SECS",
+    "TOKEN_CACHE_BACKEND",
+    "REFRESH_FAILURE_ACTION",
+    "KV_URL",
+    "RUN_MIGRATIONS",
+    "DATABASE_POOL_SIZE",
+    "SLOW_QUERY_WARN_MS",
+    "DATABASE_BREAKER_THRESHOLD",
+    "NEON_COMPAT",
+    "DATABASE_APP_NAME",
+    "DIESEL_STATEMENT_CACHE_SIZE",
+    "FEATURES",
+    "COMMAND_PREFIX",
+    "DISCORD_INTENTS",
+    "METRICS_ADDR",
+    "METRICS_REFRESH_SECS",
+    "USER_TOUCH_INTERVAL_SECS",
+    "LINK_REQUEST_TTL_SECS",
+    "BOT_ACTIVITY_TYPE",
+    "SPOTIFY_API_BASE_URL",
+    "TLS_PROVIDER",
+    "HTTPS_PROXY",
+];
+
+/// Friendly names that can be used in `LOG_DIRECTIVES`, and the log targets they map to
+const LOG_TARGETS: &[(&str, &str)] = &[
+    ("bot", "spoticord_bot"),
+    ("config", "spoticord_config"),
+    ("db", "spoticord_database"),
+    ("database", "spoticord_database"),
+    ("player", "spoticord_player"),
+    ("session", "spoticord_session"),
+    ("stats", "spoticord_stats"),
+    ("utils", "spoticord_utils"),
+];
+
+/// Install the configured crypto provider as the rustls default.
+///
+/// Since multiple dependencies either enable aws-lc-rs or ring, they cause a clash, so we have to explicitly tell
+/// rustls which provider to use. This must happen before the first TLS connection is made.
+fn install_crypto_provider() {
+    let (name, provider) = match spoticord_config::tls_provider() {
+        TlsProvider::AwsLcRs => ("aws-lc-rs", rustls::crypto::aws_lc_rs::default_provider()),
+        TlsProvider::Ring => ("ring", rustls::crypto::ring::default_provider()),
+    };
+
+    if provider.install_default().is_err() {
+        warn!("A rustls crypto provider was already installed, so {name} could not be installed");
+    } else {
+        info!("Using the {name} crypto provider for TLS");
+    }
+}
+
 #[shuttle_runtime::main]
 async fn main(
     #[shuttle_runtime::Secrets] secrets: SecretStore,
 ) -> shuttle_serenity::ShuttleSerenity {
-    // Force aws-lc-rs as default crypto provider
-    // Since multiple dependencies either enable aws_lc_rs or ring, they cause a clash, so we have to
-    // explicitly tell rustls to use the aws-lc-rs provider
-    _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
-
     // Setup logging
     if std::env::var("RUST_LOG").is_err() {
         #[cfg(debug_assertions)]
@@ -29,6 +95,15 @@ async fn main(
         std::env::set_var("RUST_LOG", "spoticord=info");
     }
 
+    // Append per-crate overrides, e.g. `LOG_DIRECTIVES=db=debug,session=warn`
+    if let Some(directives) = secrets
+        .get("LOG_DIRECTIVES")
+        .or_else(|| env::var("LOG_DIRECTIVES").ok())
+    {
+        let filter = env::var("RUST_LOG").unwrap_or_default();
+        env::set_var("RUST_LOG", log_filter(&filter, &directives));
+    }
+
     // Shuttle runtime already installs a global tracing/logging subscriber.
     // Using init() after another logger is set causes a panic (SetLoggerError).
     // try_init() will silently ignore if a logger is already installed.
@@ -57,10 +132,6 @@ async fn main(
         .get("SPOTIFY_CLIENT_SECRET")
         .context("Missing SPOTIFY_CLIENT_SECRET");
 
-    // Optional
-    let guild_id = secrets.get("GUILD_ID");
-    let discord_voice_channel_id = secrets.get("DISCORD_VOICE_CHANNEL_ID");
-
     // --- Set environment variables for spoticord_config ---
     env::set_var("DISCORD_TOKEN", &discord_token);
     env::set_var("DATABASE_URL", &database_url);
@@ -69,13 +140,30 @@ async fn main(
     env::set_var("SPOTIFY_CLIENT_SECRET", &spotify_client_secret?);
 
     // Set optional environment variables if they exist
-    if let Some(guild_id) = guild_id {
-        env::set_var("GUILD_ID", guild_id);
+    for key in OPTIONAL_SECRETS {
+        if let Some(value) = secrets.get(key) {
+            env::set_var(key, value);
+        }
     }
-    if let Some(discord_voice_channel_id) = discord_voice_channel_id {
-        env::set_var("DISCORD_VOICE_CHANNEL_ID", discord_voice_channel_id);
+
+    spoticord_config::validate().map_err(|why| anyhow::anyhow!("Invalid configuration: {why}"))?;
+
+    if spoticord_config::token_encryption_key().is_none() {
+        warn!("TOKEN_ENCRYPTION_KEY is not set, Spotify tokens are stored in the database as plaintext");
+    }
+
+    install_crypto_provider();
+
+    if let Some(proxy) = spoticord_config::https_proxy() {
+        info!(
+            "Connecting to Spotify through proxy {}",
+            proxy.host_str().unwrap_or_default()
+        );
     }
 
+    // Parse locale files before anything tries to use them
+    i18n::load();
+
     // Set up database
     let database: Database = match Database::connect().await {
         Ok(db) => db,
@@ -104,7 +192,9 @@ async fn main(
         spoticord_config::discord_intents(),
     )
     .framework(framework)
-    .register_songbird_from_config(songbird::Config::default().use_softclip(false))
+    .register_songbird_from_config(
+        songbird::Config::default().use_softclip(spoticord_config::audio_softclip()),
+    )
     .await
     {
         Ok(client) => client,
@@ -124,3 +214,35 @@ async fn main(
 
     Ok(client.into())
 }
+
+/// Extend a `RUST_LOG` filter with `LOG_DIRECTIVES`, replacing friendly names with their log targets.
+///
+/// Names that aren't listed in [`LOG_TARGETS`] are passed through as-is, so module paths can be used as well.
+fn log_filter(base: &str, directives: &str) -> String {
+    let directives = directives
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| {
+            let (name, level) = match directive.split_once('=') {
+                Some((name, level)) => (name.trim(), Some(level.trim())),
+                None => (directive, None),
+            };
+
+            let target = LOG_TARGETS
+                .iter()
+                .find(|(friendly, _)| friendly.eq_ignore_ascii_case(name))
+                .map_or(name, |(_, target)| *target);
+
+            match level {
+                Some(level) => format!("{target}={level}"),
+                None => target.to_string(),
+            }
+        });
+
+    std::iter::once(base.to_string())
+        .filter(|base| !base.is_empty())
+        .chain(directives)
+        .collect::<Vec<_>>()
+        .join(",")
+}
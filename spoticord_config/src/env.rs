@@ -1,8 +1,55 @@
 use std::sync::LazyLock;
 
+use log::warn;
+use serenity::all::{ActivityType, GatewayIntents};
+
+use url::Url;
+
+use crate::{AloneAction, PlayBehavior, RefreshFailureAction, TlsProvider, TokenCacheBackend};
+
 pub static DISCORD_TOKEN: LazyLock<String> = LazyLock::new(|| {
     std::env::var("DISCORD_TOKEN").expect("missing DISCORD_TOKEN environment variable")
 });
+pub static COMMAND_PREFIX: LazyLock<Option<String>> = LazyLock::new(|| {
+    std::env::var("COMMAND_PREFIX")
+        .ok()
+        .map(|prefix| prefix.trim().to_string())
+        .filter(|prefix| !prefix.is_empty())
+});
+pub static DISCORD_INTENTS: LazyLock<GatewayIntents> = LazyLock::new(|| {
+    // Message content is only needed to read prefix commands
+    let mut required = GatewayIntents::GUILDS | GatewayIntents::GUILD_VOICE_STATES;
+    if COMMAND_PREFIX.is_some() {
+        required |= GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+    }
+
+    let Ok(value) = std::env::var("DISCORD_INTENTS") else {
+        return required;
+    };
+
+    let intents = value
+        .split(',')
+        .map(|name| name.trim().to_uppercase())
+        .filter(|name| !name.is_empty())
+        .fold(
+            GatewayIntents::empty(),
+            |intents, name| match GatewayIntents::from_name(&name) {
+                Some(intent) => intents | intent,
+                None => {
+                    warn!("Unknown intent '{name}' in DISCORD_INTENTS, ignoring");
+                    intents
+                }
+            },
+        );
+
+    for (name, intent) in required.iter_names() {
+        if !intents.contains(intent) {
+            warn!("DISCORD_INTENTS is missing {name}, which Spoticord needs with the current configuration");
+        }
+    }
+
+    intents
+});
 pub static DATABASE_URL: LazyLock<String> = LazyLock::new(|| {
     std::env::var("DATABASE_URL").expect("missing DATABASE_URL environment variable")
 });
@@ -15,11 +62,231 @@ pub static SPOTIFY_CLIENT_SECRET: LazyLock<String> = LazyLock::new(|| {
     std::env::var("SPOTIFY_CLIENT_SECRET")
         .expect("missing SPOTIFY_CLIENT_SECRET environment variable")
 });
-pub static DISCORD_VOICE_CHANNEL_ID: LazyLock<String> = LazyLock::new(|| {
-    std::env::var("DISCORD_VOICE_CHANNEL_ID")
-        .expect("missing DISCORD_VOICE_CHANNEL_ID environment variable")
+pub static SPOTIFY_API_BASE_URL: LazyLock<Option<String>> = LazyLock::new(|| {
+    // rspotify appends endpoint paths directly to the base URL, so it must end with a slash
+    std::env::var("SPOTIFY_API_BASE_URL").ok().map(|url| {
+        let url = url.trim().trim_end_matches('/');
+        format!("{url}/")
+    })
 });
+pub static DISCORD_VOICE_CHANNEL_ID: LazyLock<Option<String>> =
+    LazyLock::new(|| std::env::var("DISCORD_VOICE_CHANNEL_ID").ok());
+
+pub static OWNER_IDS: LazyLock<Option<String>> = LazyLock::new(|| std::env::var("OWNER_IDS").ok());
+pub static TOKEN_ENCRYPTION_KEY: LazyLock<Option<String>> =
+    LazyLock::new(|| std::env::var("TOKEN_ENCRYPTION_KEY").ok());
+pub static AUDIO_SOFTCLIP: LazyLock<bool> = LazyLock::new(|| flag("AUDIO_SOFTCLIP", false));
+pub static PLAYLIST_IMPORT_LIMIT: LazyLock<usize> =
+    LazyLock::new(|| number_or_default("PLAYLIST_IMPORT_LIMIT", 500));
+pub static MAX_QUEUE_LENGTH: LazyLock<usize> =
+    LazyLock::new(|| number_or_default("MAX_QUEUE_LENGTH", 0));
+pub static PLAY_DEFAULT_BEHAVIOR: LazyLock<PlayBehavior> = LazyLock::new(|| {
+    let Ok(value) = std::env::var("PLAY_DEFAULT_BEHAVIOR") else {
+        return PlayBehavior::Append;
+    };
+
+    match value.trim().to_lowercase().as_str() {
+        "append" => PlayBehavior::Append,
+        "replace" => PlayBehavior::Replace,
+        other => {
+            warn!("Unknown PLAY_DEFAULT_BEHAVIOR '{other}', falling back to 'append'");
+            PlayBehavior::Append
+        }
+    }
+});
+pub static DEDUP_QUEUE: LazyLock<bool> = LazyLock::new(|| flag("DEDUP_QUEUE", false));
+pub static DATABASE_APP_NAME: LazyLock<String> = LazyLock::new(|| {
+    std::env::var("DATABASE_APP_NAME").unwrap_or_else(|_| format!("spoticord/{}", crate::VERSION))
+});
+pub static TOKEN_REFRESH_MARGIN_SECS: LazyLock<u64> =
+    LazyLock::new(|| number_or_default("TOKEN_REFRESH_MARGIN_SECS", 60));
+pub static OWNER_ONLY_CONTROLS: LazyLock<bool> =
+    LazyLock::new(|| flag("OWNER_ONLY_CONTROLS", false));
+pub static AUTO_RESUME: LazyLock<bool> = LazyLock::new(|| flag("AUTO_RESUME", false));
+pub static POST_WELCOME: LazyLock<bool> = LazyLock::new(|| flag("POST_WELCOME", false));
+pub static ALONE_ACTION: LazyLock<AloneAction> = LazyLock::new(|| {
+    let Ok(value) = std::env::var("ALONE_ACTION") else {
+        return AloneAction::Nothing;
+    };
+
+    match value.trim().to_lowercase().as_str() {
+        "nothing" => AloneAction::Nothing,
+        "pause" => AloneAction::Pause,
+        "disconnect" => AloneAction::Disconnect,
+        other => {
+            warn!("Unknown ALONE_ACTION '{other}', falling back to 'nothing'");
+            AloneAction::Nothing
+        }
+    }
+});
+pub static ALONE_GRACE_SECS: LazyLock<u64> =
+    LazyLock::new(|| number_or_default("ALONE_GRACE_SECS", 30));
+pub static REFRESH_FAILURE_ACTION: LazyLock<RefreshFailureAction> = LazyLock::new(|| {
+    let Ok(value) = std::env::var("REFRESH_FAILURE_ACTION") else {
+        return RefreshFailureAction::Delete;
+    };
+
+    match value.trim().to_lowercase().as_str() {
+        "delete" => RefreshFailureAction::Delete,
+        "disable" => RefreshFailureAction::Disable,
+        other => {
+            warn!("Unknown REFRESH_FAILURE_ACTION '{other}', falling back to 'delete'");
+            RefreshFailureAction::Delete
+        }
+    }
+});
+pub static TOKEN_CACHE_BACKEND: LazyLock<TokenCacheBackend> = LazyLock::new(|| {
+    let Ok(value) = std::env::var("TOKEN_CACHE_BACKEND") else {
+        return TokenCacheBackend::Memory;
+    };
+
+    match value.trim().to_lowercase().as_str() {
+        "memory" => TokenCacheBackend::Memory,
+        "none" => TokenCacheBackend::None,
+        "kv" if KV_URL.is_some() => TokenCacheBackend::Kv,
+        "kv" => {
+            warn!("TOKEN_CACHE_BACKEND is set to 'kv' but KV_URL is not set, falling back to 'memory'");
+            TokenCacheBackend::Memory
+        }
+        other => {
+            warn!("Unknown TOKEN_CACHE_BACKEND '{other}', falling back to 'memory'");
+            TokenCacheBackend::Memory
+        }
+    }
+});
+pub static TLS_PROVIDER: LazyLock<TlsProvider> = LazyLock::new(|| {
+    let Ok(value) = std::env::var("TLS_PROVIDER") else {
+        return TlsProvider::AwsLcRs;
+    };
+
+    match value.trim().to_lowercase().as_str() {
+        "aws-lc-rs" | "aws_lc_rs" => TlsProvider::AwsLcRs,
+        "ring" => TlsProvider::Ring,
+        other => {
+            warn!("Unknown TLS_PROVIDER '{other}', falling back to 'aws-lc-rs'");
+            TlsProvider::AwsLcRs
+        }
+    }
+});
+pub static HTTPS_PROXY: LazyLock<Option<Url>> = LazyLock::new(|| {
+    let value = std::env::var("HTTPS_PROXY")
+        .or_else(|_| std::env::var("https_proxy"))
+        .ok()?;
+
+    match Url::parse(value.trim()) {
+        Ok(url) => Some(url),
+        Err(why) => {
+            warn!("Ignoring invalid HTTPS_PROXY: {why}");
+            None
+        }
+    }
+});
+pub static NEON_COMPAT: LazyLock<bool> = LazyLock::new(|| flag("NEON_COMPAT", true));
+pub static DATABASE_POOL_SIZE: LazyLock<u32> = LazyLock::new(|| {
+    let size = number_or_default("DATABASE_POOL_SIZE", 1).max(1);
+
+    if size > 1 && *NEON_COMPAT {
+        warn!(
+            "DATABASE_POOL_SIZE is ignored while NEON_COMPAT is enabled, using a single connection"
+        );
+        return 1;
+    }
+
+    size
+});
+pub static SLOW_QUERY_WARN_MS: LazyLock<u64> =
+    LazyLock::new(|| number_or_default("SLOW_QUERY_WARN_MS", 1000));
+pub static DATABASE_BREAKER_THRESHOLD: LazyLock<u32> =
+    LazyLock::new(|| number_or_default("DATABASE_BREAKER_THRESHOLD", 3));
+pub static RUN_MIGRATIONS: LazyLock<bool> = LazyLock::new(|| flag("RUN_MIGRATIONS", true));
+pub static METRICS_REFRESH_SECS: LazyLock<u64> =
+    LazyLock::new(|| number_or_default("METRICS_REFRESH_SECS", 60).max(1));
+pub static LINK_REQUEST_TTL_SECS: LazyLock<u64> =
+    LazyLock::new(|| match number_or_default("LINK_REQUEST_TTL_SECS", 3600) {
+        0 => {
+            warn!("LINK_REQUEST_TTL_SECS must be positive, falling back to 3600");
+            3600
+        }
+        secs => secs,
+    });
+pub static USER_TOUCH_INTERVAL_SECS: LazyLock<u64> =
+    LazyLock::new(|| number_or_default("USER_TOUCH_INTERVAL_SECS", 300));
+pub static METRICS_ADDR: LazyLock<Option<String>> =
+    LazyLock::new(|| std::env::var("METRICS_ADDR").ok());
+pub static BOT_ACTIVITY_TYPE: LazyLock<ActivityType> = LazyLock::new(|| {
+    let Ok(value) = std::env::var("BOT_ACTIVITY_TYPE") else {
+        return ActivityType::Listening;
+    };
+
+    match value.trim().to_lowercase().as_str() {
+        "listening" => ActivityType::Listening,
+        "playing" => ActivityType::Playing,
+        "watching" => ActivityType::Watching,
+        "competing" => ActivityType::Competing,
+        other => {
+            warn!("Unknown BOT_ACTIVITY_TYPE '{other}', falling back to 'listening'");
+            ActivityType::Listening
+        }
+    }
+});
+pub static FEATURES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    // Lyrics were available before feature flags existed, so keep them enabled unless configured otherwise
+    let features = std::env::var("FEATURES").unwrap_or_else(|_| "lyrics".to_string());
+
+    features
+        .split(',')
+        .map(|feature| feature.trim().to_lowercase())
+        .filter(|feature| !feature.is_empty())
+        .inspect(|feature| {
+            if !crate::KNOWN_FEATURES.contains(&feature.as_str()) {
+                warn!("Unknown feature '{feature}' in FEATURES, ignoring");
+            }
+        })
+        .collect()
+});
+
+pub static KV_URL: LazyLock<Option<String>> = LazyLock::new(|| std::env::var("KV_URL").ok());
+
+/// Parse a boolean environment variable, falling back to `default` if it is not set
+fn flag(name: &str, default: bool) -> bool {
+    match std::env::var(name) {
+        Ok(value) => matches!(
+            value.trim().to_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        ),
+        Err(_) => default,
+    }
+}
+
+/// Parse a numeric environment variable, falling back to `default` if it is not set
+pub(crate) fn number<T: std::str::FromStr>(name: &str, default: T) -> Result<T, String> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| format!("{name} must be a valid number, but is '{value}'")),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Parse a numeric environment variable like [`number`], but also fall back to `default` if it is invalid.
+/// Invalid values are reported by [`validate_numbers`] at startup.
+fn number_or_default<T: std::str::FromStr + Copy>(name: &str, default: T) -> T {
+    number(name, default).unwrap_or(default)
+}
+
+/// Check that every numeric environment variable that is set holds a valid number
+pub fn validate_numbers() -> Result<(), String> {
+    number::<usize>("PLAYLIST_IMPORT_LIMIT", 0)?;
+    number::<usize>("MAX_QUEUE_LENGTH", 0)?;
+    number::<u64>("TOKEN_REFRESH_MARGIN_SECS", 0)?;
+    number::<u64>("ALONE_GRACE_SECS", 0)?;
+    number::<u32>("DATABASE_POOL_SIZE", 0)?;
+    number::<u64>("SLOW_QUERY_WARN_MS", 0)?;
+    number::<u32>("DATABASE_BREAKER_THRESHOLD", 0)?;
+    number::<u64>("METRICS_REFRESH_SECS", 0)?;
+    number::<u64>("LINK_REQUEST_TTL_SECS", 0)?;
+    number::<u64>("USER_TOUCH_INTERVAL_SECS", 0)?;
 
-// Locked behind `stats` feature
-// pub static KV_URL: LazyLock<String> =
-//     LazyLock::new(|| std::env::var("KV_URL").expect("missing KV_URL environment variable"));
+    Ok(())
+}
@@ -1,7 +1,13 @@
 mod env;
+mod retry;
 
+pub use retry::{record_spotify_call, with_retry};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use rspotify::{AuthCodeSpotify, Config, Credentials, OAuth, Token};
-use serenity::all::{ChannelId, GatewayIntents};
+use serenity::all::{ActivityData, ActivityType, ChannelId, GatewayIntents, UserId};
+use std::{net::SocketAddr, time::Duration};
+use url::Url;
 
 #[cfg(not(debug_assertions))]
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -12,6 +18,46 @@ pub const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "-dev");
 /// The "listening to" message that shows up under the Spoticord bot user
 pub const MOTD: &str = "some good 'ol music";
 
+/// Experimental features that can be enabled with the `FEATURES` environment variable
+pub const KNOWN_FEATURES: &[&str] = &["autoplay", "crossfade", "lyrics"];
+
+/// What a session does when everyone else has left its voice channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AloneAction {
+    Nothing,
+    Pause,
+    Disconnect,
+}
+
+/// What happens to a linked account if Spotify rejects its refresh token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshFailureAction {
+    Delete,
+    Disable,
+}
+
+/// Where Spotify access tokens are cached, see [`token_cache_backend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCacheBackend {
+    Memory,
+    Kv,
+    None,
+}
+
+/// What `/play` does by default when no mode is given, see [`play_default_behavior`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayBehavior {
+    Append,
+    Replace,
+}
+
+/// The rustls crypto provider used for all TLS connections, see [`tls_provider`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsProvider {
+    AwsLcRs,
+    Ring,
+}
+
 /// The time it takes (in seconds) for Spoticord to disconnect when no music is being played
 // pub const DISCONNECT_TIME: u64 = 5 * 60; // Removed timeout functionality
 
@@ -19,28 +65,308 @@ pub fn discord_token() -> &'static str {
     &env::DISCORD_TOKEN
 }
 
+/// The gateway intents to connect with, either the ones set in `DISCORD_INTENTS` or the ones the enabled features need
 pub fn discord_intents() -> GatewayIntents {
-    GatewayIntents::GUILDS | GatewayIntents::GUILD_VOICE_STATES
+    *env::DISCORD_INTENTS
+}
+
+/// The prefix for message-based commands, if they are enabled
+pub fn command_prefix() -> Option<&'static str> {
+    env::COMMAND_PREFIX.as_deref()
 }
 
 pub fn database_url() -> &'static str {
     &env::DATABASE_URL
 }
 
+/// The `application_name` Spoticord reports to Postgres, as shown in `pg_stat_activity`
+pub fn database_app_name() -> &'static str {
+    &env::DATABASE_APP_NAME
+}
+
 pub fn link_url() -> &'static str {
     &env::LINK_URL
 }
 
-// pub fn kv_url() -> &'static str {
-//     &env::KV_URL
-// }
+/// The 256-bit key used to encrypt Spotify tokens at rest, if encryption is enabled.
+///
+/// `TOKEN_ENCRYPTION_KEY` holds the key base64 encoded, an invalid key is rejected by [`validate`].
+pub fn token_encryption_key() -> Option<[u8; 32]> {
+    parse_encryption_key(env::TOKEN_ENCRYPTION_KEY.as_deref()?).ok()
+}
+
+/// The connection URL of the KV store (a redis-server instance), if one is configured
+pub fn kv_url() -> Option<&'static str> {
+    env::KV_URL.as_deref()
+}
+
+/// The voice channel to join when the user invoking `/join` is not in a voice channel.
+///
+/// An invalid value is rejected by [`validate`] at startup.
+pub fn voice_channel_id() -> Option<ChannelId> {
+    parse_channel_id(env::DISCORD_VOICE_CHANNEL_ID.as_deref()?).ok()
+}
+
+/// Check the configuration values that would otherwise only fail once they are first used, so a misconfigured
+/// deployment fails at startup instead of on the first command
+pub fn validate() -> Result<(), String> {
+    env::validate_numbers()?;
+    validate_voice_channel_id(env::DISCORD_VOICE_CHANNEL_ID.as_deref())?;
+    validate_refresh_margin(*env::TOKEN_REFRESH_MARGIN_SECS)?;
+    validate_owner_ids(env::OWNER_IDS.as_deref())?;
+    validate_encryption_key(env::TOKEN_ENCRYPTION_KEY.as_deref())?;
+    validate_metrics_addr(env::METRICS_ADDR.as_deref())?;
+
+    Ok(())
+}
+
+/// An absent `DISCORD_VOICE_CHANNEL_ID` is valid, since it is optional
+fn validate_voice_channel_id(value: Option<&str>) -> Result<(), String> {
+    if let Some(value) = value {
+        parse_channel_id(value).map_err(|why| format!("DISCORD_VOICE_CHANNEL_ID {why}"))?;
+    }
+
+    Ok(())
+}
+
+/// Spotify access tokens are valid for an hour, a larger margin would refresh on every use
+fn validate_refresh_margin(secs: u64) -> Result<(), String> {
+    if !(1..3600).contains(&secs) {
+        return Err(format!(
+            "TOKEN_REFRESH_MARGIN_SECS must be between 1 and 3599, but is {secs}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// An absent or empty `OWNER_IDS` is valid, there are simply no owners
+fn validate_owner_ids(value: Option<&str>) -> Result<(), String> {
+    if let Some(value) = value {
+        parse_owner_ids(value).map_err(|why| format!("OWNER_IDS {why}"))?;
+    }
+
+    Ok(())
+}
+
+/// An absent `TOKEN_ENCRYPTION_KEY` is valid, tokens are then stored without encryption
+fn validate_encryption_key(value: Option<&str>) -> Result<(), String> {
+    if let Some(value) = value {
+        parse_encryption_key(value).map_err(|why| format!("TOKEN_ENCRYPTION_KEY {why}"))?;
+    }
+
+    Ok(())
+}
+
+/// An absent `METRICS_ADDR` is valid, metrics are then disabled
+fn validate_metrics_addr(value: Option<&str>) -> Result<(), String> {
+    if let Some(value) = value {
+        parse_metrics_addr(value).map_err(|why| format!("METRICS_ADDR {why}"))?;
+    }
+
+    Ok(())
+}
+
+fn parse_channel_id(value: &str) -> Result<ChannelId, String> {
+    match value.trim().parse::<u64>() {
+        Ok(id) if id != 0 => Ok(ChannelId::new(id)),
+        _ => Err(format!("must be a Discord channel ID, but is '{value}'")),
+    }
+}
+
+fn parse_owner_ids(value: &str) -> Result<Vec<u64>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| match id.parse::<u64>() {
+            Ok(id) if id != 0 => Ok(id),
+            _ => Err(format!(
+                "must be a comma-separated list of Discord user IDs, but contains '{id}'"
+            )),
+        })
+        .collect()
+}
+
+fn parse_encryption_key(value: &str) -> Result<[u8; 32], String> {
+    let key = BASE64
+        .decode(value.trim())
+        .map_err(|_| "must be valid base64".to_string())?;
+
+    key.try_into()
+        .map_err(|key: Vec<u8>| format!("must be exactly 32 bytes, but is {} bytes", key.len()))
+}
 
-pub fn voice_channel_id() -> ChannelId {
-    let id_str = &env::DISCORD_VOICE_CHANNEL_ID;
-    let id_num: u64 = id_str
+fn parse_metrics_addr(value: &str) -> Result<SocketAddr, String> {
+    value
+        .trim()
         .parse()
-        .expect("DISCORD_VOICE_CHANNEL_ID must be a valid u64");
-    return ChannelId::from(id_num);
+        .map_err(|_| format!("must be a socket address, e.g. 0.0.0.0:9100, but is '{value}'"))
+}
+
+/// Whether a Discord user is listed in `OWNER_IDS`, and thus allowed to use every command
+pub fn is_owner(user: UserId) -> bool {
+    env::OWNER_IDS
+        .as_deref()
+        .and_then(|ids| parse_owner_ids(ids).ok())
+        .is_some_and(|ids| ids.contains(&user.get()))
+}
+
+/// Whether songbird should apply softclipping to the mixed audio
+pub fn audio_softclip() -> bool {
+    *env::AUDIO_SOFTCLIP
+}
+
+/// The maximum amount of tracks that will be queued from a single playlist, 0 means unlimited
+pub fn playlist_import_limit() -> usize {
+    *env::PLAYLIST_IMPORT_LIMIT
+}
+
+/// The maximum amount of items the bot may have waiting in a session's queue, 0 means unlimited
+pub fn max_queue_length() -> usize {
+    *env::MAX_QUEUE_LENGTH
+}
+
+/// Whether `/play` adds items to the end of the queue or plays them right away, if the user doesn't choose
+pub fn play_default_behavior() -> PlayBehavior {
+    *env::PLAY_DEFAULT_BEHAVIOR
+}
+
+/// Whether items that are already in the queue are refused instead of being queued again
+pub fn dedup_queue() -> bool {
+    *env::DEDUP_QUEUE
+}
+
+/// How long before expiry a Spotify access token is refreshed.
+///
+/// A value outside of 1 to 3599 seconds is rejected by [`validate`] at startup.
+pub fn token_refresh_margin() -> Duration {
+    Duration::from_secs(*env::TOKEN_REFRESH_MARGIN_SECS)
+}
+
+/// Whether playback control commands are restricted to the session owner and server admins
+pub fn owner_only_controls() -> bool {
+    *env::OWNER_ONLY_CONTROLS
+}
+
+/// Whether playback that was lost when a session ended is resumed automatically, instead of offering to resume it
+pub fn auto_resume() -> bool {
+    *env::AUTO_RESUME
+}
+
+/// Whether a setup message is posted when the bot is added to a new server
+pub fn post_welcome() -> bool {
+    *env::POST_WELCOME
+}
+
+/// What a session does when the bot is left alone in its voice channel
+pub fn alone_action() -> AloneAction {
+    *env::ALONE_ACTION
+}
+
+/// How long the bot has to be alone before [`alone_action`] is applied
+pub fn alone_grace() -> Duration {
+    Duration::from_secs(*env::ALONE_GRACE_SECS)
+}
+
+/// Whether an account is deleted or only disabled when its refresh token is rejected
+pub fn refresh_failure_action() -> RefreshFailureAction {
+    *env::REFRESH_FAILURE_ACTION
+}
+
+/// Where Spotify access tokens are cached in between uses. Falls back to memory if the KV store is not configured.
+pub fn token_cache_backend() -> TokenCacheBackend {
+    *env::TOKEN_CACHE_BACKEND
+}
+
+/// The crypto provider that is installed for rustls at startup
+pub fn tls_provider() -> TlsProvider {
+    *env::TLS_PROVIDER
+}
+
+/// The proxy that connections to Spotify are made through, taken from `HTTPS_PROXY`
+pub fn https_proxy() -> Option<&'static Url> {
+    env::HTTPS_PROXY.as_ref()
+}
+
+/// Whether workarounds for Neon's connection pooler should be applied, such as disabling the statement cache
+pub fn neon_compat() -> bool {
+    *env::NEON_COMPAT
+}
+
+/// The maximum amount of database connections. Always `1` with [`neon_compat`], as Neon invalidates prepared
+/// statements between connections.
+pub fn database_pool_size() -> u32 {
+    *env::DATABASE_POOL_SIZE
+}
+
+/// Whether pending database migrations should be run when connecting to the database
+pub fn run_migrations() -> bool {
+    *env::RUN_MIGRATIONS
+}
+
+/// The activity that shows up under the Spoticord bot user, using the [`MOTD`]
+pub fn activity() -> ActivityData {
+    match *env::BOT_ACTIVITY_TYPE {
+        ActivityType::Playing => ActivityData::playing(MOTD),
+        ActivityType::Watching => ActivityData::watching(MOTD),
+        ActivityType::Competing => ActivityData::competing(MOTD),
+        _ => ActivityData::listening(MOTD),
+    }
+}
+
+/// The address the Prometheus metrics endpoint listens on, if metrics are enabled.
+///
+/// An invalid address is rejected by [`validate`] at startup.
+pub fn metrics_addr() -> Option<SocketAddr> {
+    parse_metrics_addr(env::METRICS_ADDR.as_deref()?).ok()
+}
+
+/// How long a database operation may hold the database connection before a warning is logged, if at all
+pub fn slow_query_threshold() -> Option<Duration> {
+    match *env::SLOW_QUERY_WARN_MS {
+        0 => None,
+        millis => Some(Duration::from_millis(millis)),
+    }
+}
+
+/// After how many consecutive failed database operations the database is considered unavailable, if at all
+pub fn database_breaker_threshold() -> Option<u32> {
+    match *env::DATABASE_BREAKER_THRESHOLD {
+        0 => None,
+        failures => Some(failures),
+    }
+}
+
+/// How often metrics that have to be queried from the database, such as table sizes, are refreshed
+pub fn metrics_refresh_interval() -> Duration {
+    Duration::from_secs(*env::METRICS_REFRESH_SECS)
+}
+
+/// How long a link request (and the link sent by `/link`) stays valid
+pub fn link_request_ttl() -> Duration {
+    Duration::from_secs(*env::LINK_REQUEST_TTL_SECS)
+}
+
+/// The minimum time between two writes of a user's last activity to the database
+pub fn user_touch_interval() -> Duration {
+    Duration::from_secs(*env::USER_TOUCH_INTERVAL_SECS)
+}
+
+/// Whether an experimental feature has been enabled for this deployment
+pub fn feature_enabled(name: &str) -> bool {
+    env::FEATURES.iter().any(|feature| feature == name)
+}
+
+/// The rspotify configuration, pointing at `SPOTIFY_API_BASE_URL` if it is set
+fn spotify_config() -> Config {
+    match env::SPOTIFY_API_BASE_URL.as_ref() {
+        Some(api_base_url) => Config {
+            api_base_url: api_base_url.clone(),
+            ..Default::default()
+        },
+        None => Config::default(),
+    }
 }
 
 pub fn get_spotify(token: Token) -> AuthCodeSpotify {
@@ -51,6 +377,101 @@ pub fn get_spotify(token: Token) -> AuthCodeSpotify {
             secret: Some(env::SPOTIFY_CLIENT_SECRET.to_string()),
         },
         OAuth::default(),
-        Config::default(),
+        spotify_config(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_channel_ids() {
+        assert_eq!(
+            parse_channel_id("1234567890"),
+            Ok(ChannelId::new(1234567890))
+        );
+        assert_eq!(
+            parse_channel_id(" 1234567890 "),
+            Ok(ChannelId::new(1234567890))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_channel_ids() {
+        for value in ["", "0", "-1", "abc", "123abc", "<#1234567890>"] {
+            assert!(
+                parse_channel_id(value).is_err(),
+                "'{value}' should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn validates_voice_channel_id() {
+        assert!(validate_voice_channel_id(None).is_ok());
+        assert!(validate_voice_channel_id(Some("1234567890")).is_ok());
+
+        let why = validate_voice_channel_id(Some("general")).unwrap_err();
+        assert!(why.contains("DISCORD_VOICE_CHANNEL_ID"));
+        assert!(why.contains("general"));
+    }
+
+    #[test]
+    fn validates_refresh_margin() {
+        assert!(validate_refresh_margin(1).is_ok());
+        assert!(validate_refresh_margin(3599).is_ok());
+        assert!(validate_refresh_margin(0).is_err());
+        assert!(validate_refresh_margin(3600).is_err());
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(env::number("SPOTICORD_TEST_UNSET_NUMBER", 5u32), Ok(5));
+
+        std::env::set_var("SPOTICORD_TEST_VALID_NUMBER", " 42 ");
+        assert_eq!(env::number("SPOTICORD_TEST_VALID_NUMBER", 5u32), Ok(42));
+
+        std::env::set_var("SPOTICORD_TEST_INVALID_NUMBER", "-1");
+        let why = env::number("SPOTICORD_TEST_INVALID_NUMBER", 5u32).unwrap_err();
+        assert!(why.contains("SPOTICORD_TEST_INVALID_NUMBER"));
+        assert!(why.contains("-1"));
+    }
+
+    #[test]
+    fn validates_owner_ids() {
+        assert!(validate_owner_ids(None).is_ok());
+        assert!(validate_owner_ids(Some("")).is_ok());
+        assert_eq!(parse_owner_ids(" 123, 456,"), Ok(vec![123, 456]));
+
+        let why = validate_owner_ids(Some("123,admin")).unwrap_err();
+        assert!(why.contains("OWNER_IDS"));
+        assert!(why.contains("admin"));
+    }
+
+    #[test]
+    fn validates_metrics_addr() {
+        assert!(validate_metrics_addr(None).is_ok());
+        assert_eq!(
+            parse_metrics_addr(" 0.0.0.0:9100 "),
+            Ok(SocketAddr::from(([0, 0, 0, 0], 9100)))
+        );
+
+        let why = validate_metrics_addr(Some("localhost")).unwrap_err();
+        assert!(why.contains("METRICS_ADDR"));
+        assert!(why.contains("localhost"));
+    }
+
+    #[test]
+    fn validates_encryption_key() {
+        assert!(validate_encryption_key(None).is_ok());
+        assert_eq!(parse_encryption_key(&BASE64.encode([7; 32])), Ok([7; 32]));
+
+        let why = validate_encryption_key(Some("not a key")).unwrap_err();
+        assert!(why.contains("TOKEN_ENCRYPTION_KEY"));
+        assert!(why.contains("base64"));
+
+        let why = validate_encryption_key(Some(&BASE64.encode([7; 16]))).unwrap_err();
+        assert!(why.contains("32 bytes"));
+    }
+}
@@ -0,0 +1,97 @@
+use std::{future::Future, time::Duration};
+
+use log::debug;
+use rspotify::{http::HttpError, ClientError, ClientResult};
+use spoticord_utils::metrics;
+
+/// The amount of times a failed Spotify call is retried
+const MAX_RETRIES: u32 = 3;
+
+/// The delay before the first retry, doubled for every retry after that
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// The longest a `Retry-After` header is honored for, longer waits are better reported to the user
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Run a Spotify Web API call, retrying it with exponential backoff if it fails transiently.
+///
+/// Rate limited (429) calls are retried after the delay Spotify asks for in `Retry-After`.
+/// Server errors and connection failures are retried as well, so only use this for idempotent calls.
+/// Every attempt is counted under `operation`, see [`record_spotify_call`].
+pub async fn with_retry<T, F, Fut>(operation: &'static str, mut call: F) -> ClientResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ClientResult<T>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        let result = call().await;
+        record_spotify_call(operation, &result);
+
+        let error = match result {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        let Some(delay) = retry_delay(&error, attempt) else {
+            return Err(error);
+        };
+
+        attempt += 1;
+        debug!("Spotify call failed ({error}), retry {attempt}/{MAX_RETRIES} in {delay:?}");
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// How long to wait before retrying a failed call, or `None` if it should not be retried
+fn retry_delay(error: &ClientError, attempt: u32) -> Option<Duration> {
+    if attempt >= MAX_RETRIES {
+        return None;
+    }
+
+    let backoff = BASE_DELAY * 2u32.pow(attempt);
+
+    match error {
+        ClientError::Http(error) => match error.as_ref() {
+            HttpError::StatusCode(response) if response.status().as_u16() == 429 => {
+                let retry_after = response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse().ok())
+                    .map(Duration::from_secs)
+                    .unwrap_or(backoff);
+
+                (retry_after <= MAX_RETRY_AFTER).then_some(retry_after)
+            }
+            HttpError::StatusCode(response) if response.status().is_server_error() => Some(backoff),
+            HttpError::Client(error) if error.is_timeout() || error.is_connect() => Some(backoff),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Count a Spotify Web API call in `spoticord_spotify_requests_total`, labeled by operation and outcome
+pub fn record_spotify_call<T>(operation: &'static str, result: &ClientResult<T>) {
+    let outcome = match result {
+        Ok(_) => "success",
+        Err(ClientError::Http(error)) => match error.as_ref() {
+            HttpError::StatusCode(response) => match response.status().as_u16() {
+                429 => "429",
+                401 | 403 => "auth_error",
+                status if status >= 500 => "5xx",
+                _ => "error",
+            },
+            _ => "error",
+        },
+        Err(_) => "error",
+    };
+
+    metrics::increment_counter(
+        "spoticord_spotify_requests_total",
+        &[("operation", operation), ("outcome", outcome)],
+    );
+}
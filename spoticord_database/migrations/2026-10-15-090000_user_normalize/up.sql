@@ -0,0 +1 @@
+ALTER TABLE "user" ADD COLUMN normalize BOOLEAN NOT NULL DEFAULT FALSE;
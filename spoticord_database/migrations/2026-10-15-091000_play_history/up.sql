@@ -0,0 +1,10 @@
+CREATE TABLE "play_history" (
+    id BIGSERIAL PRIMARY KEY,
+    user_id VARCHAR NOT NULL,
+    track_uri VARCHAR(128) NOT NULL,
+    played_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_play_history_user_id FOREIGN KEY (user_id) REFERENCES "user" (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_play_history_user_id ON "play_history" (user_id, id DESC);
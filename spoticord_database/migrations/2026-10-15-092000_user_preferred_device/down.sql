@@ -0,0 +1 @@
+ALTER TABLE "user" DROP COLUMN preferred_device_id;
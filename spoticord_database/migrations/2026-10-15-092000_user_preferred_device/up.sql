@@ -0,0 +1 @@
+ALTER TABLE "user" ADD COLUMN preferred_device_id VARCHAR(64);
@@ -0,0 +1 @@
+ALTER TABLE "user" ADD COLUMN allow_explicit BOOLEAN NOT NULL DEFAULT TRUE;
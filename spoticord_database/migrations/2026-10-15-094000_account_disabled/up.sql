@@ -0,0 +1 @@
+ALTER TABLE account ADD COLUMN disabled BOOLEAN NOT NULL DEFAULT FALSE;
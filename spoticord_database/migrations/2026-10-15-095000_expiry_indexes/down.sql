@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_account_last_updated;
+DROP INDEX IF EXISTS idx_link_request_expires;
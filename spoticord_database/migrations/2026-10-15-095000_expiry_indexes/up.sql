@@ -0,0 +1,8 @@
+-- link_request.token, link_request.user_id and account.user_id are already covered by their
+-- PRIMARY KEY and UNIQUE constraints, so only the columns used for range scans need an index.
+
+-- Pruning expired link requests
+CREATE INDEX IF NOT EXISTS idx_link_request_expires ON "link_request" (expires);
+
+-- Finding inactive accounts
+CREATE INDEX IF NOT EXISTS idx_account_last_updated ON "account" (last_updated);
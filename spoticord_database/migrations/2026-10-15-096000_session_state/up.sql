@@ -0,0 +1,6 @@
+CREATE TABLE "session_state" (
+    guild_id VARCHAR PRIMARY KEY,
+    playback_channel_id VARCHAR,
+    playback_message_id VARCHAR,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
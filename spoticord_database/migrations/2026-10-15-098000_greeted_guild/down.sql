@@ -0,0 +1 @@
+DROP TABLE "greeted_guild";
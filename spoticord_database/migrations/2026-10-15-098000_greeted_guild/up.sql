@@ -0,0 +1,4 @@
+CREATE TABLE "greeted_guild" (
+    guild_id VARCHAR PRIMARY KEY,
+    greeted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
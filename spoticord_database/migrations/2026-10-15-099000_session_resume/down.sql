@@ -0,0 +1,5 @@
+ALTER TABLE "session_state"
+    DROP COLUMN owner_id,
+    DROP COLUMN track_uri,
+    DROP COLUMN position_ms,
+    DROP COLUMN playback_saved_at;
@@ -0,0 +1,5 @@
+ALTER TABLE "session_state"
+    ADD COLUMN owner_id VARCHAR,
+    ADD COLUMN track_uri VARCHAR,
+    ADD COLUMN position_ms INTEGER,
+    ADD COLUMN playback_saved_at TIMESTAMP;
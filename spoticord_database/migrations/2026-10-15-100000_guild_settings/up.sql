@@ -0,0 +1,4 @@
+CREATE TABLE "guild_settings" (
+    guild_id VARCHAR PRIMARY KEY,
+    disabled_commands TEXT[] NOT NULL DEFAULT '{}'
+);
@@ -0,0 +1,7 @@
+CREATE TABLE "audit_log" (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    user_id VARCHAR NOT NULL,
+    action VARCHAR(32) NOT NULL,
+    detail TEXT
+);
@@ -0,0 +1 @@
+ALTER TABLE "user" ADD COLUMN share_activity BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,8 @@
+CREATE TABLE "favorite" (
+    user_id VARCHAR NOT NULL,
+    track_uri VARCHAR(128) NOT NULL,
+    added_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, track_uri),
+    CONSTRAINT fk_favorite_user_id FOREIGN KEY (user_id) REFERENCES "user" (id) ON DELETE CASCADE
+);
@@ -0,0 +1,69 @@
+//! Stops database operations from waiting for a connection while the database can't be reached.
+//!
+//! Every operation that can't reach the database waits for the full connection timeout of the pool. After
+//! [`spoticord_config::database_breaker_threshold`] of those failures in a row the breaker opens, and operations
+//! fail immediately with [`DatabaseError::Unavailable`] until a ping succeeds again.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use diesel::result::DatabaseErrorKind;
+use log::{info, warn};
+
+use crate::error::{DatabaseError, Result};
+
+/// The operation that is let through while the breaker is open, to find out whether the database is back
+const PROBE_OPERATION: &str = "ping";
+
+static FAILURES: AtomicU32 = AtomicU32::new(0);
+static OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Whether database operations are currently failing fast
+pub fn is_open() -> bool {
+    OPEN.load(Ordering::Relaxed)
+}
+
+/// Fail if the breaker is open, unless the operation is the ping that can close it again
+pub fn check(operation: &str) -> Result<()> {
+    if is_open() && operation != PROBE_OPERATION {
+        return Err(DatabaseError::Unavailable);
+    }
+
+    Ok(())
+}
+
+/// Record the outcome of a database operation
+pub fn record<T>(result: &Result<T>) {
+    match result {
+        Err(DatabaseError::Unavailable) => {}
+        Err(why) if is_unreachable(why) => {
+            let failures = FAILURES.fetch_add(1, Ordering::Relaxed) + 1;
+            let Some(threshold) = spoticord_config::database_breaker_threshold() else {
+                return;
+            };
+
+            if failures >= threshold && !OPEN.swap(true, Ordering::Relaxed) {
+                warn!("The database could not be reached {failures} times in a row, failing database operations until it is back");
+            }
+        }
+        // Any response from the database, even an error, means it can be reached
+        _ => {
+            FAILURES.store(0, Ordering::Relaxed);
+
+            if OPEN.swap(false, Ordering::Relaxed) {
+                info!("The database can be reached again");
+            }
+        }
+    }
+}
+
+/// Whether an error means that the database could not be reached, rather than that a query failed
+fn is_unreachable(error: &DatabaseError) -> bool {
+    match error {
+        DatabaseError::Pool(_) => true,
+        DatabaseError::Diesel(diesel::result::Error::DatabaseError(kind, _)) => matches!(
+            kind,
+            DatabaseErrorKind::ClosedConnection | DatabaseErrorKind::UnableToSendCommand
+        ),
+        _ => false,
+    }
+}
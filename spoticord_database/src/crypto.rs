@@ -0,0 +1,72 @@
+use std::sync::LazyLock;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+
+use crate::{
+    error::{DatabaseError, Result},
+    models::Account,
+};
+
+/// Marks a stored value as encrypted. Values without this prefix are legacy plaintext tokens.
+const PREFIX: &str = "enc:v1:";
+
+const NONCE_LENGTH: usize = 12;
+
+/// The key is checked by [`spoticord_config::validate`] on startup
+static CIPHER: LazyLock<Option<ChaCha20Poly1305>> = LazyLock::new(|| {
+    let key = spoticord_config::token_encryption_key()?;
+
+    Some(ChaCha20Poly1305::new(Key::from_slice(&key)))
+});
+
+/// Encrypt a token for storage. If no encryption key is configured, the token is returned as-is.
+pub fn encrypt(value: &str) -> Result<String> {
+    let Some(cipher) = CIPHER.as_ref() else {
+        return Ok(value.to_string());
+    };
+
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, value.as_bytes())
+        .map_err(|_| DatabaseError::TokenEncryption)?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+
+    Ok(format!("{PREFIX}{}", BASE64.encode(payload)))
+}
+
+/// Decrypt a stored token. Tokens that were stored before encryption was enabled are returned as-is.
+pub fn decrypt(value: &str) -> Result<String> {
+    let Some(payload) = value.strip_prefix(PREFIX) else {
+        return Ok(value.to_string());
+    };
+
+    let cipher = CIPHER.as_ref().ok_or(DatabaseError::TokenEncryption)?;
+    let payload = BASE64
+        .decode(payload)
+        .map_err(|_| DatabaseError::TokenEncryption)?;
+
+    if payload.len() < NONCE_LENGTH {
+        return Err(DatabaseError::TokenEncryption);
+    }
+
+    let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| DatabaseError::TokenEncryption)?;
+
+    String::from_utf8(plaintext).map_err(|_| DatabaseError::TokenEncryption)
+}
+
+/// Decrypt the Spotify tokens of an account that was read from the database
+pub fn decrypt_account(mut account: Account) -> Result<Account> {
+    account.access_token = decrypt(&account.access_token)?;
+    account.refresh_token = decrypt(&account.refresh_token)?;
+
+    Ok(account)
+}
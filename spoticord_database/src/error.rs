@@ -2,17 +2,38 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum DatabaseError {
-    #[error(transparent)]
-    Diesel(diesel::result::Error),
+    /// The underlying error is also returned from `source()`, so error chains (e.g. `{:#}` with anyhow) show the full cause.
+    /// It is repeated in the message since most call sites log errors with plain `{}` formatting.
+    #[error("Database query failed: {0}")]
+    Diesel(#[source] diesel::result::Error),
 
-    #[error(transparent)]
+    #[error("Failed to get a database connection from the pool: {0}")]
     Pool(#[from] r2d2::Error),
 
     #[error("Failed to refresh token")]
     RefreshTokenFailure,
 
+    /// Only returned once per suspension, later refreshes fail with [`DatabaseError::RefreshTokenFailure`]
+    #[error("Token refreshes are suspended after repeated failures")]
+    RefreshSuspended,
+
+    #[error("Failed to encrypt or decrypt a stored token")]
+    TokenEncryption,
+
     #[error("The requested record was not found")]
     NotFound,
+
+    #[error("The maximum amount of favorites has been reached")]
+    FavoritesFull,
+
+    #[error("The database is temporarily unavailable")]
+    Unavailable,
+
+    #[error("'{0}' is not a valid Discord user ID")]
+    InvalidUserId(String),
+
+    #[error("A device name must contain at least one visible character")]
+    InvalidDeviceName,
 }
 
 impl From<diesel::result::Error> for DatabaseError {
@@ -38,3 +59,53 @@ impl<T> DatabaseResultExt<T> for Result<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, time::Duration};
+
+    use diesel::{
+        r2d2::{ConnectionManager, Pool},
+        PgConnection,
+    };
+
+    use super::*;
+
+    #[test]
+    fn wrapping_variants_return_their_source() {
+        let diesel = DatabaseError::Diesel(diesel::result::Error::RollbackTransaction);
+        assert!(diesel.source().is_some());
+
+        // Nothing listens on port 1, so the pool fails to hand out a connection
+        let pool = Pool::builder()
+            .connection_timeout(Duration::from_millis(100))
+            .build_unchecked(ConnectionManager::<PgConnection>::new(
+                "postgres://127.0.0.1:1/spoticord",
+            ));
+        let pool = DatabaseError::from(pool.get().expect_err("no database is running"));
+        assert!(pool.source().is_some());
+    }
+
+    #[test]
+    fn leaf_variants_have_no_source() {
+        for error in [
+            DatabaseError::NotFound,
+            DatabaseError::RefreshTokenFailure,
+            DatabaseError::Unavailable,
+            DatabaseError::InvalidUserId("abc".to_string()),
+        ] {
+            assert!(error.source().is_none(), "{error:?} should have no source");
+        }
+    }
+
+    #[test]
+    fn display_includes_the_source() {
+        let error = DatabaseError::Diesel(diesel::result::Error::RollbackTransaction);
+        let source = error
+            .source()
+            .expect("diesel errors have a source")
+            .to_string();
+
+        assert!(error.to_string().contains(&source));
+    }
+}
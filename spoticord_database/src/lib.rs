@@ -1,46 +1,265 @@
 pub mod error;
 
+mod circuit_breaker;
+mod crypto;
 mod migrations;
-mod models;
+pub mod models;
+mod recent_users;
+mod refresh_budget;
 mod schema;
+mod token_cache;
+mod watchdog;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use diesel::prelude::*;
-use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use diesel::PgConnection;
 use error::*;
-use models::{Account, LinkRequest, User};
+use log::{debug, error, info, warn};
+use models::{
+    Account, AccountMeta, AuditEntry, DatabaseStats, Favorite, HealthStatus, LinkRequest,
+    PlayHistory, User, UserExport,
+};
 use rand::{distributions::Alphanumeric, Rng};
 use rspotify::{clients::BaseClient, Token};
+use spoticord_config::RefreshFailureAction;
+use spoticord_utils::metrics;
 use tokio::task;
 
-/// Helper to retry database operations that fail due to Neon invalidating prepared statements
+/// Add an `application_name` to a connection string, unless it already specifies one.
+///
+/// Supports both URIs (`postgres://...`) and `key=value` connection strings.
+fn with_application_name(database_url: &str, app_name: &str) -> String {
+    if database_url.contains("application_name=") {
+        return database_url.to_string();
+    }
+
+    if database_url.contains("://") {
+        let separator = if database_url.contains('?') { '&' } else { '?' };
+        let encoded = app_name
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                    (byte as char).to_string()
+                }
+                byte => format!("%{byte:02X}"),
+            })
+            .collect::<String>();
+
+        format!("{database_url}{separator}application_name={encoded}")
+    } else {
+        let escaped = app_name.replace('\\', "\\\\").replace('\'', "\\'");
+
+        format!("{database_url} application_name='{escaped}'")
+    }
+}
+
+/// Take a connection from the pool on behalf of `operation`, recording how long it took to become available.
+///
+/// The pool usually holds a single connection, so this wait is where concurrent commands queue up.
+fn get_connection(
+    pool: &Pool<ConnectionManager<PgConnection>>,
+    operation: &'static str,
+) -> Result<PooledConnection<ConnectionManager<PgConnection>>> {
+    circuit_breaker::check(operation)?;
+
+    let start = Instant::now();
+    watchdog::set_operation(operation);
+    let connection = pool.get()?;
+
+    metrics::observe_histogram(
+        "spoticord_database_connection_wait_seconds",
+        &[],
+        start.elapsed().as_secs_f64(),
+    );
+
+    Ok(connection)
+}
+
+/// Database errors that are transient, so the operation that caused them can succeed when it is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransientError {
+    /// Neon invalidated a prepared statement that diesel tried to reuse
+    PreparedStatement,
+
+    /// A transaction couldn't be serialized with a concurrent one (`40001`)
+    SerializationFailure,
+
+    /// A transaction was aborted to resolve a deadlock (`40P01`)
+    Deadlock,
+}
+
+impl TransientError {
+    const ALL: &'static [Self] = &[
+        Self::PreparedStatement,
+        Self::SerializationFailure,
+        Self::Deadlock,
+    ];
+
+    fn matches(self, error: &DatabaseError) -> bool {
+        use diesel::result::{DatabaseErrorKind, Error};
+
+        let DatabaseError::Diesel(Error::DatabaseError(kind, info)) = error else {
+            return false;
+        };
+
+        match self {
+            Self::PreparedStatement => {
+                matches!(kind, DatabaseErrorKind::Unknown)
+                    && info
+                        .message()
+                        .contains("unnamed prepared statement does not exist")
+            }
+            Self::SerializationFailure => matches!(kind, DatabaseErrorKind::SerializationFailure),
+            Self::Deadlock => info.message().contains("deadlock detected"),
+        }
+    }
+}
+
+/// How many times transactions that lock rows are attempted before a transient error is returned
+const TRANSACTION_ATTEMPTS: u32 = 3;
+
+/// The delay before the first retry of a transient error, which doubles with every following attempt
+const TRANSIENT_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Helper to retry database operations that fail due to Neon invalidating prepared statements.
+///
+/// The outcome is recorded by the circuit breaker, so operations fail fast once the database can't be reached.
 async fn retry_on_prepared_statement_error<F, R>(operation: F) -> Result<R>
 where
     F: Fn() -> Result<R> + Send + 'static + Clone,
     R: Send + 'static,
 {
-    let op_clone = operation.clone();
-    let result = task::spawn_blocking(operation)
-        .await
-        .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))?;
-
-    match result {
-        Err(DatabaseError::Diesel(diesel::result::Error::DatabaseError(
-            diesel::result::DatabaseErrorKind::Unknown,
-            ref info,
-        ))) if info
-            .message()
-            .contains("unnamed prepared statement does not exist") =>
-        {
-            // Retry once - the prepared statement should be recreated
-            task::spawn_blocking(op_clone)
-                .await
-                .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))?
+    retry_transient(operation, &[TransientError::PreparedStatement], 2).await
+}
+
+/// Run a blocking database operation, attempting it up to `max_attempts` times while it fails with one of the
+/// `retriable` errors. Attempts are spaced out with an exponential backoff.
+///
+/// The outcome is recorded by the circuit breaker, so operations fail fast once the database can't be reached.
+async fn retry_transient<F, R>(
+    operation: F,
+    retriable: &'static [TransientError],
+    max_attempts: u32,
+) -> Result<R>
+where
+    F: Fn() -> Result<R> + Send + 'static + Clone,
+    R: Send + 'static,
+{
+    let mut backoff = TRANSIENT_RETRY_BACKOFF;
+    let mut attempt = 1;
+
+    let result = loop {
+        let result = task::spawn_blocking(operation.clone())
+            .await
+            .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))
+            .and_then(|result| result);
+
+        let transient = match &result {
+            Err(why) => retriable.iter().find(|kind| kind.matches(why)),
+            Ok(_) => None,
+        };
+
+        match transient {
+            Some(kind) if attempt < max_attempts => {
+                debug!("Retrying database operation after a transient error ({kind:?})");
+
+                // Neon recreates the prepared statement right away, so there is nothing to wait for
+                if *kind != TransientError::PreparedStatement {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+
+                attempt += 1;
+            }
+            _ => break result,
+        }
+    };
+
+    circuit_breaker::record(&result);
+
+    result
+}
+
+/// Tables that see enough churn to benefit from a periodic `VACUUM ANALYZE`
+const MAINTENANCE_TABLES: &[&str] = &["user", "account", "link_request", "play_history"];
+
+/// How often the database is pinged while it is unavailable
+const AVAILABILITY_PROBE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often expired link requests are removed by [`Database::cleanup_expired_requests_periodically`]
+const REQUEST_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// The amount of alphanumeric characters in a link request token
+pub const LINK_TOKEN_LENGTH: usize = 64;
+
+/// The maximum amount of characters in a device name, longer names are truncated
+pub const DEVICE_NAME_MAX_LENGTH: usize = 64;
+
+/// Users whose Spotify account has not been refreshed for this long are considered inactive
+const INACTIVE_USER_DAYS: i64 = 60;
+
+/// How long saved playback can be resumed after a session ended
+const RESUME_WINDOW_HOURS: i64 = 24;
+
+/// The amount of plays that are kept per user, older plays are pruned when a new one is recorded
+const PLAY_HISTORY_LIMIT: i64 = 100;
+
+/// The amount of favorites a user can save, which fits in a single select menu
+pub const FAVORITES_LIMIT: i64 = 25;
+
+/// Trim a Discord user ID and make sure it looks like a snowflake before it is used in a query
+fn parse_user_id(user_id: impl AsRef<str>) -> Result<String> {
+    let user_id = user_id.as_ref().trim();
+
+    if user_id.is_empty() || !user_id.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(DatabaseError::InvalidUserId(user_id.to_string()));
+    }
+
+    Ok(user_id.to_string())
+}
+
+/// Trim a device name, strip control characters (which break the Spotify Connect UI) and truncate it to
+/// [`DEVICE_NAME_MAX_LENGTH`] characters
+fn sanitize_device_name(name: &str) -> Result<String> {
+    let name = name
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(DEVICE_NAME_MAX_LENGTH)
+        .collect::<String>();
+    let name = name.trim_end();
+
+    if name.is_empty() {
+        return Err(DatabaseError::InvalidDeviceName);
+    }
+
+    Ok(name.to_string())
+}
+
+/// Options for the database connection pool, see [`Database::connect_with_options`]
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    /// The maximum amount of connections. Neon invalidates prepared statements between connections, so it needs `1`.
+    pub max_size: u32,
+
+    /// How long an operation waits for a connection to become available
+    pub connection_timeout: std::time::Duration,
+
+    /// Disable diesel's prepared statement cache, as Neon drops prepared statements that diesel would reuse
+    pub disable_statement_cache: bool,
+}
+
+impl Default for PoolOptions {
+    /// The configured pool, which is a single connection without a statement cache unless `NEON_COMPAT` is disabled
+    fn default() -> Self {
+        Self {
+            max_size: spoticord_config::database_pool_size(),
+            connection_timeout: std::time::Duration::from_secs(30),
+            disable_statement_cache: spoticord_config::neon_compat(),
         }
-        other => other,
     }
 }
 
@@ -49,36 +268,37 @@ pub struct Database(Arc<Pool<ConnectionManager<PgConnection>>>);
 
 impl Database {
     pub async fn connect() -> Result<Self> {
-        Self::connect_with_url(&spoticord_config::database_url()).await
+        let database_url = spoticord_config::database_url();
+
+        if spoticord_config::run_migrations() {
+            Self::connect_with_url(database_url).await
+        } else {
+            Self::connect_without_migrations(database_url).await
+        }
     }
 
     pub async fn connect_with_url(database_url: &str) -> Result<Self> {
-        // Neon + sync diesel can encounter ephemeral prepared statement invalidation.
-        // Disable statement cache so diesel doesn't reuse dropped prepared statements.
-        std::env::set_var("DIESEL_STATEMENT_CACHE_SIZE", "0");
-        // Use single connection to avoid prepared statement conflicts between connections
-        let effective_url = database_url.to_string();
-        let manager = ConnectionManager::<PgConnection>::new(effective_url);
-        let pool = Pool::builder()
-            .max_size(1) // Single connection eliminates prepared statement conflicts
-            .connection_timeout(std::time::Duration::from_secs(30))
-            .build(manager)
-            .map_err(DatabaseError::from)?;
+        Self::connect_with_options(database_url, PoolOptions::default()).await
+    }
+
+    /// Connect to the database with a custom connection pool, and run pending migrations
+    pub async fn connect_with_options(database_url: &str, options: PoolOptions) -> Result<Self> {
+        let pool = Self::build_pool(database_url, &options)?;
 
         // Run migrations in blocking thread
         {
             let pool_clone = pool.clone();
             task::spawn_blocking(move || -> Result<()> {
-                let mut conn = pool_clone.get().map_err(DatabaseError::from)?;
+                let mut conn = get_connection(&pool_clone, "run_migrations")?;
                 migrations::run_migrations(&mut conn).map_err(|e| {
-                    eprintln!("Database migration failed: {:?}", e);
+                    error!("Database migration failed: {e}");
                     DatabaseError::from(e)
                 })?;
                 Ok(())
             })
             .await
             .map_err(|e| {
-                eprintln!("Task join error: {:?}", e);
+                error!("Migration task failed: {e}");
                 DatabaseError::Diesel(diesel::result::Error::RollbackTransaction)
             })??;
         }
@@ -86,15 +306,75 @@ impl Database {
         Ok(Self(Arc::new(pool)))
     }
 
+    /// Connect to the database without running migrations, for deployments that migrate the database separately.
+    ///
+    /// Pending migrations are still detected, and will be logged as a warning.
+    pub async fn connect_without_migrations(database_url: &str) -> Result<Self> {
+        let pool = Self::build_pool(database_url, &PoolOptions::default())?;
+
+        let pool_clone = pool.clone();
+        let pending = task::spawn_blocking(move || -> Result<usize> {
+            let mut conn = get_connection(&pool_clone, "pending_migrations")?;
+            Ok(migrations::pending_migrations(&mut conn)?)
+        })
+        .await
+        .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))??;
+
+        if pending > 0 {
+            warn!(
+                "Skipped running migrations, but the database has {pending} pending migration(s)"
+            );
+        }
+
+        Ok(Self(Arc::new(pool)))
+    }
+
+    /// Count the migrations that have not been applied to the database yet.
+    ///
+    /// Instances started with `RUN_MIGRATIONS=false` can use this to wait for the instance that migrates the database.
+    pub async fn pending_migration_count(&self) -> Result<usize> {
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<usize> {
+            let mut connection = get_connection(&pool, "pending_migration_count")?;
+            Ok(migrations::pending_migrations(&mut connection)?)
+        })
+        .await
+    }
+
+    fn build_pool(
+        database_url: &str,
+        options: &PoolOptions,
+    ) -> Result<Pool<ConnectionManager<PgConnection>>> {
+        // Neon + sync diesel can encounter ephemeral prepared statement invalidation.
+        // Disable statement cache so diesel doesn't reuse dropped prepared statements.
+        // Other Postgres servers benefit from the cache, so only an explicitly configured size is respected there.
+        if options.disable_statement_cache {
+            std::env::set_var("DIESEL_STATEMENT_CACHE_SIZE", "0");
+        }
+        let effective_url =
+            with_application_name(database_url, spoticord_config::database_app_name());
+        let manager = ConnectionManager::<PgConnection>::new(effective_url);
+        let pool = Pool::builder()
+            .max_size(options.max_size)
+            .connection_timeout(options.connection_timeout)
+            .event_handler(Box::new(watchdog::EventHandler))
+            .build(manager)
+            .map_err(DatabaseError::from)?;
+
+        watchdog::start();
+
+        Ok(pool)
+    }
+
     // User operations
 
     pub async fn get_user(&self, user_id: impl AsRef<str>) -> Result<User> {
         use schema::user::dsl::*;
 
         let pool = self.0.clone();
-        let uid = user_id.as_ref().to_string();
+        let uid = parse_user_id(user_id)?;
         retry_on_prepared_statement_error(move || -> Result<User> {
-            let mut connection = pool.get().map_err(DatabaseError::from)?;
+            let mut connection = get_connection(&pool, "get_user")?;
             let result = user
                 .filter(id.eq(&uid))
                 .select(User::as_select())
@@ -104,13 +384,35 @@ impl Database {
         .await
     }
 
+    /// Retrieve a user together with their linked Spotify account, if they have one, in a single query
+    pub async fn get_user_with_account(
+        &self,
+        user_id: impl AsRef<str>,
+    ) -> Result<(User, Option<Account>)> {
+        use schema::{account, user};
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<(User, Option<Account>)> {
+            let mut connection = get_connection(&pool, "get_user_with_account")?;
+            let (found, linked) = user::table
+                .left_join(account::table)
+                .filter(user::id.eq(&uid))
+                .select((User::as_select(), Option::<Account>::as_select()))
+                .first::<(User, Option<Account>)>(&mut connection)?;
+
+            Ok((found, linked.map(crypto::decrypt_account).transpose()?))
+        })
+        .await
+    }
+
     pub async fn create_user(&self, user_id: impl AsRef<str>) -> Result<User> {
         use schema::user::dsl::*;
 
         let pool = self.0.clone();
-        let uid = user_id.as_ref().to_string();
+        let uid = parse_user_id(user_id)?;
         retry_on_prepared_statement_error(move || -> Result<User> {
-            let mut connection = pool.get().map_err(DatabaseError::from)?;
+            let mut connection = get_connection(&pool, "create_user")?;
             let result = diesel::insert_into(user)
                 .values(id.eq(&uid))
                 .returning(User::as_returning())
@@ -124,224 +426,1794 @@ impl Database {
         use schema::user::dsl::*;
 
         let pool = self.0.clone();
-        let uid = user_id.as_ref().to_string();
-        retry_on_prepared_statement_error(move || -> Result<usize> {
-            let mut connection = pool.get().map_err(DatabaseError::from)?;
-            let affected = diesel::delete(user)
-                .filter(id.eq(&uid))
-                .execute(&mut connection)?;
-            Ok(affected)
+        let uid = parse_user_id(user_id)?;
+
+        let affected = retry_on_prepared_statement_error({
+            let uid = uid.clone();
+
+            move || -> Result<usize> {
+                let mut connection = get_connection(&pool, "delete_user")?;
+                let affected = diesel::delete(user)
+                    .filter(id.eq(&uid))
+                    .execute(&mut connection)?;
+                Ok(affected)
+            }
         })
-        .await
+        .await?;
+
+        token_cache::remove(&uid).await;
+
+        Ok(affected)
     }
 
+    /// Delete multiple users at once, cascading to their accounts and link requests.
+    ///
+    /// IDs that don't belong to a user are ignored, the returned count only includes users that were deleted.
+    pub async fn delete_users(&self, user_ids: &[String]) -> Result<usize> {
+        use schema::user::dsl::*;
+
+        let pool = self.0.clone();
+        let uids = user_ids
+            .iter()
+            .map(parse_user_id)
+            .collect::<Result<Vec<_>>>()?;
+        let deleted = retry_on_prepared_statement_error(move || -> Result<Vec<String>> {
+            let mut connection = get_connection(&pool, "delete_users")?;
+            let deleted = diesel::delete(user)
+                .filter(id.eq_any(&uids))
+                .returning(id)
+                .get_results(&mut connection)?;
+            Ok(deleted)
+        })
+        .await?;
+
+        for uid in &deleted {
+            token_cache::remove(uid).await;
+        }
+
+        Ok(deleted.len())
+    }
+
+    /// Retrieve a user, creating them if they don't exist yet.
+    ///
+    /// This is a single upsert, so concurrent calls for the same new user cannot race each other.
     pub async fn get_or_create_user(&self, user_id: impl AsRef<str>) -> Result<User> {
-        match self.get_user(&user_id).await {
-            Err(DatabaseError::NotFound) => self.create_user(user_id).await,
-            result => result,
+        use diesel::upsert::excluded;
+        use schema::user::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<User> {
+            let mut connection = get_connection(&pool, "get_or_create_user")?;
+            // The no-op update makes sure `RETURNING` also yields the row if it already exists
+            let result = diesel::insert_into(user)
+                .values(id.eq(&uid))
+                .on_conflict(id)
+                .do_update()
+                .set(id.eq(excluded(id)))
+                .returning(User::as_returning())
+                .get_result(&mut connection)?;
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Record that a user is active, creating them if they don't exist yet.
+    ///
+    /// Writes are coalesced: this is a no-op if the user was already touched within
+    /// [`spoticord_config::user_touch_interval`].
+    pub async fn touch_user(&self, user_id: impl AsRef<str>) -> Result<()> {
+        use diesel::upsert::excluded;
+        use schema::user::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(user_id)?;
+
+        if !recent_users::claim(&uid) {
+            return Ok(());
+        }
+
+        let result = retry_on_prepared_statement_error({
+            let uid = uid.clone();
+
+            move || -> Result<()> {
+                let mut connection = get_connection(&pool, "touch_user")?;
+                diesel::insert_into(user)
+                    .values((id.eq(&uid), last_seen.eq(Utc::now().naive_utc())))
+                    .on_conflict(id)
+                    .do_update()
+                    .set(last_seen.eq(excluded(last_seen)))
+                    .execute(&mut connection)?;
+                Ok(())
+            }
+        })
+        .await;
+
+        if result.is_err() {
+            recent_users::release(&uid);
         }
+
+        result
     }
 
+    /// Change a user's device name, returning the name it had before.
+    ///
+    /// Returns `None` if the user doesn't exist, in which case nothing is updated. The name is trimmed and truncated to
+    /// [`DEVICE_NAME_MAX_LENGTH`] characters, and [`DatabaseError::InvalidDeviceName`] is returned if nothing is left.
     pub async fn update_device_name(
         &self,
         user_id: impl AsRef<str>,
         _device_name: impl AsRef<str>,
-    ) -> Result<()> {
+    ) -> Result<Option<String>> {
+        use schema::user::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(user_id)?;
+        let dname = sanitize_device_name(_device_name.as_ref())?;
+        retry_transient(
+            move || -> Result<Option<String>> {
+                let mut connection = get_connection(&pool, "update_device_name")?;
+                connection.transaction(|connection| -> Result<Option<String>> {
+                    // Lock the row, so the returned name is the one that was actually replaced
+                    let previous = user
+                        .filter(id.eq(&uid))
+                        .select(device_name)
+                        .for_update()
+                        .first::<String>(connection)
+                        .optional()?;
+
+                    if previous.is_some() {
+                        diesel::update(user)
+                            .filter(id.eq(&uid))
+                            .set(device_name.eq(&dname))
+                            .execute(connection)?;
+                    }
+
+                    Ok(previous)
+                })
+            },
+            TransientError::ALL,
+            TRANSACTION_ATTEMPTS,
+        )
+        .await
+    }
+
+    pub async fn update_normalize(&self, user_id: impl AsRef<str>, enabled: bool) -> Result<()> {
         use schema::user::dsl::*;
 
         let pool = self.0.clone();
-        let uid = user_id.as_ref().to_string();
-        let dname = _device_name.as_ref().to_string();
+        let uid = parse_user_id(user_id)?;
         retry_on_prepared_statement_error(move || -> Result<()> {
-            let mut connection = pool.get().map_err(DatabaseError::from)?;
+            let mut connection = get_connection(&pool, "update_normalize")?;
             diesel::update(user)
                 .filter(id.eq(&uid))
-                .set(device_name.eq(&dname))
+                .set(normalize.eq(enabled))
                 .execute(&mut connection)?;
             Ok(())
         })
         .await
     }
 
-    // Account operations
-
-    pub async fn get_account(&self, _user_id: impl AsRef<str>) -> Result<Account> {
-        use schema::account::dsl::*;
+    /// Whether explicit tracks may be queued in sessions owned by a user
+    pub async fn get_allow_explicit(&self, user_id: impl AsRef<str>) -> Result<bool> {
+        let user = self.get_user(user_id).await?;
 
-        let pool = self.0.clone();
-        let uid = _user_id.as_ref().to_string();
-        retry_on_prepared_statement_error(move || -> Result<Account> {
-            let mut connection = pool.get().map_err(DatabaseError::from)?;
-            let result = account
-                .select(Account::as_select())
-                .filter(user_id.eq(&uid))
-                .first(&mut connection)?;
-            Ok(result)
-        })
-        .await
+        Ok(user.allow_explicit)
     }
 
-    pub async fn delete_account(&self, _user_id: impl AsRef<str>) -> Result<usize> {
-        use schema::account::dsl::*;
+    pub async fn update_allow_explicit(
+        &self,
+        user_id: impl AsRef<str>,
+        allowed: bool,
+    ) -> Result<()> {
+        use schema::user::dsl::*;
 
         let pool = self.0.clone();
-        let uid = _user_id.as_ref().to_string();
-        retry_on_prepared_statement_error(move || -> Result<usize> {
-            let mut connection = pool.get().map_err(DatabaseError::from)?;
-            let affected = diesel::delete(account)
-                .filter(user_id.eq(&uid))
+        let uid = parse_user_id(user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "update_allow_explicit")?;
+            diesel::update(user)
+                .filter(id.eq(&uid))
+                .set(allow_explicit.eq(allowed))
                 .execute(&mut connection)?;
-            Ok(affected)
+            Ok(())
         })
         .await
     }
 
-    pub async fn update_session_token(
+    pub async fn update_share_activity(
         &self,
-        _user_id: impl AsRef<str>,
-        _session_token: Option<String>,
+        user_id: impl AsRef<str>,
+        enabled: bool,
     ) -> Result<()> {
-        use schema::account::dsl::*;
+        use schema::user::dsl::*;
 
         let pool = self.0.clone();
-        let uid = _user_id.as_ref().to_string();
-        let token_opt = _session_token.clone();
+        let uid = parse_user_id(user_id)?;
         retry_on_prepared_statement_error(move || -> Result<()> {
-            let mut connection = pool.get().map_err(DatabaseError::from)?;
-            diesel::update(account)
-                .filter(user_id.eq(&uid))
-                .set(session_token.eq(token_opt.as_deref()))
+            let mut connection = get_connection(&pool, "update_share_activity")?;
+            diesel::update(user)
+                .filter(id.eq(&uid))
+                .set(share_activity.eq(enabled))
                 .execute(&mut connection)?;
             Ok(())
         })
         .await
     }
 
-    // Request operations
-
-    pub async fn get_request(&self, _user_id: impl AsRef<str>) -> Result<LinkRequest> {
-        use schema::link_request::dsl::*;
+    /// Of the given users, retrieve the ones that opted in to sharing what they're listening to
+    pub async fn filter_sharing_users(&self, user_ids: Vec<String>) -> Result<Vec<String>> {
+        use schema::user::dsl::*;
 
         let pool = self.0.clone();
-        let uid = _user_id.as_ref().to_string();
-        retry_on_prepared_statement_error(move || -> Result<LinkRequest> {
-            let mut connection = pool.get()?;
-            let result = link_request
-                .select(LinkRequest::as_select())
-                .filter(user_id.eq(&uid))
-                .first(&mut connection)?;
+        retry_on_prepared_statement_error(move || -> Result<Vec<String>> {
+            let mut connection = get_connection(&pool, "filter_sharing_users")?;
+            let result = user
+                .filter(id.eq_any(&user_ids))
+                .filter(share_activity.eq(true))
+                .select(id)
+                .load(&mut connection)?;
             Ok(result)
         })
         .await
     }
 
-    pub async fn delete_request(&self, _user_id: impl AsRef<str>) -> Result<usize> {
-        use schema::link_request::dsl::*;
+    /// Retrieve the Spotify device a user prefers playback to be sent to, if they have set one
+    pub async fn get_preferred_device(&self, user_id: impl AsRef<str>) -> Result<Option<String>> {
+        let user = self.get_user(user_id).await?;
+
+        Ok(user.preferred_device_id)
+    }
+
+    /// Set or clear the Spotify device a user prefers playback to be sent to
+    pub async fn update_preferred_device(
+        &self,
+        user_id: impl AsRef<str>,
+        device_id: Option<String>,
+    ) -> Result<()> {
+        use schema::user::dsl::*;
 
         let pool = self.0.clone();
-        let uid = _user_id.as_ref().to_string();
-        retry_on_prepared_statement_error(move || -> Result<usize> {
-            let mut connection = pool.get().map_err(DatabaseError::from)?;
-            let affected = diesel::delete(link_request)
-                .filter(user_id.eq(&uid))
+        let uid = parse_user_id(user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "update_preferred_device")?;
+            diesel::update(user)
+                .filter(id.eq(&uid))
+                .set(preferred_device_id.eq(&device_id))
                 .execute(&mut connection)?;
-            Ok(affected)
+            Ok(())
         })
         .await
     }
 
-    /// Create a new link request that expires after an hour
-    pub async fn create_request(&self, _user_id: impl AsRef<str>) -> Result<LinkRequest> {
-        use schema::link_request::dsl::*;
+    // Account operations
+
+    pub async fn get_account(&self, _user_id: impl AsRef<str>) -> Result<Account> {
+        use schema::account::dsl::*;
 
         let pool = self.0.clone();
-        let uid = _user_id.as_ref().to_string();
-        task::spawn_blocking(move || -> Result<LinkRequest> {
-            let mut connection = pool.get()?;
-            let _token: String = rand::thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(64)
-                .map(char::from)
-                .collect();
-            let _expires = (Utc::now() + Duration::hours(1)).naive_utc();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<Account> {
+            let mut connection = get_connection(&pool, "get_account")?;
+            let result = account
+                .select(Account::as_select())
+                .filter(user_id.eq(&uid))
+                .first(&mut connection)?;
+            crypto::decrypt_account(result)
+        })
+        .await
+    }
+
+    /// Retrieve the accounts of multiple users with a single query. Users without an account are left out.
+    pub async fn get_accounts(&self, user_ids: &[String]) -> Result<Vec<Account>> {
+        use schema::account::dsl::*;
+
+        if user_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let uids = user_ids
+            .iter()
+            .map(parse_user_id)
+            .collect::<Result<Vec<_>>>()?;
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<Vec<Account>> {
+            let mut connection = get_connection(&pool, "get_accounts")?;
+            account
+                .select(Account::as_select())
+                .filter(user_id.eq_any(&uids))
+                .load(&mut connection)?
+                .into_iter()
+                .map(crypto::decrypt_account)
+                .collect()
+        })
+        .await
+    }
+
+    /// Iterate over all linked accounts in batches of at most `batch_size`, ordered by user ID.
+    ///
+    /// Only one batch is held in memory at a time. Iteration stops at the first error returned by `f`.
+    pub async fn for_each_account<F>(&self, batch_size: i64, mut f: F) -> Result<()>
+    where
+        F: FnMut(Vec<Account>) -> Result<()>,
+    {
+        use schema::account::dsl::*;
+
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let pool = self.0.clone();
+            let after = cursor.clone();
+            let batch = retry_on_prepared_statement_error(move || -> Result<Vec<Account>> {
+                let mut connection = get_connection(&pool, "for_each_account")?;
+                let mut query = account
+                    .select(Account::as_select())
+                    .order(user_id.asc())
+                    .limit(batch_size)
+                    .into_boxed();
+
+                if let Some(after) = &after {
+                    query = query.filter(user_id.gt(after));
+                }
+
+                query
+                    .load(&mut connection)?
+                    .into_iter()
+                    .map(crypto::decrypt_account)
+                    .collect()
+            })
+            .await?;
+
+            let Some(last) = batch.last() else {
+                return Ok(());
+            };
+
+            cursor = Some(last.user_id.clone());
+            let done = (batch.len() as i64) < batch_size;
+
+            f(batch)?;
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Whether a user has linked a Spotify account that is not disabled, without loading the account itself
+    pub async fn account_exists(&self, _user_id: impl AsRef<str>) -> Result<bool> {
+        use diesel::dsl::exists;
+        use schema::account::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<bool> {
+            let mut connection = get_connection(&pool, "account_exists")?;
+            let result = diesel::select(exists(
+                account.filter(user_id.eq(&uid)).filter(disabled.eq(false)),
+            ))
+            .get_result(&mut connection)?;
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Retrieve the details of a user's linked Spotify account, without loading the tokens
+    pub async fn get_account_meta(&self, _user_id: impl AsRef<str>) -> Result<AccountMeta> {
+        use schema::{account, user};
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<AccountMeta> {
+            let mut connection = get_connection(&pool, "get_account_meta")?;
+            let result = account::table
+                .inner_join(user::table)
+                .filter(account::user_id.eq(&uid))
+                .select((
+                    account::user_id,
+                    account::username,
+                    user::device_name,
+                    account::expires,
+                    account::last_updated,
+                    account::disabled,
+                ))
+                .first(&mut connection)?;
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Create or replace the Spotify account linked to a user.
+    ///
+    /// Relinking invalidates any stored librespot credentials, so the session token is cleared.
+    pub async fn upsert_account(
+        &self,
+        _user_id: impl AsRef<str>,
+        _username: impl AsRef<str>,
+        _access_token: impl AsRef<str>,
+        _refresh_token: impl AsRef<str>,
+        _expires: DateTime<Utc>,
+    ) -> Result<Account> {
+        use schema::account::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        let uname = _username.as_ref().to_string();
+        let access = crypto::encrypt(_access_token.as_ref())?;
+        let refresh = crypto::encrypt(_refresh_token.as_ref())?;
+        let expires_val = _expires.naive_utc();
+
+        let result = retry_on_prepared_statement_error(move || -> Result<Account> {
+            let mut connection = get_connection(&pool, "upsert_account")?;
+            let result = diesel::insert_into(account)
+                .values((
+                    user_id.eq(&uid),
+                    username.eq(&uname),
+                    access_token.eq(&access),
+                    refresh_token.eq(&refresh),
+                    expires.eq(&expires_val),
+                ))
+                .on_conflict(user_id)
+                .do_update()
+                .set((
+                    username.eq(&uname),
+                    access_token.eq(&access),
+                    refresh_token.eq(&refresh),
+                    session_token.eq(None::<String>),
+                    expires.eq(&expires_val),
+                    disabled.eq(false),
+                ))
+                .returning(Account::as_returning())
+                .get_result(&mut connection)?;
+            crypto::decrypt_account(result)
+        })
+        .await?;
+
+        token_cache::remove(&result.user_id).await;
+        self.audit(
+            &result.user_id,
+            "link",
+            Some(format!("Linked Spotify account {}", result.username)),
+        )
+        .await;
+
+        Ok(result)
+    }
+
+    /// Store the Spotify account a user just linked, as a single transaction.
+    ///
+    /// The user is created if they don't exist yet, their account is created or replaced with `token`, and their link
+    /// request is deleted. Either all of these happen, or none of them do.
+    ///
+    /// Fails with [`DatabaseError::RefreshTokenFailure`] if `token` has no refresh token, as the account could never
+    /// be refreshed.
+    pub async fn link_account(
+        &self,
+        _user_id: impl AsRef<str>,
+        _username: impl AsRef<str>,
+        token: Token,
+    ) -> Result<Account> {
+        use schema::{account, link_request, user};
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        let uname = _username.as_ref().to_string();
+        let refresh_token = token
+            .refresh_token
+            .as_deref()
+            .ok_or(DatabaseError::RefreshTokenFailure)?;
+        let access = crypto::encrypt(&token.access_token)?;
+        let refresh = crypto::encrypt(refresh_token)?;
+        let expires_val = token
+            .expires_at
+            .unwrap_or_else(|| Utc::now() + token.expires_in)
+            .naive_utc();
+
+        let result = retry_transient(
+            move || -> Result<Account> {
+                let mut connection = get_connection(&pool, "link_account")?;
+                let linked = connection.transaction(|connection| -> Result<Account> {
+                    diesel::insert_into(user::table)
+                        .values(user::id.eq(&uid))
+                        .on_conflict_do_nothing()
+                        .execute(connection)?;
+
+                    let linked = diesel::insert_into(account::table)
+                        .values((
+                            account::user_id.eq(&uid),
+                            account::username.eq(&uname),
+                            account::access_token.eq(&access),
+                            account::refresh_token.eq(&refresh),
+                            account::expires.eq(&expires_val),
+                        ))
+                        .on_conflict(account::user_id)
+                        .do_update()
+                        .set((
+                            account::username.eq(&uname),
+                            account::access_token.eq(&access),
+                            account::refresh_token.eq(&refresh),
+                            account::session_token.eq(None::<String>),
+                            account::expires.eq(&expires_val),
+                            account::disabled.eq(false),
+                        ))
+                        .returning(Account::as_returning())
+                        .get_result(connection)?;
+
+                    diesel::delete(link_request::table)
+                        .filter(link_request::user_id.eq(&uid))
+                        .execute(connection)?;
+
+                    Ok(linked)
+                })?;
+
+                crypto::decrypt_account(linked)
+            },
+            TransientError::ALL,
+            TRANSACTION_ATTEMPTS,
+        )
+        .await?;
+
+        token_cache::remove(&result.user_id).await;
+        self.audit(
+            &result.user_id,
+            "link",
+            Some(format!("Linked Spotify account {}", result.username)),
+        )
+        .await;
+
+        Ok(result)
+    }
+
+    pub async fn delete_account(&self, _user_id: impl AsRef<str>) -> Result<usize> {
+        let uid = parse_user_id(_user_id)?;
+        let affected = self.remove_account(&uid).await?;
+
+        if affected != 0 {
+            self.audit(&uid, "unlink", None).await;
+        }
+
+        Ok(affected)
+    }
+
+    /// Delete a user's account without recording an unlink, for when the account is removed for another reason that
+    /// is audited by the caller
+    async fn remove_account(&self, uid: &str) -> Result<usize> {
+        use schema::account::dsl::*;
+
+        let pool = self.0.clone();
+
+        let affected = retry_on_prepared_statement_error({
+            let uid = uid.to_string();
+
+            move || -> Result<usize> {
+                let mut connection = get_connection(&pool, "delete_account")?;
+                let affected = diesel::delete(account)
+                    .filter(user_id.eq(&uid))
+                    .execute(&mut connection)?;
+                Ok(affected)
+            }
+        })
+        .await?;
+
+        token_cache::remove(uid).await;
+
+        Ok(affected)
+    }
+
+    /// Mark a user's account as disabled, keeping the row around. Disabled accounts are not refreshed, their stored
+    /// session is cleared, and [`Database::account_exists`] treats them as unlinked so the user can link their account
+    /// again.
+    pub async fn disable_account(&self, _user_id: impl AsRef<str>) -> Result<usize> {
+        use schema::account::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+
+        let affected = retry_on_prepared_statement_error({
+            let uid = uid.clone();
+
+            move || -> Result<usize> {
+                let mut connection = get_connection(&pool, "disable_account")?;
+                let affected = diesel::update(account)
+                    .filter(user_id.eq(&uid))
+                    .set((disabled.eq(true), session_token.eq(None::<String>)))
+                    .execute(&mut connection)?;
+                Ok(affected)
+            }
+        })
+        .await?;
+
+        token_cache::remove(&uid).await;
+
+        if affected != 0 {
+            self.audit(&uid, "disable", None).await;
+        }
+
+        Ok(affected)
+    }
+
+    pub async fn update_session_token(
+        &self,
+        _user_id: impl AsRef<str>,
+        _session_token: Option<String>,
+    ) -> Result<()> {
+        use schema::account::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        let token_opt = _session_token.clone();
+        retry_on_prepared_statement_error(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "update_session_token")?;
+            diesel::update(account)
+                .filter(user_id.eq(&uid))
+                .set(session_token.eq(token_opt.as_deref()))
+                .execute(&mut connection)?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Request operations
+
+    pub async fn get_request(&self, _user_id: impl AsRef<str>) -> Result<LinkRequest> {
+        use schema::link_request::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<LinkRequest> {
+            let mut connection = get_connection(&pool, "get_request")?;
+            let result = link_request
+                .select(LinkRequest::as_select())
+                .filter(user_id.eq(&uid))
+                .first(&mut connection)?;
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Retrieve a user's link request, only if it can still be used.
+    ///
+    /// Link requests expire [`spoticord_config::link_request_ttl`] (an hour by default) after they were created. An
+    /// expired request is treated like a missing one and is deleted, so the user can create a new one. Use
+    /// [`Database::get_request`] to retrieve the stored request regardless of its expiry.
+    pub async fn get_valid_request(
+        &self,
+        _user_id: impl AsRef<str>,
+    ) -> Result<Option<LinkRequest>> {
+        use schema::link_request::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<Option<LinkRequest>> {
+            let mut connection = get_connection(&pool, "get_valid_request")?;
+            let Some(request) = link_request
+                .select(LinkRequest::as_select())
+                .filter(user_id.eq(&uid))
+                .first(&mut connection)
+                .optional()?
+            else {
+                return Ok(None);
+            };
+
+            if !request.expired() {
+                return Ok(Some(request));
+            }
+
+            // Only delete the request if it wasn't replaced in the meantime
+            diesel::delete(
+                link_request
+                    .filter(user_id.eq(&uid))
+                    .filter(token.eq(&request.token)),
+            )
+            .execute(&mut connection)?;
+
+            Ok(None)
+        })
+        .await
+    }
+
+    pub async fn delete_request(&self, _user_id: impl AsRef<str>) -> Result<usize> {
+        use schema::link_request::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<usize> {
+            let mut connection = get_connection(&pool, "delete_request")?;
+            let affected = diesel::delete(link_request)
+                .filter(user_id.eq(&uid))
+                .execute(&mut connection)?;
+            Ok(affected)
+        })
+        .await
+    }
+
+    /// Create a new link request that expires after [`spoticord_config::link_request_ttl`]
+    pub async fn create_request(&self, _user_id: impl AsRef<str>) -> Result<LinkRequest> {
+        use schema::link_request::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        let ttl = Duration::from_std(spoticord_config::link_request_ttl())
+            .unwrap_or_else(|_| Duration::hours(1));
+        task::spawn_blocking(move || -> Result<LinkRequest> {
+            let mut connection = get_connection(&pool, "create_request")?;
+            let _token: String = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(LINK_TOKEN_LENGTH)
+                .map(char::from)
+                .collect();
+            let _expires = (Utc::now() + ttl).naive_utc();
             let request = diesel::insert_into(link_request)
                 .values((user_id.eq(&uid), token.eq(&_token), expires.eq(_expires)))
                 .on_conflict(user_id)
                 .do_update()
-                .set((token.eq(&_token), expires.eq(_expires)))
-                .returning(LinkRequest::as_returning())
-                .get_result(&mut connection)?;
-            Ok(request)
+                .set((token.eq(&_token), expires.eq(_expires)))
+                .returning(LinkRequest::as_returning())
+                .get_result(&mut connection)?;
+            Ok(request)
+        })
+        .await
+        .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))?
+    }
+
+    // Play history operations
+
+    /// Record that a user started playing a track, pruning their history down to the last [`PLAY_HISTORY_LIMIT`] plays
+    pub async fn record_play(
+        &self,
+        _user_id: impl AsRef<str>,
+        _track_uri: impl AsRef<str>,
+    ) -> Result<()> {
+        use schema::play_history::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        let uri = _track_uri.as_ref().to_string();
+        retry_on_prepared_statement_error(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "record_play")?;
+
+            diesel::insert_into(play_history)
+                .values((user_id.eq(&uid), track_uri.eq(&uri)))
+                .execute(&mut connection)?;
+
+            let cutoff = play_history
+                .filter(user_id.eq(&uid))
+                .order(id.desc())
+                .select(id)
+                .offset(PLAY_HISTORY_LIMIT)
+                .first::<i64>(&mut connection)
+                .optional()?;
+
+            if let Some(cutoff) = cutoff {
+                diesel::delete(play_history)
+                    .filter(user_id.eq(&uid))
+                    .filter(id.le(cutoff))
+                    .execute(&mut connection)?;
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Retrieve the most recent plays of a user, newest first
+    pub async fn recent_plays(
+        &self,
+        _user_id: impl AsRef<str>,
+        limit: i64,
+    ) -> Result<Vec<PlayHistory>> {
+        use schema::play_history::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<Vec<PlayHistory>> {
+            let mut connection = get_connection(&pool, "recent_plays")?;
+            let result = play_history
+                .filter(user_id.eq(&uid))
+                .order(id.desc())
+                .limit(limit)
+                .select(PlayHistory::as_select())
+                .load(&mut connection)?;
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Collect all data that is stored about a user, for a data export.
+    ///
+    /// Access, refresh and session tokens and link request tokens are never included.
+    pub async fn export_user_data(&self, _user_id: impl AsRef<str>) -> Result<UserExport> {
+        use schema::{account, favorite, link_request, play_history, user};
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<UserExport> {
+            let mut connection = get_connection(&pool, "export_user_data")?;
+
+            let exported_user = user::table
+                .filter(user::id.eq(&uid))
+                .select(User::as_select())
+                .first(&mut connection)?;
+
+            let account = account::table
+                .inner_join(user::table)
+                .filter(account::user_id.eq(&uid))
+                .select((
+                    account::user_id,
+                    account::username,
+                    user::device_name,
+                    account::expires,
+                    account::last_updated,
+                    account::disabled,
+                ))
+                .first::<AccountMeta>(&mut connection)
+                .optional()?;
+
+            let link_request_expires = link_request::table
+                .filter(link_request::user_id.eq(&uid))
+                .select(link_request::expires)
+                .first(&mut connection)
+                .optional()?;
+
+            let play_history = play_history::table
+                .filter(play_history::user_id.eq(&uid))
+                .order(play_history::id.desc())
+                .select(PlayHistory::as_select())
+                .load(&mut connection)?;
+
+            let favorites = favorite::table
+                .filter(favorite::user_id.eq(&uid))
+                .order(favorite::added_at.desc())
+                .select(Favorite::as_select())
+                .load(&mut connection)?;
+
+            Ok(UserExport {
+                user: exported_user,
+                account,
+                link_request_expires,
+                play_history,
+                favorites,
+            })
+        })
+        .await
+    }
+
+    // Favorite operations
+
+    /// Save a track as one of a user's favorites. Returns `false` if it already was one.
+    ///
+    /// Fails with [`DatabaseError::FavoritesFull`] if the user already has [`FAVORITES_LIMIT`] favorites.
+    pub async fn add_favorite(
+        &self,
+        _user_id: impl AsRef<str>,
+        _track_uri: impl AsRef<str>,
+    ) -> Result<bool> {
+        use schema::favorite::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        let uri = _track_uri.as_ref().to_string();
+        retry_transient(
+            move || -> Result<bool> {
+                let mut connection = get_connection(&pool, "add_favorite")?;
+
+                connection.transaction(|connection| -> Result<bool> {
+                    let favorites = favorite
+                        .filter(user_id.eq(&uid))
+                        .select(track_uri)
+                        .for_update()
+                        .load::<String>(connection)?;
+
+                    if favorites.contains(&uri) {
+                        return Ok(false);
+                    }
+
+                    if favorites.len() as i64 >= FAVORITES_LIMIT {
+                        return Err(DatabaseError::FavoritesFull);
+                    }
+
+                    diesel::insert_into(favorite)
+                        .values((user_id.eq(&uid), track_uri.eq(&uri)))
+                        .execute(connection)?;
+
+                    Ok(true)
+                })
+            },
+            TransientError::ALL,
+            TRANSACTION_ATTEMPTS,
+        )
+        .await
+    }
+
+    /// Remove a track from a user's favorites. Returns `false` if it wasn't one.
+    pub async fn remove_favorite(
+        &self,
+        _user_id: impl AsRef<str>,
+        _track_uri: impl AsRef<str>,
+    ) -> Result<bool> {
+        use schema::favorite::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        let uri = _track_uri.as_ref().to_string();
+        retry_on_prepared_statement_error(move || -> Result<bool> {
+            let mut connection = get_connection(&pool, "remove_favorite")?;
+            let affected = diesel::delete(favorite)
+                .filter(user_id.eq(&uid))
+                .filter(track_uri.eq(&uri))
+                .execute(&mut connection)?;
+
+            Ok(affected > 0)
+        })
+        .await
+    }
+
+    /// Retrieve a user's favorites, most recently added first
+    pub async fn get_favorites(&self, _user_id: impl AsRef<str>) -> Result<Vec<Favorite>> {
+        use schema::favorite::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = parse_user_id(_user_id)?;
+        retry_on_prepared_statement_error(move || -> Result<Vec<Favorite>> {
+            let mut connection = get_connection(&pool, "get_favorites")?;
+            let result = favorite
+                .filter(user_id.eq(&uid))
+                .order(added_at.desc())
+                .select(Favorite::as_select())
+                .load(&mut connection)?;
+            Ok(result)
+        })
+        .await
+    }
+
+    // Session state operations
+
+    /// Retrieve the channel and message ID of the playback embed that was last sent in a guild, if any
+    pub async fn get_playback_message(&self, guild: u64) -> Result<Option<(u64, u64)>> {
+        use schema::session_state::dsl::*;
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<Option<(u64, u64)>> {
+            let mut connection = get_connection(&pool, "get_playback_message")?;
+            let result = session_state
+                .filter(guild_id.eq(guild.to_string()))
+                .select((playback_channel_id, playback_message_id))
+                .first::<(Option<String>, Option<String>)>(&mut connection)
+                .optional()?;
+
+            Ok(match result {
+                Some((Some(channel), Some(message))) => {
+                    channel.parse().ok().zip(message.parse().ok())
+                }
+                _ => None,
+            })
+        })
+        .await
+    }
+
+    /// Remember the playback embed of a guild, so it can be updated again after the bot restarts
+    pub async fn set_playback_message(&self, guild: u64, channel: u64, message: u64) -> Result<()> {
+        use schema::session_state::dsl::*;
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "set_playback_message")?;
+            let values = (
+                playback_channel_id.eq(channel.to_string()),
+                playback_message_id.eq(message.to_string()),
+                updated_at.eq(diesel::dsl::now),
+            );
+
+            diesel::insert_into(session_state)
+                .values((guild_id.eq(guild.to_string()), values.clone()))
+                .on_conflict(guild_id)
+                .do_update()
+                .set(values)
+                .execute(&mut connection)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Forget the playback embed of a guild, for example because the message was deleted
+    pub async fn clear_playback_message(&self, guild: u64) -> Result<()> {
+        use schema::session_state::dsl::*;
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "clear_playback_message")?;
+
+            diesel::update(session_state.filter(guild_id.eq(guild.to_string())))
+                .set((
+                    playback_channel_id.eq(None::<String>),
+                    playback_message_id.eq(None::<String>),
+                    updated_at.eq(diesel::dsl::now),
+                ))
+                .execute(&mut connection)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Remember what the owner of a session was playing when it ended, so playback can be resumed later
+    pub async fn save_playback(
+        &self,
+        guild: u64,
+        owner: u64,
+        uri: impl AsRef<str>,
+        position: u32,
+    ) -> Result<()> {
+        use schema::session_state::dsl::*;
+
+        let pool = self.0.clone();
+        let uri = uri.as_ref().to_string();
+        retry_on_prepared_statement_error(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "save_playback")?;
+            let values = (
+                owner_id.eq(owner.to_string()),
+                track_uri.eq(&uri),
+                position_ms.eq(position.min(i32::MAX as u32) as i32),
+                playback_saved_at.eq(diesel::dsl::now),
+                updated_at.eq(diesel::dsl::now),
+            );
+
+            diesel::insert_into(session_state)
+                .values((guild_id.eq(guild.to_string()), values.clone()))
+                .on_conflict(guild_id)
+                .do_update()
+                .set(values)
+                .execute(&mut connection)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Retrieve and forget the playback that was saved for an owner in a guild, as a track URI and a position in
+    /// milliseconds.
+    ///
+    /// Playback that was saved more than a day ago is not returned.
+    pub async fn take_playback(&self, guild: u64, owner: u64) -> Result<Option<(String, u32)>> {
+        use schema::session_state::dsl::*;
+
+        let pool = self.0.clone();
+        retry_transient(
+            move || -> Result<Option<(String, u32)>> {
+                let mut connection = get_connection(&pool, "take_playback")?;
+                connection.transaction(|connection| -> Result<Option<(String, u32)>> {
+                    let saved = session_state
+                        .filter(guild_id.eq(guild.to_string()))
+                        .filter(owner_id.eq(owner.to_string()))
+                        .filter(
+                            playback_saved_at
+                                .gt(Utc::now().naive_utc() - Duration::hours(RESUME_WINDOW_HOURS)),
+                        )
+                        .select((track_uri, position_ms))
+                        .for_update()
+                        .first::<(Option<String>, Option<i32>)>(connection)
+                        .optional()?;
+
+                    diesel::update(session_state)
+                        .filter(guild_id.eq(guild.to_string()))
+                        .filter(owner_id.eq(owner.to_string()))
+                        .set((
+                            owner_id.eq(None::<String>),
+                            track_uri.eq(None::<String>),
+                            position_ms.eq(None::<i32>),
+                            playback_saved_at.eq(None::<chrono::NaiveDateTime>),
+                        ))
+                        .execute(connection)?;
+
+                    Ok(match saved {
+                        Some((Some(uri), Some(position))) => Some((uri, position.max(0) as u32)),
+                        _ => None,
+                    })
+                })
+            },
+            TransientError::ALL,
+            TRANSACTION_ATTEMPTS,
+        )
+        .await
+    }
+
+    // Guild operations
+
+    /// Record that the welcome message has been posted in a guild.
+    ///
+    /// Returns `false` if the guild had already been greeted, in which case no message should be posted.
+    pub async fn mark_guild_greeted(&self, guild: u64) -> Result<bool> {
+        use schema::greeted_guild::dsl::*;
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<bool> {
+            let mut connection = get_connection(&pool, "mark_guild_greeted")?;
+
+            let inserted = diesel::insert_into(greeted_guild)
+                .values(guild_id.eq(guild.to_string()))
+                .on_conflict_do_nothing()
+                .execute(&mut connection)?;
+
+            Ok(inserted == 1)
+        })
+        .await
+    }
+
+    /// Retrieve the commands that have been disabled in a guild
+    pub async fn get_disabled_commands(&self, guild: u64) -> Result<Vec<String>> {
+        use schema::guild_settings::dsl::*;
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<Vec<String>> {
+            let mut connection = get_connection(&pool, "get_disabled_commands")?;
+            let result = guild_settings
+                .filter(guild_id.eq(guild.to_string()))
+                .select(disabled_commands)
+                .first::<Vec<String>>(&mut connection)
+                .optional()?;
+
+            Ok(result.unwrap_or_default())
+        })
+        .await
+    }
+
+    /// Disable or enable a command in a guild, returning the commands that are disabled afterwards
+    pub async fn set_command_disabled(
+        &self,
+        guild: u64,
+        command: impl AsRef<str>,
+        disabled: bool,
+    ) -> Result<Vec<String>> {
+        use schema::guild_settings::dsl::*;
+
+        let pool = self.0.clone();
+        let command = command.as_ref().to_string();
+        retry_transient(
+            move || -> Result<Vec<String>> {
+                let mut connection = get_connection(&pool, "set_command_disabled")?;
+                connection.transaction(|connection| -> Result<Vec<String>> {
+                    diesel::insert_into(guild_settings)
+                        .values(guild_id.eq(guild.to_string()))
+                        .on_conflict_do_nothing()
+                        .execute(connection)?;
+
+                    let mut commands = guild_settings
+                        .filter(guild_id.eq(guild.to_string()))
+                        .select(disabled_commands)
+                        .for_update()
+                        .first::<Vec<String>>(connection)?;
+
+                    commands.retain(|name| *name != command);
+                    if disabled {
+                        commands.push(command.clone());
+                        commands.sort();
+                    }
+
+                    diesel::update(guild_settings.filter(guild_id.eq(guild.to_string())))
+                        .set(disabled_commands.eq(&commands))
+                        .execute(connection)?;
+
+                    Ok(commands)
+                })
+            },
+            TransientError::ALL,
+            TRANSACTION_ATTEMPTS,
+        )
+        .await
+    }
+
+    // Audit log operations
+
+    /// Record an operation that affected a user's linked account.
+    ///
+    /// This is best-effort: failures are logged, but never fail the operation that is being recorded.
+    pub async fn audit(&self, _user_id: impl AsRef<str>, _action: &str, _detail: Option<String>) {
+        use schema::audit_log::dsl::*;
+
+        let pool = self.0.clone();
+        let uid = _user_id.as_ref().to_string();
+        let act = _action.to_string();
+
+        let result = retry_on_prepared_statement_error({
+            let uid = uid.clone();
+            let act = act.clone();
+
+            move || -> Result<()> {
+                let mut connection = get_connection(&pool, "audit")?;
+                diesel::insert_into(audit_log)
+                    .values((user_id.eq(&uid), action.eq(&act), detail.eq(&_detail)))
+                    .execute(&mut connection)?;
+                Ok(())
+            }
+        })
+        .await;
+
+        if let Err(why) = result {
+            warn!("Failed to write audit log entry ({act} for user {uid}): {why}");
+        }
+    }
+
+    /// Retrieve the most recent audit log entries, newest first
+    pub async fn recent_audit(&self, limit: i64) -> Result<Vec<AuditEntry>> {
+        use schema::audit_log::dsl::*;
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<Vec<AuditEntry>> {
+            let mut connection = get_connection(&pool, "recent_audit")?;
+            let result = audit_log
+                .order(id.desc())
+                .limit(limit)
+                .select(AuditEntry::as_select())
+                .load(&mut connection)?;
+            Ok(result)
         })
         .await
-        .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))?
     }
 
     // Special operations
 
     /// Retrieve a user's Spotify access token. This token, if expired, will automatically be refreshed
     /// using the refresh token stored in the database. If this succeeds, the access token will be updated.
-    pub async fn get_access_token(&self, _user_id: impl AsRef<str>) -> Result<String> {
+    pub async fn get_access_token(&self, user_id: impl AsRef<str>) -> Result<String> {
+        let (token, _) = self.get_access_token_with_expiry(user_id).await?;
+
+        Ok(token)
+    }
+
+    /// Retrieve a user's Spotify access token together with the moment it expires.
+    ///
+    /// This refreshes the token the same way [`Database::get_access_token`] does, so callers that cache
+    /// the token only have to call this again once the expiry approaches.
+    pub async fn get_access_token_with_expiry(
+        &self,
+        _user_id: impl AsRef<str>,
+    ) -> Result<(String, DateTime<Utc>)> {
         use schema::account::dsl::*;
 
-        let uid = _user_id.as_ref().to_string();
+        let uid = parse_user_id(_user_id)?;
+        if let Some(cached) = token_cache::get(&uid).await {
+            return Ok(cached);
+        }
+
         let pool = self.0.clone();
         let mut result: Account = task::spawn_blocking({
             let pool = pool.clone();
             let uid = uid.clone();
             move || -> Result<Account> {
-                let mut connection = pool.get().map_err(DatabaseError::from)?;
+                let mut connection = get_connection(&pool, "get_access_token_with_expiry")?;
                 let result = account
                     .filter(user_id.eq(&uid))
                     .select(Account::as_select())
                     .first(&mut connection)?;
-                Ok(result)
+                crypto::decrypt_account(result)
             }
         })
         .await
         .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))??;
 
-        if result.expired_offset(Duration::minutes(1)) {
-            let refresh_token_value = result.refresh_token.clone();
-            let spotify = spoticord_config::get_spotify(Token {
-                refresh_token: Some(refresh_token_value),
-                ..Default::default()
-            });
-
-            let token = match spotify.refetch_token().await {
-                Ok(Some(token)) => token,
-                _ => {
-                    self.delete_account(&uid).await.ok();
-                    return Err(DatabaseError::RefreshTokenFailure);
+        if result.disabled {
+            return Err(DatabaseError::RefreshTokenFailure);
+        }
+
+        if result.needs_refresh() {
+            result = self.refresh_account(&uid, result.refresh_token).await?;
+        }
+
+        let expires_at = result.expires_at();
+        token_cache::insert(&uid, &result.access_token, expires_at).await;
+
+        Ok((result.access_token, expires_at))
+    }
+
+    /// Refresh a user's Spotify access token, regardless of whether it has expired.
+    ///
+    /// Returns the moment the new access token expires. If Spotify rejects the refresh token, the account is deleted
+    /// or disabled, and the user will have to link their Spotify account again. Disabled accounts are not refreshed,
+    /// as a successful refresh would enable them again.
+    pub async fn force_refresh_token(&self, _user_id: impl AsRef<str>) -> Result<DateTime<Utc>> {
+        let uid = parse_user_id(_user_id)?;
+        let current = self.get_account(&uid).await?;
+        if current.disabled {
+            return Err(DatabaseError::RefreshTokenFailure);
+        }
+        let refreshed = self.refresh_account(&uid, current.refresh_token).await?;
+
+        Ok(refreshed.expires_at())
+    }
+
+    /// Exchange a refresh token for a new access token, and store the new tokens in the user's account.
+    ///
+    /// If Spotify rejects the refresh token, the account is deleted or disabled depending on
+    /// [`spoticord_config::refresh_failure_action`].
+    ///
+    /// If Spotify keeps handing out unusable tokens, refreshing is suspended for a while after
+    /// [`refresh_budget::MAX_FAILURES`] consecutive failures, and fails immediately instead. The first of those failures
+    /// is a [`DatabaseError::RefreshSuspended`], so the user can be told about it once.
+    async fn refresh_account(&self, uid: &str, current_refresh_token: String) -> Result<Account> {
+        use schema::account::dsl::*;
+
+        if refresh_budget::exhausted(uid) {
+            return Err(refresh_budget::failure(uid));
+        }
+
+        let spotify = spoticord_config::get_spotify(Token {
+            refresh_token: Some(current_refresh_token),
+            ..Default::default()
+        });
+
+        let result = spotify.refetch_token().await;
+        spoticord_config::record_spotify_call("token_refresh", &result);
+
+        let token = match result {
+            Ok(Some(token))
+                if !token.access_token.is_empty()
+                    && token.expires_at.is_some_and(|at| at > Utc::now()) =>
+            {
+                token
+            }
+            Ok(Some(_)) => {
+                if refresh_budget::record_failure(uid) {
+                    warn!(
+                        "Spotify returned {} unusable tokens in a row for user {uid}, suspending token refreshes",
+                        refresh_budget::MAX_FAILURES
+                    );
                 }
-            };
 
-            let pool2 = pool.clone();
-            let uid2 = uid.clone();
-            let access_token_val = token.access_token.clone();
-            let refresh_token_val = token.refresh_token.clone();
-            let expires_val = token
-                .expires_at
-                .expect("token expires_at is none, we broke time")
-                .naive_utc();
-
-            result = task::spawn_blocking(move || -> Result<Account> {
-                let mut connection = pool2.get().map_err(DatabaseError::from)?;
+                return Err(refresh_budget::failure(uid));
+            }
+            _ => {
+                self.audit(
+                    uid,
+                    "refresh_failure",
+                    Some("Spotify rejected the refresh token".to_string()),
+                )
+                .await;
+
+                match spoticord_config::refresh_failure_action() {
+                    RefreshFailureAction::Delete => {
+                        // Already audited as a refresh failure, the user didn't unlink their account
+                        self.remove_account(uid).await.ok();
+                    }
+                    RefreshFailureAction::Disable => {
+                        self.disable_account(uid).await.ok();
+                    }
+                }
+
+                return Err(DatabaseError::RefreshTokenFailure);
+            }
+        };
+
+        refresh_budget::reset(uid);
+
+        let pool = self.0.clone();
+        let access_token_val = crypto::encrypt(&token.access_token)?;
+        let refresh_token_val = crypto::encrypt(token.refresh_token.as_deref().unwrap_or(""))?;
+        let expires_val = token
+            .expires_at
+            .expect("token expires_at is none, we broke time")
+            .naive_utc();
+
+        let updated = task::spawn_blocking({
+            let uid = uid.to_string();
+
+            move || -> Result<Account> {
+                let mut connection = get_connection(&pool, "refresh_account")?;
                 let updated = diesel::update(account)
-                    .filter(user_id.eq(&uid2))
+                    .filter(user_id.eq(&uid))
                     .set((
                         access_token.eq(&access_token_val),
-                        refresh_token.eq(refresh_token_val.as_deref().unwrap_or("")),
+                        refresh_token.eq(&refresh_token_val),
                         expires.eq(&expires_val),
+                        disabled.eq(false),
                     ))
                     .returning(Account::as_returning())
                     .get_result(&mut connection)?;
-                Ok(updated)
+                crypto::decrypt_account(updated)
+            }
+        })
+        .await
+        .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))??;
+
+        token_cache::remove(uid).await;
+
+        Ok(updated)
+    }
+
+    /// Delete users whose linked Spotify account has not been used for [`INACTIVE_USER_DAYS`] days.
+    ///
+    /// With `dry_run` nothing is deleted, and the amount of users that would be deleted is returned instead.
+    pub async fn delete_inactive_users(&self, dry_run: bool) -> Result<usize> {
+        use schema::{account, user};
+
+        let pool = self.0.clone();
+        let cutoff = (Utc::now() - Duration::days(INACTIVE_USER_DAYS)).naive_utc();
+
+        if dry_run {
+            return retry_on_prepared_statement_error(move || -> Result<usize> {
+                let mut connection = get_connection(&pool, "delete_inactive_users")?;
+                let inactive = account::table
+                    .filter(account::last_updated.lt(cutoff))
+                    .select(account::user_id);
+
+                let count = user::table
+                    .filter(user::id.eq_any(inactive))
+                    .count()
+                    .get_result::<i64>(&mut connection)?;
+
+                Ok(count as usize)
             })
-            .await
-            .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))??;
+            .await;
+        }
+
+        let deleted = retry_on_prepared_statement_error(move || -> Result<Vec<String>> {
+            let mut connection = get_connection(&pool, "delete_inactive_users")?;
+            let inactive = account::table
+                .filter(account::last_updated.lt(cutoff))
+                .select(account::user_id);
+
+            let deleted = diesel::delete(user::table.filter(user::id.eq_any(inactive)))
+                .returning(user::id)
+                .get_results::<String>(&mut connection)?;
+
+            Ok(deleted)
+        })
+        .await?;
+
+        for uid in &deleted {
+            token_cache::remove(uid).await;
+            self.audit(
+                uid,
+                "inactive_delete",
+                Some(format!("Inactive for {INACTIVE_USER_DAYS} days")),
+            )
+            .await;
+        }
+
+        Ok(deleted.len())
+    }
+
+    /// Delete all link requests that have expired.
+    ///
+    /// With `dry_run` nothing is deleted, and the amount of requests that would be deleted is returned instead.
+    pub async fn prune_expired_requests(&self, dry_run: bool) -> Result<usize> {
+        use schema::link_request::dsl::*;
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<usize> {
+            let mut connection = get_connection(&pool, "prune_expired_requests")?;
+            let now = Utc::now().naive_utc();
+
+            let affected = if dry_run {
+                link_request
+                    .filter(expires.lt(now))
+                    .count()
+                    .get_result::<i64>(&mut connection)? as usize
+            } else {
+                diesel::delete(link_request.filter(expires.lt(now))).execute(&mut connection)?
+            };
+
+            Ok(affected)
+        })
+        .await
+    }
+
+    /// Delete all link requests that have expired, returning the amount of requests that were removed
+    pub async fn cleanup_expired_requests(&self) -> Result<usize> {
+        self.prune_expired_requests(false).await
+    }
+
+    /// Keep removing expired link requests, so the `link_request` table doesn't grow forever
+    pub async fn cleanup_expired_requests_periodically(self) {
+        let mut interval = tokio::time::interval(REQUEST_CLEANUP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            match self.cleanup_expired_requests().await {
+                Ok(0) => {}
+                Ok(removed) => debug!("Removed {removed} expired link request(s)"),
+                Err(why) => warn!("Failed to remove expired link requests: {why}"),
+            }
+        }
+    }
+
+    /// Count users, linked accounts and link requests in a single query, to save round trips on the connection
+    pub async fn stats(&self) -> Result<DatabaseStats> {
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<DatabaseStats> {
+            let mut connection = get_connection(&pool, "stats")?;
+            let stats = diesel::sql_query(
+                "SELECT \
+                    (SELECT COUNT(*) FROM \"user\") AS users, \
+                    (SELECT COUNT(*) FROM account) AS linked_accounts, \
+                    COUNT(*) FILTER (WHERE link_request.expires >= $1) AS pending_requests, \
+                    COUNT(*) FILTER (WHERE link_request.expires < $1) AS expired_requests \
+                FROM link_request",
+            )
+            .bind::<diesel::sql_types::Timestamp, _>(Utc::now().naive_utc())
+            .get_result(&mut connection)?;
+
+            Ok(stats)
+        })
+        .await
+    }
+
+    /// Find linked accounts whose user no longer exists, returning their user IDs.
+    ///
+    /// These can only exist if a user was removed without cascading, for example by editing the database by hand.
+    pub async fn find_orphaned_accounts(&self) -> Result<Vec<String>> {
+        use schema::{account, user};
+
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<Vec<String>> {
+            let mut connection = get_connection(&pool, "find_orphaned_accounts")?;
+            let orphaned = account::table
+                .filter(account::user_id.ne_all(user::table.select(user::id)))
+                .select(account::user_id)
+                .order(account::user_id)
+                .load::<String>(&mut connection)?;
+
+            Ok(orphaned)
+        })
+        .await
+    }
+
+    /// Delete all linked accounts whose user no longer exists, see [`Database::find_orphaned_accounts`].
+    pub async fn cleanup_orphaned_accounts(&self) -> Result<usize> {
+        use schema::{account, user};
+
+        let pool = self.0.clone();
+        let deleted = retry_on_prepared_statement_error(move || -> Result<Vec<String>> {
+            let mut connection = get_connection(&pool, "cleanup_orphaned_accounts")?;
+            let deleted = diesel::delete(
+                account::table.filter(account::user_id.ne_all(user::table.select(user::id))),
+            )
+            .returning(account::user_id)
+            .get_results::<String>(&mut connection)?;
+
+            Ok(deleted)
+        })
+        .await?;
+
+        for uid in &deleted {
+            token_cache::remove(uid).await;
+            self.audit(uid, "orphan_delete", None).await;
+        }
+
+        Ok(deleted.len())
+    }
+
+    /// Whether the database can be reached, or database operations are currently failing fast
+    pub fn is_available(&self) -> bool {
+        !circuit_breaker::is_open()
+    }
+
+    /// Keep pinging the database while it is unavailable, so operations are accepted again as soon as it is back
+    pub async fn watch_availability(self) {
+        let mut interval = tokio::time::interval(AVAILABILITY_PROBE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if !self.is_available() {
+                _ = self.ping().await;
+            }
+        }
+    }
+
+    /// Check whether the database can be reached, by running a trivial query
+    pub async fn ping(&self) -> Result<()> {
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "ping")?;
+            diesel::sql_query("SELECT 1").execute(&mut connection)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Run a trivial query to confirm the database is reachable, and report its latency and the state of the pool
+    pub async fn health_check(&self) -> Result<HealthStatus> {
+        let pool = self.0.clone();
+        retry_on_prepared_statement_error(move || -> Result<HealthStatus> {
+            // The pool state is read before this check takes a connection itself
+            let state = pool.state();
+
+            let mut connection = get_connection(&pool, "health_check")?;
+            let start = std::time::Instant::now();
+            diesel::sql_query("SELECT 1").execute(&mut connection)?;
+
+            Ok(HealthStatus {
+                latency: start.elapsed(),
+                idle_connections: state.idle_connections,
+                in_use_connections: state.connections - state.idle_connections,
+            })
+        })
+        .await
+    }
+
+    /// Reclaim space and refresh planner statistics on the tables that see the most churn.
+    ///
+    /// `VACUUM` cannot run inside of a transaction, so every table is processed with its own statement.
+    /// This is skipped on backends that merely speak the Postgres protocol, as those usually don't support `VACUUM`.
+    pub async fn run_maintenance(&self) -> Result<()> {
+        let pool = self.0.clone();
+        task::spawn_blocking(move || -> Result<()> {
+            let mut connection = get_connection(&pool, "run_maintenance")?;
+
+            let version: String =
+                diesel::select(diesel::dsl::sql::<diesel::sql_types::Text>("version()"))
+                    .get_result(&mut connection)?;
+
+            if !version.starts_with("PostgreSQL") {
+                warn!("Skipping database maintenance, backend does not support VACUUM: {version}");
+                return Ok(());
+            }
+
+            let start = Instant::now();
+
+            for table in MAINTENANCE_TABLES {
+                diesel::sql_query(format!("VACUUM ANALYZE \"{table}\""))
+                    .execute(&mut connection)?;
+            }
+
+            info!("Database maintenance finished in {:?}", start.elapsed());
+
+            Ok(())
+        })
+        .await
+        .map_err(|_| DatabaseError::Diesel(diesel::result::Error::RollbackTransaction))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use diesel::result::{DatabaseErrorKind, Error};
+
+    use super::*;
+
+    fn database_error(kind: DatabaseErrorKind, message: &str) -> DatabaseError {
+        DatabaseError::Diesel(Error::DatabaseError(kind, Box::new(message.to_string())))
+    }
+
+    fn prepared_statement_error() -> DatabaseError {
+        database_error(
+            DatabaseErrorKind::Unknown,
+            "unnamed prepared statement does not exist",
+        )
+    }
+
+    /// An operation that fails with `error` for the first `failures` attempts, and counts how often it was run
+    fn flaky(
+        failures: u32,
+        error: fn() -> DatabaseError,
+    ) -> (Arc<AtomicU32>, impl Fn() -> Result<u32> + Send + Clone) {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+
+        let operation = move || {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+
+            if attempt <= failures {
+                Err(error())
+            } else {
+                Ok(attempt)
+            }
+        };
+
+        (attempts, operation)
+    }
+
+    #[test]
+    fn transient_errors_match_their_kind() {
+        let prepared = prepared_statement_error();
+        let serialization = database_error(
+            DatabaseErrorKind::SerializationFailure,
+            "could not serialize access due to concurrent update",
+        );
+        let deadlock = database_error(DatabaseErrorKind::Unknown, "deadlock detected");
+
+        assert!(TransientError::PreparedStatement.matches(&prepared));
+        assert!(!TransientError::PreparedStatement.matches(&serialization));
+        assert!(!TransientError::PreparedStatement.matches(&deadlock));
+
+        assert!(TransientError::SerializationFailure.matches(&serialization));
+        assert!(!TransientError::SerializationFailure.matches(&prepared));
+
+        assert!(TransientError::Deadlock.matches(&deadlock));
+        assert!(!TransientError::Deadlock.matches(&prepared));
+    }
+
+    #[test]
+    fn other_errors_are_not_transient() {
+        let errors = [
+            DatabaseError::NotFound,
+            DatabaseError::Diesel(Error::RollbackTransaction),
+            database_error(
+                DatabaseErrorKind::UniqueViolation,
+                "duplicate key value violates unique constraint",
+            ),
+        ];
+
+        for error in &errors {
+            assert!(
+                TransientError::ALL.iter().all(|kind| !kind.matches(error)),
+                "{error:?} should not be transient"
+            );
         }
+    }
+
+    #[tokio::test]
+    async fn succeeds_on_first_attempt() {
+        let (attempts, operation) = flaky(0, prepared_statement_error);
+
+        let result = retry_transient(operation, TransientError::ALL, 3).await;
+
+        assert!(matches!(result, Ok(1)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn succeeds_after_a_retry() {
+        let (attempts, operation) = flaky(1, prepared_statement_error);
+
+        let result = retry_on_prepared_statement_error(operation).await;
+
+        assert!(matches!(result, Ok(2)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let (attempts, operation) = flaky(u32::MAX, || {
+            database_error(DatabaseErrorKind::Unknown, "deadlock detected")
+        });
+
+        let result = retry_transient(operation, TransientError::ALL, 3).await;
+
+        assert!(result.is_err_and(|why| TransientError::Deadlock.matches(&why)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn passes_other_errors_through() {
+        let (attempts, operation) = flaky(1, || DatabaseError::NotFound);
+
+        let result = retry_transient(operation, TransientError::ALL, 3).await;
+
+        assert!(matches!(result, Err(DatabaseError::NotFound)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn only_retries_the_given_errors() {
+        let (attempts, operation) = flaky(1, || {
+            database_error(DatabaseErrorKind::SerializationFailure, "serialization")
+        });
+
+        let result = retry_on_prepared_statement_error(operation).await;
+
+        assert!(result.is_err_and(|why| TransientError::SerializationFailure.matches(&why)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn maps_a_panicking_operation_to_a_rollback() {
+        let result = retry_on_prepared_statement_error(|| -> Result<()> {
+            panic!("operation panicked");
+        })
+        .await;
 
-        Ok(result.access_token)
+        assert!(matches!(
+            result,
+            Err(DatabaseError::Diesel(Error::RollbackTransaction))
+        ));
     }
 }
@@ -1,14 +1,48 @@
 use diesel::pg::PgConnection;
+use diesel::sql_types::BigInt;
+use diesel::RunQueryDsl;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use log::error;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
+/// Key of the advisory lock that makes sure only one instance runs migrations at a time ("Spoticor")
+const MIGRATION_LOCK_KEY: i64 = 0x5370_6f74_6963_6f72;
+
+/// Count the migrations that have not been applied to the database yet
+pub fn pending_migrations(connection: &mut PgConnection) -> Result<usize, diesel::result::Error> {
+    match connection.pending_migrations(MIGRATIONS) {
+        Ok(pending) => Ok(pending.len()),
+        Err(e) => {
+            error!("Migration error: {e}");
+            Err(diesel::result::Error::RollbackTransaction)
+        }
+    }
+}
+
+/// Run all pending migrations while holding a Postgres advisory lock.
+///
+/// Other instances calling this at the same time will wait for the lock, after which they will find no pending migrations.
 pub fn run_migrations(connection: &mut PgConnection) -> Result<(), diesel::result::Error> {
-    match connection.run_pending_migrations(MIGRATIONS) {
+    diesel::sql_query("SELECT pg_advisory_lock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+        .execute(connection)?;
+
+    let result = match connection.run_pending_migrations(MIGRATIONS) {
         Ok(_) => Ok(()),
         Err(e) => {
-            eprintln!("Migration error: {:?}", e);
+            error!("Migration error: {e}");
             Err(diesel::result::Error::RollbackTransaction)
         }
-    }
+    };
+
+    // Always release the lock, even if the migrations failed
+    let unlock = diesel::sql_query("SELECT pg_advisory_unlock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+        .execute(connection);
+
+    result?;
+    unlock?;
+
+    Ok(())
 }
@@ -1,5 +1,6 @@
-use chrono::Utc;
-use diesel::prelude::*;
+use chrono::{DateTime, TimeZone, Utc};
+use diesel::{prelude::*, sql_types::BigInt};
+use serde::Serialize;
 
 #[derive(Queryable, Selectable, Debug)]
 #[diesel(table_name = super::schema::user)]
@@ -7,6 +8,10 @@ use diesel::prelude::*;
 pub struct User {
     pub id: String,
     pub device_name: String,
+    pub normalize: bool,
+    pub preferred_device_id: Option<String>,
+    pub allow_explicit: bool,
+    pub share_activity: bool,
 }
 
 #[derive(Queryable, Selectable, Debug)]
@@ -19,9 +24,15 @@ pub struct Account {
     pub refresh_token: String,
     pub session_token: Option<String>,
     pub expires: chrono::NaiveDateTime,
+    pub disabled: bool,
 }
 
 impl Account {
+    /// The moment the access token expires. The column holds a naive UTC timestamp.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&self.expires)
+    }
+
     pub fn expired(&self) -> bool {
         Utc::now().naive_utc() > self.expires
     }
@@ -29,6 +40,37 @@ impl Account {
     pub fn expired_offset(&self, offset: chrono::Duration) -> bool {
         Utc::now().naive_utc() > self.expires - offset
     }
+
+    /// Whether the access token expires within [`spoticord_config::token_refresh_margin`]
+    pub fn needs_refresh(&self) -> bool {
+        let margin = chrono::Duration::from_std(spoticord_config::token_refresh_margin())
+            .unwrap_or_else(|_| chrono::Duration::minutes(1));
+
+        self.expired_offset(margin)
+    }
+}
+
+/// The non-sensitive details of a linked Spotify account, without any tokens
+#[derive(Queryable, Debug)]
+pub struct AccountMeta {
+    pub user_id: String,
+    pub username: String,
+    pub device_name: String,
+    pub expires: chrono::NaiveDateTime,
+    pub last_updated: chrono::NaiveDateTime,
+    pub disabled: bool,
+}
+
+impl AccountMeta {
+    /// The moment the access token expires
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&self.expires)
+    }
+
+    /// The moment the account was last refreshed or relinked
+    pub fn last_updated_at(&self) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&self.last_updated)
+    }
 }
 
 #[derive(Queryable, Selectable, Debug)]
@@ -41,6 +83,11 @@ pub struct LinkRequest {
 }
 
 impl LinkRequest {
+    /// The moment the link request expires. The column holds a naive UTC timestamp.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&self.expires)
+    }
+
     pub fn expired(&self) -> bool {
         Utc::now().naive_utc() > self.expires
     }
@@ -49,3 +96,126 @@ impl LinkRequest {
         Utc::now().naive_utc() > self.expires - offset
     }
 }
+
+#[derive(Queryable, Selectable, Debug)]
+#[diesel(table_name = super::schema::play_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PlayHistory {
+    pub id: i64,
+    pub user_id: String,
+    pub track_uri: String,
+    pub played_at: chrono::NaiveDateTime,
+}
+
+/// A track a user bookmarked in Spoticord
+#[derive(Queryable, Selectable, Debug)]
+#[diesel(table_name = super::schema::favorite)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Favorite {
+    pub user_id: String,
+    pub track_uri: String,
+    pub added_at: chrono::NaiveDateTime,
+}
+
+/// An operation that affected a user's linked Spotify account
+#[derive(Queryable, Selectable, Debug)]
+#[diesel(table_name = super::schema::audit_log)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditEntry {
+    pub id: i64,
+    pub created_at: chrono::NaiveDateTime,
+    pub user_id: String,
+    pub action: String,
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    /// The moment the operation happened. The column holds a naive UTC timestamp.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&self.created_at)
+    }
+}
+
+/// Row counts across all tables, retrieved with a single query by [`crate::Database::stats`]
+#[derive(QueryableByName, Serialize, Debug, Clone, Copy)]
+pub struct DatabaseStats {
+    #[diesel(sql_type = BigInt)]
+    pub users: i64,
+    #[diesel(sql_type = BigInt)]
+    pub linked_accounts: i64,
+    #[diesel(sql_type = BigInt)]
+    pub pending_requests: i64,
+    #[diesel(sql_type = BigInt)]
+    pub expired_requests: i64,
+}
+
+/// The result of [`crate::Database::health_check`]
+#[derive(Debug, Clone, Copy)]
+pub struct HealthStatus {
+    /// How long a trivial query took to complete
+    pub latency: std::time::Duration,
+
+    pub idle_connections: u32,
+    pub in_use_connections: u32,
+}
+
+/// Everything Spoticord stores about a user, with all tokens left out
+#[derive(Debug)]
+pub struct UserExport {
+    pub user: User,
+    pub account: Option<AccountMeta>,
+
+    /// When the user's pending link request expires, if they have one
+    pub link_request_expires: Option<chrono::NaiveDateTime>,
+
+    pub play_history: Vec<PlayHistory>,
+    pub favorites: Vec<Favorite>,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn account_expiring_in(remaining: Duration) -> Account {
+        Account {
+            user_id: "1".to_string(),
+            username: "user".to_string(),
+            access_token: String::new(),
+            refresh_token: String::new(),
+            session_token: None,
+            expires: (Utc::now() + remaining).naive_utc(),
+            disabled: false,
+        }
+    }
+
+    #[test]
+    fn expired_offset_without_offset_matches_expired() {
+        let valid = account_expiring_in(Duration::minutes(10));
+        let expired = account_expiring_in(Duration::minutes(-10));
+
+        assert!(!valid.expired_offset(Duration::zero()));
+        assert_eq!(valid.expired_offset(Duration::zero()), valid.expired());
+
+        assert!(expired.expired_offset(Duration::zero()));
+        assert_eq!(expired.expired_offset(Duration::zero()), expired.expired());
+    }
+
+    #[test]
+    fn expired_offset_around_the_remaining_time() {
+        let account = account_expiring_in(Duration::minutes(10));
+
+        // Just short of the remaining lifetime the token is still usable, just past it the token counts as expired
+        assert!(!account.expired_offset(Duration::minutes(10) - Duration::seconds(5)));
+        assert!(account.expired_offset(Duration::minutes(10) + Duration::seconds(5)));
+    }
+
+    #[test]
+    fn expired_offset_with_negative_offset_extends_the_lifetime() {
+        let account = account_expiring_in(Duration::seconds(-30));
+
+        assert!(account.expired());
+        assert!(!account.expired_offset(Duration::minutes(-1)));
+    }
+}
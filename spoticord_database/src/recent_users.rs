@@ -0,0 +1,52 @@
+//! Remembers which users recently had their activity recorded, so that a user running a lot of commands
+//! doesn't cause a database write for every single one of them.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+
+/// Above this amount of entries, expired entries are removed before a new one is added
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Users mapped to the moment their activity has to be recorded again
+static RECENT: LazyLock<Mutex<HashMap<String, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Claim the write for a user. Returns `false` if their activity was already recorded within the interval.
+///
+/// Every entry lives up to 10% longer than the interval, so users that were seen together (e.g. after a
+/// restart) don't all have to be written again at the same moment.
+pub fn claim(user_id: &str) -> bool {
+    let interval = spoticord_config::user_touch_interval();
+    if interval.is_zero() {
+        return true;
+    }
+
+    let now = Instant::now();
+    let mut recent = RECENT.lock().expect("mutex poisoned");
+
+    if recent.get(user_id).is_some_and(|until| *until > now) {
+        return false;
+    }
+
+    if recent.len() >= PRUNE_THRESHOLD {
+        recent.retain(|_, until| *until > now);
+    }
+
+    let jitter = rand::thread_rng().gen_range(0..=interval.as_millis() as u64 / 10);
+    recent.insert(
+        user_id.to_string(),
+        now + interval + Duration::from_millis(jitter),
+    );
+
+    true
+}
+
+/// Give up a claimed write that didn't go through, so the next command tries again
+pub fn release(user_id: &str) {
+    RECENT.lock().expect("mutex poisoned").remove(user_id);
+}
@@ -0,0 +1,114 @@
+//! Tracks consecutive token refresh failures per account, so that an account for which Spotify keeps
+//! returning unusable tokens isn't refreshed over and over again.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::error::DatabaseError;
+
+/// The amount of consecutive failed refreshes after which refreshing is suspended
+pub const MAX_FAILURES: u32 = 3;
+
+/// How long failures are remembered, counted from the first failure
+const FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+struct Failures {
+    count: u32,
+    since: Instant,
+
+    /// Whether the user has been told that refreshing is suspended
+    notified: bool,
+}
+
+static FAILURES: LazyLock<Mutex<HashMap<String, Failures>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Whether refreshing is currently suspended for a user
+pub fn exhausted(user_id: &str) -> bool {
+    let mut failures = FAILURES.lock().expect("mutex poisoned");
+
+    match failures.get(user_id) {
+        Some(entry) if entry.since.elapsed() > FAILURE_WINDOW => {
+            failures.remove(user_id);
+            false
+        }
+        Some(entry) => entry.count >= MAX_FAILURES,
+        None => false,
+    }
+}
+
+/// Record a failed refresh. Returns `true` if this failure caused refreshing to be suspended.
+pub fn record_failure(user_id: &str) -> bool {
+    let mut failures = FAILURES.lock().expect("mutex poisoned");
+
+    let entry = failures
+        .entry(user_id.to_string())
+        .and_modify(|entry| {
+            if entry.since.elapsed() > FAILURE_WINDOW {
+                entry.count = 0;
+                entry.since = Instant::now();
+                entry.notified = false;
+            }
+        })
+        .or_insert_with(|| Failures {
+            count: 0,
+            since: Instant::now(),
+            notified: false,
+        });
+
+    entry.count += 1;
+    entry.count == MAX_FAILURES
+}
+
+/// The error for a refresh that failed or was refused while refreshing is suspended.
+///
+/// The first one after refreshing got suspended is [`DatabaseError::RefreshSuspended`], so the user is told about it
+/// once per suspension, every other one is [`DatabaseError::RefreshTokenFailure`].
+pub fn failure(user_id: &str) -> DatabaseError {
+    let mut failures = FAILURES.lock().expect("mutex poisoned");
+
+    match failures.get_mut(user_id) {
+        Some(entry) if entry.count >= MAX_FAILURES && !entry.notified => {
+            entry.notified = true;
+            DatabaseError::RefreshSuspended
+        }
+        _ => DatabaseError::RefreshTokenFailure,
+    }
+}
+
+/// Forget all failures of a user, after a successful refresh
+pub fn reset(user_id: &str) {
+    FAILURES.lock().expect("mutex poisoned").remove(user_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suspension_is_reported_once() {
+        let user_id = "refresh-budget-suspension";
+
+        for _ in 1..MAX_FAILURES {
+            assert!(!record_failure(user_id));
+            assert!(matches!(
+                failure(user_id),
+                DatabaseError::RefreshTokenFailure
+            ));
+        }
+
+        assert!(record_failure(user_id));
+        assert!(exhausted(user_id));
+        assert!(matches!(failure(user_id), DatabaseError::RefreshSuspended));
+        assert!(matches!(
+            failure(user_id),
+            DatabaseError::RefreshTokenFailure
+        ));
+
+        reset(user_id);
+        assert!(!exhausted(user_id));
+    }
+}
@@ -1,5 +1,16 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_log (id) {
+        id -> Int8,
+        created_at -> Timestamp,
+        user_id -> Varchar,
+        #[max_length = 32]
+        action -> Varchar,
+        detail -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     account (user_id) {
         user_id -> Varchar,
@@ -13,6 +24,30 @@ diesel::table! {
         session_token -> Nullable<Varchar>,
         expires -> Timestamp,
         last_updated -> Timestamp,
+        disabled -> Bool,
+    }
+}
+
+diesel::table! {
+    favorite (user_id, track_uri) {
+        user_id -> Varchar,
+        #[max_length = 128]
+        track_uri -> Varchar,
+        added_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    greeted_guild (guild_id) {
+        guild_id -> Varchar,
+        greeted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    guild_settings (guild_id) {
+        guild_id -> Varchar,
+        disabled_commands -> Array<Text>,
     }
 }
 
@@ -24,19 +59,56 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    play_history (id) {
+        id -> Int8,
+        user_id -> Varchar,
+        #[max_length = 128]
+        track_uri -> Varchar,
+        played_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    session_state (guild_id) {
+        guild_id -> Varchar,
+        playback_channel_id -> Nullable<Varchar>,
+        playback_message_id -> Nullable<Varchar>,
+        updated_at -> Timestamp,
+        owner_id -> Nullable<Varchar>,
+        track_uri -> Nullable<Varchar>,
+        position_ms -> Nullable<Int4>,
+        playback_saved_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     user (id) {
         id -> Varchar,
         #[max_length = 32]
         device_name -> Varchar,
+        normalize -> Bool,
+        #[max_length = 64]
+        preferred_device_id -> Nullable<Varchar>,
+        allow_explicit -> Bool,
+        last_seen -> Nullable<Timestamp>,
+        share_activity -> Bool,
     }
 }
 
 diesel::joinable!(account -> user (user_id));
+diesel::joinable!(favorite -> user (user_id));
 diesel::joinable!(link_request -> user (user_id));
+diesel::joinable!(play_history -> user (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     account,
+    audit_log,
+    favorite,
+    greeted_guild,
+    guild_settings,
     link_request,
+    play_history,
+    session_state,
     user,
 );
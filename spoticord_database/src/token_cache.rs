@@ -0,0 +1,177 @@
+//! Caches Spotify access tokens, so that retrieving a token doesn't require a database query every time.
+//!
+//! Depending on [`spoticord_config::token_cache_backend`] tokens are kept in memory, in the KV store (where they are
+//! shared between instances and survive restarts), or not cached at all.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use redis::{Client, Commands, Connection, RedisResult};
+use spoticord_config::TokenCacheBackend;
+use tokio::task;
+
+use crate::crypto;
+
+const KEY_PREFIX: &str = "spoticord-token:";
+
+#[derive(Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl CachedToken {
+    /// Whether the token can still be used without refreshing it first
+    fn is_fresh(&self) -> bool {
+        let margin = chrono::Duration::from_std(spoticord_config::token_refresh_margin())
+            .unwrap_or_else(|_| chrono::Duration::minutes(1));
+
+        self.expires_at - margin > Utc::now()
+    }
+}
+
+enum Backend {
+    Memory(Mutex<HashMap<String, CachedToken>>),
+    Kv {
+        client: Client,
+        connection: Mutex<Option<Connection>>,
+    },
+    None,
+}
+
+static BACKEND: LazyLock<Backend> =
+    LazyLock::new(|| match spoticord_config::token_cache_backend() {
+        TokenCacheBackend::Memory => Backend::Memory(Mutex::new(HashMap::new())),
+        TokenCacheBackend::None => Backend::None,
+        TokenCacheBackend::Kv => {
+            let url =
+                spoticord_config::kv_url().expect("KV backend is only selected if KV_URL is set");
+
+            match Client::open(url) {
+                Ok(client) => Backend::Kv {
+                    client,
+                    connection: Mutex::new(None),
+                },
+                Err(why) => {
+                    warn!("Invalid KV_URL, falling back to the in-memory token cache: {why}");
+                    Backend::Memory(Mutex::new(HashMap::new()))
+                }
+            }
+        }
+    });
+
+/// Retrieve a cached access token and the moment it expires, if it doesn't need to be refreshed yet
+pub async fn get(user_id: &str) -> Option<(String, DateTime<Utc>)> {
+    let cached = match &*BACKEND {
+        Backend::Memory(tokens) => tokens.lock().expect("mutex poisoned").get(user_id).cloned(),
+        Backend::Kv { .. } => {
+            let key = format!("{KEY_PREFIX}{user_id}");
+            let value = kv(move |connection| connection.get::<_, Option<String>>(key))
+                .await
+                .flatten()?;
+
+            parse(&value)
+        }
+        Backend::None => None,
+    }?;
+
+    cached
+        .is_fresh()
+        .then_some((cached.access_token, cached.expires_at))
+}
+
+/// Cache an access token until it needs to be refreshed
+pub async fn insert(user_id: &str, access_token: &str, expires_at: DateTime<Utc>) {
+    let token = CachedToken {
+        access_token: access_token.to_string(),
+        expires_at,
+    };
+
+    if !token.is_fresh() {
+        return;
+    }
+
+    match &*BACKEND {
+        Backend::Memory(tokens) => {
+            tokens
+                .lock()
+                .expect("mutex poisoned")
+                .insert(user_id.to_string(), token);
+        }
+        Backend::Kv { .. } => {
+            // Tokens in the KV store are encrypted just like the ones in the database
+            let Ok(encrypted) = crypto::encrypt(&token.access_token) else {
+                return;
+            };
+
+            let key = format!("{KEY_PREFIX}{user_id}");
+            let value = format!("{}:{encrypted}", expires_at.timestamp());
+            let ttl = (expires_at - Utc::now()).num_seconds().max(1) as u64;
+
+            kv(move |connection| connection.set_ex::<_, _, ()>(key, value, ttl)).await;
+        }
+        Backend::None => {}
+    }
+}
+
+/// Forget a user's cached access token, after it was refreshed or the account was removed
+pub async fn remove(user_id: &str) {
+    match &*BACKEND {
+        Backend::Memory(tokens) => {
+            tokens.lock().expect("mutex poisoned").remove(user_id);
+        }
+        Backend::Kv { .. } => {
+            let key = format!("{KEY_PREFIX}{user_id}");
+
+            kv(move |connection| connection.del::<_, ()>(key)).await;
+        }
+        Backend::None => {}
+    }
+}
+
+/// Parse a token stored in the KV store as `<expiry timestamp>:<token>`
+fn parse(value: &str) -> Option<CachedToken> {
+    let (expires_at, access_token) = value.split_once(':')?;
+
+    Some(CachedToken {
+        access_token: crypto::decrypt(access_token).ok()?,
+        expires_at: DateTime::from_timestamp(expires_at.parse().ok()?, 0)?,
+    })
+}
+
+/// Run a command against the KV store, reconnecting if the previous connection broke.
+///
+/// Failures are logged and treated as a cache miss, so an unavailable KV store only costs a database query.
+async fn kv<T: Send + 'static>(
+    command: impl FnOnce(&mut Connection) -> RedisResult<T> + Send + 'static,
+) -> Option<T> {
+    let Backend::Kv { client, connection } = &*BACKEND else {
+        return None;
+    };
+
+    task::spawn_blocking(move || {
+        let mut connection = connection.lock().expect("mutex poisoned");
+
+        if connection.is_none() {
+            *connection = client
+                .get_connection()
+                .inspect_err(|why| warn!("Failed to connect to the KV store: {why}"))
+                .ok();
+        }
+
+        let result = command(connection.as_mut()?);
+        if let Err(why) = &result {
+            warn!("Token cache command failed: {why}");
+            *connection = None;
+        }
+
+        result.ok()
+    })
+    .await
+    .ok()
+    .flatten()
+}
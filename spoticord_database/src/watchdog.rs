@@ -0,0 +1,117 @@
+//! Keeps track of how long database connections are checked out, and by which operation.
+//!
+//! The pool usually holds a single connection, so an operation that holds on to it for too long stalls every other
+//! database operation. Operations that hold the connection for longer than [`spoticord_config::slow_query_threshold`]
+//! are logged, both while they are still running and once they release the connection.
+
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    sync::{LazyLock, Mutex, Once},
+    thread,
+    time::{Duration, Instant},
+};
+
+use diesel::r2d2::event::{CheckinEvent, CheckoutEvent, HandleEvent};
+use log::warn;
+use spoticord_utils::metrics;
+
+/// How often the watchdog checks for connections that are held for too long, at most
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+thread_local! {
+    /// The operation that is about to check out a connection on this thread
+    static OPERATION: Cell<&'static str> = const { Cell::new("unknown") };
+}
+
+struct Checkout {
+    operation: &'static str,
+    since: Instant,
+    reported: bool,
+}
+
+static CHECKOUTS: LazyLock<Mutex<HashMap<u64, Checkout>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Name the operation for the next connection that is checked out on this thread
+pub fn set_operation(operation: &'static str) {
+    OPERATION.set(operation);
+}
+
+/// Start the thread that reports connections which are held for too long. Does nothing if it is already running.
+pub fn start() {
+    static STARTED: Once = Once::new();
+
+    let Some(threshold) = spoticord_config::slow_query_threshold() else {
+        return;
+    };
+
+    STARTED.call_once(|| {
+        let interval = (threshold / 2).min(MAX_POLL_INTERVAL);
+
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+
+            let mut checkouts = CHECKOUTS.lock().expect("mutex poisoned");
+            for checkout in checkouts.values_mut() {
+                let held = checkout.since.elapsed();
+
+                if !checkout.reported && held > threshold {
+                    warn!(
+                        "Database connection has been held by `{}` for {} ms and is still in use",
+                        checkout.operation,
+                        held.as_millis()
+                    );
+
+                    checkout.reported = true;
+                }
+            }
+        });
+    });
+}
+
+/// Pool event handler that records every checkout, so the watchdog knows which operation holds a connection
+#[derive(Debug)]
+pub struct EventHandler;
+
+impl HandleEvent for EventHandler {
+    fn handle_checkout(&self, event: CheckoutEvent) {
+        let operation = OPERATION.replace("unknown");
+
+        CHECKOUTS.lock().expect("mutex poisoned").insert(
+            event.id(),
+            Checkout {
+                operation,
+                since: Instant::now(),
+                reported: false,
+            },
+        );
+    }
+
+    fn handle_checkin(&self, event: CheckinEvent) {
+        let Some(checkout) = CHECKOUTS
+            .lock()
+            .expect("mutex poisoned")
+            .remove(&event.id())
+        else {
+            return;
+        };
+
+        let held = event.duration();
+
+        metrics::observe_histogram(
+            "spoticord_database_connection_hold_seconds",
+            &[("operation", checkout.operation)],
+            held.as_secs_f64(),
+        );
+
+        match spoticord_config::slow_query_threshold() {
+            Some(threshold) if held > threshold => warn!(
+                "Database connection was held by `{}` for {} ms",
+                checkout.operation,
+                held.as_millis()
+            ),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Tests that run against a real Postgres database.
+//!
+//! They connect to `TEST_DATABASE_URL` and are skipped if it is not set. Every test uses its own random user IDs, so
+//! the tests can run in parallel against the same database.
+
+use chrono::{Duration, Utc};
+use rand::Rng;
+use spoticord_database::{error::DatabaseResultExt, Database};
+
+async fn connect() -> Option<Database> {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set, skipping");
+        return None;
+    };
+
+    Some(
+        Database::connect_with_url(&url)
+            .await
+            .expect("failed to connect to the test database"),
+    )
+}
+
+fn random_user_id() -> String {
+    rand::thread_rng()
+        .gen_range(100_000_000_000_000_000..900_000_000_000_000_000u64)
+        .to_string()
+}
+
+#[tokio::test]
+async fn delete_users_ignores_missing_ids() {
+    let Some(db) = connect().await else {
+        return;
+    };
+
+    let existing = [random_user_id(), random_user_id()];
+    for user_id in &existing {
+        db.create_user(user_id).await.unwrap();
+    }
+
+    let mut user_ids = existing.to_vec();
+    user_ids.push(random_user_id());
+
+    assert_eq!(db.delete_users(&user_ids).await.unwrap(), 2);
+
+    for user_id in &user_ids {
+        assert!(db.get_user(user_id).await.optional().unwrap().is_none());
+    }
+
+    assert_eq!(db.delete_users(&user_ids).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn get_user_with_account_returns_the_linked_account() {
+    let Some(db) = connect().await else {
+        return;
+    };
+
+    let user_id = random_user_id();
+    db.create_user(&user_id).await.unwrap();
+    db.upsert_account(
+        &user_id,
+        "spotify-user",
+        "access-token",
+        "refresh-token",
+        Utc::now() + Duration::hours(1),
+    )
+    .await
+    .unwrap();
+
+    let (user, account) = db.get_user_with_account(&user_id).await.unwrap();
+    let account = account.expect("the user has linked an account");
+
+    assert_eq!(user.id, user_id);
+    assert_eq!(account.user_id, user_id);
+    assert_eq!(account.username, "spotify-user");
+    assert_eq!(account.access_token, "access-token");
+
+    db.delete_user(&user_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn get_user_with_account_without_account() {
+    let Some(db) = connect().await else {
+        return;
+    };
+
+    let user_id = random_user_id();
+    db.create_user(&user_id).await.unwrap();
+
+    let (user, account) = db.get_user_with_account(&user_id).await.unwrap();
+
+    assert_eq!(user.id, user_id);
+    assert!(account.is_none());
+
+    db.delete_user(&user_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn get_user_with_account_for_missing_user() {
+    let Some(db) = connect().await else {
+        return;
+    };
+
+    let result = db.get_user_with_account(random_user_id()).await;
+
+    assert!(result.optional().unwrap().is_none());
+}
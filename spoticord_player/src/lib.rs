@@ -74,6 +74,7 @@ impl Player {
         credentials: Credentials,
         call: Arc<Mutex<Call>>,
         device_name: impl Into<String>,
+        normalize: bool,
     ) -> Result<(PlayerHandle, mpsc::Receiver<PlayerEvent>, Vec<u8>), librespot::core::Error> {
         let (event_tx, event_rx) = mpsc::channel(16);
 
@@ -89,7 +90,13 @@ impl Player {
         drop(call_lock);
 
         // Create librespot audio streamer
-        let session = SpotifySession::new(SessionConfig::default(), None);
+        let session = SpotifySession::new(
+            SessionConfig {
+                proxy: spoticord_config::https_proxy().cloned(),
+                ..Default::default()
+            },
+            None,
+        );
         let mixer = (mixer::find(Some("softvol")).expect("missing softvol mixer"))(MixerConfig {
             volume_ctrl: VolumeCtrl::Log(VolumeCtrl::DEFAULT_DB_RANGE),
             ..Default::default()
@@ -100,6 +107,8 @@ impl Player {
             PlayerConfig {
                 // 96kbps causes audio key errors, so enjoy the quality upgrade
                 bitrate: Bitrate::Bitrate160,
+                // Uses the loudness metadata Spotify provides with each track
+                normalisation: normalize,
                 ..Default::default()
             },
             session.clone(),
@@ -155,6 +164,9 @@ impl Player {
         // Keep auth data to reuse later for faster reconnections and less authentication requests to Spotify
         let auth_data = session.auth_data();
 
+        // Used to target this player through the Spotify Web API
+        let device_id = session.device_id().to_string();
+
         let shutdown = Arc::new(AtomicBool::new(false));
         let (tx, rx) = mpsc::channel(16);
         let player = Self {
@@ -185,7 +197,14 @@ impl Player {
         });
         tokio::spawn(player.run());
 
-        Ok((PlayerHandle { commands: tx }, event_rx, auth_data))
+        Ok((
+            PlayerHandle {
+                commands: tx,
+                device_id,
+            },
+            event_rx,
+            auth_data,
+        ))
     }
 
     async fn run(mut self) {
@@ -328,6 +347,7 @@ impl Drop for Player {
 #[derive(Clone, Debug)]
 pub struct PlayerHandle {
     commands: mpsc::Sender<PlayerCommand>,
+    device_id: String,
 }
 
 impl PlayerHandle {
@@ -335,6 +355,11 @@ impl PlayerHandle {
         !self.commands.is_closed()
     }
 
+    /// The Spotify Connect device ID of this player
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
     pub async fn next_track(&self) {
         _ = self.commands.send(PlayerCommand::NextTrack).await;
     }
@@ -1,3 +1,4 @@
+use rspotify::{http::HttpError, ClientError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,6 +15,54 @@ pub enum Error {
     #[error("Cannot perform this action on an active session")]
     AlreadyActive,
 
+    /// There is no session with an active player in this server
+    #[error("There is no active session in this server")]
+    NoSession,
+
+    /// The Spotify Connect device of the session did not show up in time
+    #[error("The Spotify device is not ready yet")]
+    DeviceNotReady,
+
+    /// The user already owns a session in another server
+    #[error("The user is already using Spoticord in another server")]
+    OwnerBusy,
+
+    /// The track is explicit, and the session owner has disabled explicit content
+    #[error("Explicit tracks are not allowed in this session")]
+    ExplicitContent,
+
+    /// The session's player has no track loaded
+    #[error("Nothing is currently playing")]
+    NothingPlaying,
+
+    /// The track is no longer available to the session owner
+    #[error("This track is not available for playback")]
+    TrackUnavailable,
+
+    /// The podcast episode can't be played through Spotify Connect, or the show has no playable episodes
+    #[error("This episode is not available for playback")]
+    EpisodeUnavailable,
+
+    /// The queue already holds the maximum amount of items
+    #[error("The queue is full")]
+    QueueFull,
+
+    /// The item is already in the queue, and duplicate items are not allowed
+    #[error("This item is already queued")]
+    AlreadyQueued,
+
+    /// Neither the session nor the owner's play history has an item before the current one
+    #[error("There is no previous track")]
+    NoPreviousTrack,
+
+    /// The requested position is not in the queue
+    #[error("There is no item at that position in the queue")]
+    InvalidQueuePosition,
+
+    /// Spotify refused to start or queue playback
+    #[error(transparent)]
+    Playback(#[from] PlaybackError),
+
     #[error(transparent)]
     Serenity(#[from] serenity::Error),
 
@@ -25,6 +74,73 @@ pub enum Error {
 
     #[error(transparent)]
     Librespot(#[from] librespot::core::Error),
+
+    #[error(transparent)]
+    Spotify(#[from] rspotify::ClientError),
+}
+
+impl Error {
+    /// Convert the error of a failed Spotify playback call, turning the reasons Spotify gives for refusing playback
+    /// into [`Error::Playback`]
+    pub(crate) async fn from_playback(error: ClientError) -> Self {
+        let ClientError::Http(http) = error else {
+            return error.into();
+        };
+
+        match *http {
+            HttpError::StatusCode(response) if matches!(response.status().as_u16(), 403 | 404) => {
+                let status = response.status().as_u16();
+                let body = response.text().await.unwrap_or_default();
+
+                PlaybackError::from_response(status, &body).into()
+            }
+            http => ClientError::Http(Box::new(http)).into(),
+        }
+    }
+}
+
+/// Why Spotify refused to start or queue playback
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PlaybackError {
+    /// The target device is not available to the account (anymore)
+    #[error("There is no active Spotify device")]
+    NoActiveDevice,
+
+    /// The account does not have Spotify Premium
+    #[error("Spotify Premium is required")]
+    PremiumRequired,
+
+    /// The item can't be played, usually because it is not available in the account's country
+    #[error("The item is not available for this account")]
+    MarketRestricted,
+
+    /// The device does not accept commands from the Web API
+    #[error("The Spotify device cannot be controlled remotely")]
+    DeviceNotControllable,
+
+    /// Spotify refused for a reason that has no dedicated variant, with the message Spotify gave
+    #[error("Spotify refused playback: {0}")]
+    Other(String),
+}
+
+impl PlaybackError {
+    /// Parse a Spotify error response, which looks like `{"error": {"status": 403, "message": "...", "reason": "..."}}`.
+    /// Only player endpoints include the reason.
+    fn from_response(status: u16, body: &str) -> Self {
+        let body: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+        let reason = body["error"]["reason"].as_str().unwrap_or_default();
+        let message = body["error"]["message"].as_str().unwrap_or_default();
+
+        match reason {
+            "NO_ACTIVE_DEVICE" => Self::NoActiveDevice,
+            "PREMIUM_REQUIRED" => Self::PremiumRequired,
+            "DEVICE_NOT_CONTROLLABLE" | "REMOTE_CONTROL_DISALLOW" => Self::DeviceNotControllable,
+            _ if message.to_lowercase().contains("restriction") => Self::MarketRestricted,
+            _ if status == 404 && message.to_lowercase().contains("device") => Self::NoActiveDevice,
+            _ if message.is_empty() => Self::Other(format!("status {status}")),
+            _ => Self::Other(message.to_string()),
+        }
+    }
 }
 
 pub type Result<T> = ::core::result::Result<T, Error>;
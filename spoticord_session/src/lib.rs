@@ -2,6 +2,10 @@ pub mod error;
 pub mod lyrics_embed;
 pub mod manager;
 pub mod playback_embed;
+pub mod queue;
+pub mod uri;
+
+mod remote;
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use error::Error;
@@ -11,7 +15,7 @@ use librespot::{
     discovery::Credentials,
     protocol::{authentication::AuthenticationType, keyexchange::ErrorCode},
 };
-use log::{debug, error, trace};
+use log::{debug, error, trace, warn};
 use lyrics_embed::LyricsEmbed;
 use manager::{SessionManager, SessionQuery};
 use playback_embed::{PlaybackEmbed, PlaybackEmbedHandle};
@@ -21,20 +25,41 @@ use serenity::{
     },
     async_trait,
 };
-use songbird::{model::payload::ClientDisconnect, Call, CoreEvent, Event, EventContext};
-use spoticord_player::{Player, PlayerEvent, PlayerHandle};
+use songbird::{
+    driver::Bitrate, model::payload::ClientDisconnect, Call, CoreEvent, Event, EventContext,
+};
+use spoticord_config::AloneAction;
+use spoticord_database::error::DatabaseError;
+use spoticord_player::{info::PlaybackInfo, Player, PlayerEvent, PlayerHandle};
 use spoticord_utils::discord::Colors;
-use std::{ops::ControlFlow, sync::Arc};
+use std::{
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use tokio::{
     sync::{mpsc, oneshot, Mutex},
     task::JoinHandle,
 };
 
+/// Voice channels with a lower bitrate (in bits per second) than this audibly degrade the audio quality
+const LOW_BITRATE: u32 = 64_000;
+
+/// The amount of played items a session remembers for `/previous`
+const SESSION_HISTORY_LIMIT: usize = 50;
+
 #[derive(Debug)]
 pub enum SessionCommand {
     GetOwner(oneshot::Sender<UserId>),
     GetPlayer(oneshot::Sender<PlayerHandle>),
     GetActive(oneshot::Sender<bool>),
+    GetBitrate(oneshot::Sender<Option<u32>>),
+    TakePrevious(oneshot::Sender<Option<String>>),
+    GetQueued(oneshot::Sender<Vec<String>>),
+    RecordQueued(String),
+    ForgetQueued(Vec<String>),
 
     CreatePlaybackEmbed(
         SessionHandle,
@@ -44,6 +69,11 @@ pub enum SessionCommand {
     CreateLyricsEmbed(SessionHandle, CommandInteraction),
 
     Reactivate(UserId, oneshot::Sender<Result<()>>),
+    TransferOwnership(UserId, oneshot::Sender<Result<()>>),
+    OwnerLeft,
+    CheckAlone,
+    AloneTimeout,
+    MoveTo(ChannelId, oneshot::Sender<Result<()>>),
     ShutdownPlayer,
     Disconnect,
     DisconnectTimedOut,
@@ -71,6 +101,21 @@ pub struct Session {
 
     playback_embed: Option<PlaybackEmbedHandle>,
     lyrics_embed: Option<JoinHandle<()>>,
+
+    /// Applies [`spoticord_config::alone_action`] once the grace period ends
+    alone_timer: Option<JoinHandle<()>>,
+
+    /// The bitrate of the voice channel, which the audio is encoded at
+    bitrate: Option<u32>,
+
+    /// The last known playback state, which is saved when the session ends so it can be resumed later
+    last_playback: Option<PlaybackInfo>,
+
+    /// The URIs of the items that were played in this session, oldest first
+    history: Vec<String>,
+
+    /// The URIs of the items the bot added to the queue that haven't started playing yet, oldest first
+    queued: Vec<String>,
 }
 
 impl Session {
@@ -89,7 +134,7 @@ impl Session {
         let (tx, rx) = mpsc::channel(16);
         let handle = SessionHandle {
             guild: guild_id,
-            voice_channel: voice_channel_id,
+            voice_channel: Arc::new(AtomicU64::new(voice_channel_id.get())),
             text_channel: text_channel_id,
 
             commands: tx,
@@ -106,18 +151,16 @@ impl Session {
         // This uses separate channels as to not cause a cyclic dependency
         let (inner_tx, inner_rx) = mpsc::channel(16);
 
-        // Grab user credentials and info before joining call
-        let account = session_manager
+        // Grab user preferences and credentials before joining call
+        let (user, account) = session_manager
             .database()
-            .get_account(owner.to_string())
+            .get_user_with_account(owner.to_string())
             .await?;
-
-        // Get user preferences
-        let device_name = session_manager
-            .database()
-            .get_user(owner.to_string())
-            .await?
-            .device_name;
+        // Disabled accounts are treated as unlinked, even though their credentials are still stored
+        let account = account
+            .filter(|account| !account.disabled)
+            .ok_or(DatabaseError::NotFound)?;
+        let (device_name, normalize) = (user.device_name, user.normalize);
 
         let credentials = match account
             .session_token
@@ -162,7 +205,7 @@ impl Session {
         }
 
         let (player, events, auth_data) =
-            match Player::create(credentials, call.clone(), device_name).await {
+            match Player::create(credentials, call.clone(), device_name, normalize).await {
                 Ok(player) => player,
                 Err(why) => {
                     // Leave call on error, otherwise bot will be stuck in call forever until manually disconnected or taken over
@@ -228,7 +271,15 @@ impl Session {
 
             playback_embed: None,
             lyrics_embed: None,
+
+            alone_timer: None,
+
+            bitrate: None,
+            last_playback: None,
+            history: vec![],
+            queued: vec![],
         };
+        session.apply_channel_bitrate(voice_channel_id).await;
         session.start_timeout();
 
         tokio::spawn(session.run());
@@ -285,6 +336,11 @@ impl Session {
             SessionCommand::GetOwner(sender) => _ = sender.send(self.owner),
             SessionCommand::GetPlayer(sender) => _ = sender.send(self.player.clone()),
             SessionCommand::GetActive(sender) => _ = sender.send(self.active),
+            SessionCommand::GetBitrate(sender) => _ = sender.send(self.bitrate),
+            SessionCommand::TakePrevious(sender) => _ = sender.send(self.take_previous()),
+            SessionCommand::GetQueued(sender) => _ = sender.send(self.queued.clone()),
+            SessionCommand::RecordQueued(uri) => self.queued.push(uri),
+            SessionCommand::ForgetQueued(uris) => self.queued.retain(|uri| !uris.contains(uri)),
 
             SessionCommand::CreatePlaybackEmbed(handle, interaction, behavior) => {
                 match PlaybackEmbed::create(self, handle, interaction, behavior).await {
@@ -315,6 +371,47 @@ impl Session {
             SessionCommand::Reactivate(new_owner, tx) => {
                 _ = tx.send(self.reactivate(new_owner).await)
             }
+            SessionCommand::TransferOwnership(new_owner, tx) => {
+                _ = tx.send(self.transfer_ownership(new_owner).await)
+            }
+            SessionCommand::OwnerLeft => self.owner_left().await,
+            SessionCommand::CheckAlone => self.check_alone().await,
+            SessionCommand::AloneTimeout => {
+                self.alone_timer = None;
+
+                // Someone may have rejoined during the grace period
+                if !self.members().await.is_empty() {
+                    return ControlFlow::Continue(());
+                }
+
+                match spoticord_config::alone_action() {
+                    AloneAction::Nothing => {}
+                    AloneAction::Pause => {
+                        if self.active {
+                            self.player.pause().await;
+                        }
+                    }
+                    AloneAction::Disconnect => {
+                        self.disconnect().await;
+
+                        _ = self
+                            .text_channel
+                            .send_message(
+                                &self.context,
+                                CreateMessage::new().embed(
+                                    CreateEmbed::new()
+                                        .title("It's a little quiet in here")
+                                        .description("Everyone has left the voice channel, so the bot has disconnected.")
+                                        .color(Colors::Warning),
+                                ),
+                            )
+                            .await;
+
+                        return ControlFlow::Break(());
+                    }
+                }
+            }
+            SessionCommand::MoveTo(channel, tx) => _ = tx.send(self.move_to(channel).await),
             SessionCommand::ShutdownPlayer => self.shutdown_player().await,
             SessionCommand::Disconnect => {
                 self.disconnect().await;
@@ -349,7 +446,7 @@ impl Session {
             PlayerEvent::Play => self.stop_timeout(),
             PlayerEvent::Pause => self.start_timeout(),
             PlayerEvent::Stopped => self.shutdown_player().await,
-            PlayerEvent::TrackChanged(_) => {}
+            PlayerEvent::TrackChanged(ref info) => self.record_play(info),
             PlayerEvent::ConnectionReset => {
                 self.disconnect().await;
 
@@ -368,6 +465,15 @@ impl Session {
             }
         }
 
+        if matches!(
+            event,
+            PlayerEvent::Play | PlayerEvent::Pause | PlayerEvent::TrackChanged(_)
+        ) {
+            if let Ok(Some(info)) = self.player.playback_info().await {
+                self.last_playback = Some(info);
+            }
+        }
+
         let force_edit = !matches!(event, PlayerEvent::TrackChanged(_));
 
         if let Some(playback_embed) = &self.playback_embed {
@@ -377,6 +483,62 @@ impl Session {
         }
     }
 
+    /// Remove the current item and the one before it from the session's history, and return the one before it.
+    ///
+    /// The returned item is added back once it starts playing.
+    fn take_previous(&mut self) -> Option<String> {
+        if self.history.len() < 2 {
+            return None;
+        }
+
+        self.history.pop();
+        self.history.pop()
+    }
+
+    /// Add a newly started track to the session's history and the owner's listening history
+    fn record_play(&mut self, info: &PlaybackInfo) {
+        let database = self.session_manager.database();
+        let owner = self.owner;
+        let uri = playback_uri(info);
+
+        if self.history.len() >= SESSION_HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        self.history.push(uri.clone());
+
+        // The queue plays in order, so anything queued before this item has been played or skipped
+        if let Some(index) = self.queued.iter().position(|queued| *queued == uri) {
+            self.queued.drain(..=index);
+        }
+
+        tokio::spawn(async move {
+            if let Err(why) = database.record_play(owner.to_string(), uri).await {
+                error!("Failed to record play history: {why}");
+            }
+        });
+    }
+
+    /// Save the last known playback state, so the owner can resume it in a new session
+    fn save_playback(&mut self) {
+        let Some(info) = self.last_playback.take() else {
+            return;
+        };
+
+        let database = self.session_manager.database();
+        let (guild, owner) = (self.guild_id, self.owner);
+        let uri = playback_uri(&info);
+        let position = info.current_position().min(info.duration());
+
+        tokio::spawn(async move {
+            if let Err(why) = database
+                .save_playback(guild.get(), owner.get(), uri, position)
+                .await
+            {
+                error!("Failed to save playback state: {why}");
+            }
+        });
+    }
+
     fn start_timeout(&mut self) {
         println!("Timeout functionality removed");
     }
@@ -385,6 +547,46 @@ impl Session {
         println!("Timeout functionality removed");
     }
 
+    /// Move the call to another voice channel. The driver is kept, so playback continues where it was.
+    async fn move_to(&mut self, channel: ChannelId) -> Result<()> {
+        // The join future must be awaited after releasing the call lock
+        let join = self.call.lock().await.join(channel).await?;
+        join.await?;
+
+        self.apply_channel_bitrate(channel).await;
+
+        Ok(())
+    }
+
+    /// Encode audio at the bitrate of a voice channel, as Discord won't deliver anything above it anyway
+    async fn apply_channel_bitrate(&mut self, channel: ChannelId) {
+        let bitrate = match channel.to_channel(&self.context).await {
+            Ok(channel) => channel.guild().and_then(|channel| channel.bitrate),
+            Err(why) => {
+                warn!("Failed to fetch voice channel {channel}: {why}");
+                None
+            }
+        };
+
+        let Some(bitrate) = bitrate else {
+            return;
+        };
+
+        if bitrate < LOW_BITRATE {
+            warn!(
+                "Voice channel {channel} in guild {} has a bitrate of {} kbps, audio quality will be degraded",
+                self.guild_id,
+                bitrate / 1000
+            );
+        }
+
+        self.call
+            .lock()
+            .await
+            .set_bitrate(Bitrate::BitsPerSecond(bitrate as i32));
+        self.bitrate = Some(bitrate);
+    }
+
     async fn reactivate(&mut self, new_owner: UserId) -> Result<()> {
         use Error::*;
 
@@ -396,14 +598,13 @@ impl Session {
 
         // Grab user credentials and info before joining call
         let account = self.session_manager.database().get_account(user_id).await?;
+        if account.disabled {
+            return Err(DatabaseError::NotFound.into());
+        }
 
         // Get user preferences
-        let device_name = self
-            .session_manager
-            .database()
-            .get_user(user_id)
-            .await?
-            .device_name;
+        let user = self.session_manager.database().get_user(user_id).await?;
+        let (device_name, normalize) = (user.device_name, user.normalize);
 
         let credentials = match account
             .session_token
@@ -431,7 +632,7 @@ impl Session {
         );
 
         let (player, player_events, auth_data) =
-            match Player::create(credentials, self.call.clone(), device_name).await {
+            match Player::create(credentials, self.call.clone(), device_name, normalize).await {
                 Ok(player) => player,
                 Err(why) => {
                     if let Some(connection::AuthenticationError::LoginFailed(
@@ -472,10 +673,135 @@ impl Session {
         self.events = player_events;
         self.active = true;
 
+        self.session_manager
+            .register_owner(self.guild_id, new_owner);
+
         Ok(())
     }
 
+    /// Hand the session over to another user, who must have linked their Spotify account.
+    ///
+    /// The player is recreated with the new owner's account, so playback restarts from their Spotify state.
+    async fn transfer_ownership(&mut self, new_owner: UserId) -> Result<()> {
+        let database = self.session_manager.database();
+
+        if !database.account_exists(new_owner.to_string()).await? {
+            return Err(Error::Database(
+                spoticord_database::error::DatabaseError::NotFound,
+            ));
+        }
+
+        if self.active {
+            self.shutdown_player().await;
+        }
+
+        self.reactivate(new_owner).await?;
+
+        _ = self
+            .text_channel
+            .send_message(
+                &self.context,
+                CreateMessage::new().embed(
+                    CreateEmbed::new()
+                        .title("Session ownership transferred")
+                        .description(format!(
+                            "<@{new_owner}> is now the owner of this session. Use Spotify to pick what to play."
+                        ))
+                        .color(Colors::Info),
+                ),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// Called when the owner leaves the voice channel. Ownership moves to another member in the channel
+    /// who has linked their Spotify account, and the player is shut down if nobody can take over.
+    async fn owner_left(&mut self) {
+        let owner = self.owner;
+        let candidates = self
+            .members()
+            .await
+            .into_iter()
+            .filter(|user| *user != owner);
+        let database = self.session_manager.database();
+
+        for candidate in candidates {
+            if self
+                .session_manager
+                .get_session(SessionQuery::Owner(candidate))
+                .is_some()
+                || !database
+                    .account_exists(candidate.to_string())
+                    .await
+                    .unwrap_or(false)
+            {
+                continue;
+            }
+
+            match self.transfer_ownership(candidate).await {
+                Ok(()) => return,
+                Err(why) => error!("Failed to transfer session to {candidate}: {why}"),
+            }
+        }
+
+        if self.active {
+            self.shutdown_player().await;
+        }
+    }
+
+    /// Start the grace period if the bot was left alone in its voice channel, or cancel it if someone is present
+    async fn check_alone(&mut self) {
+        if spoticord_config::alone_action() == AloneAction::Nothing {
+            return;
+        }
+
+        if !self.members().await.is_empty() {
+            if let Some(timer) = self.alone_timer.take() {
+                timer.abort();
+            }
+
+            return;
+        }
+
+        if self.alone_timer.is_some() {
+            return;
+        }
+
+        let commands = self.commands_inner_tx.clone();
+        self.alone_timer = Some(tokio::spawn(async move {
+            tokio::time::sleep(spoticord_config::alone_grace()).await;
+            _ = commands.send(SessionCommand::AloneTimeout).await;
+        }));
+    }
+
+    /// The users other than bots that are in the session's voice channel
+    async fn members(&self) -> Vec<UserId> {
+        let Some(channel) = self.call.lock().await.current_channel() else {
+            return vec![];
+        };
+
+        let bot = self.context.cache.current_user().id;
+        let Some(guild) = self.context.cache.guild(self.guild_id) else {
+            return vec![];
+        };
+
+        guild
+            .voice_states
+            .values()
+            .filter(|state| {
+                state
+                    .channel_id
+                    .is_some_and(|id| id.get() == channel.0.get())
+            })
+            .filter(|state| state.user_id != bot)
+            .filter(|state| !state.member.as_ref().is_some_and(|member| member.user.bot))
+            .map(|state| state.user_id)
+            .collect()
+    }
+
     async fn shutdown_player(&mut self) {
+        self.save_playback();
         self.player.shutdown().await;
         self.start_timeout();
 
@@ -490,6 +816,8 @@ impl Session {
         // Kill timeout if one is running
         self.stop_timeout();
 
+        self.save_playback();
+
         // Force close channels, as handles may otherwise hold this struct hostage
         self.commands.close();
         self.events.close();
@@ -500,6 +828,15 @@ impl Session {
     }
 }
 
+/// The Spotify URI of the track or episode that is playing
+pub fn playback_uri(info: &PlaybackInfo) -> String {
+    if info.is_episode() {
+        format!("spotify:episode:{}", info.track_id_string())
+    } else {
+        format!("spotify:track:{}", info.track_id_string())
+    }
+}
+
 impl Drop for Session {
     fn drop(&mut self) {
         // Abort timeout task
@@ -512,6 +849,11 @@ impl Drop for Session {
             lyrics.abort();
         }
 
+        // Abort the timer that would act on the bot being alone
+        if let Some(timer) = self.alone_timer.take() {
+            timer.abort();
+        }
+
         // Clean up the session from the session manager
         // This is done in Drop::drop to ensure that the session always cleans up after itself
         //  even if something went wrong
@@ -528,7 +870,7 @@ impl Drop for Session {
 #[derive(Clone, Debug)]
 pub struct SessionHandle {
     guild: GuildId,
-    voice_channel: ChannelId,
+    voice_channel: Arc<AtomicU64>,
     text_channel: ChannelId,
 
     commands: mpsc::Sender<SessionCommand>,
@@ -545,7 +887,7 @@ impl SessionHandle {
     }
 
     pub fn voice_channel(&self) -> ChannelId {
-        self.voice_channel
+        ChannelId::new(self.voice_channel.load(Ordering::Relaxed))
     }
 
     pub fn text_channel(&self) -> ChannelId {
@@ -570,6 +912,51 @@ impl SessionHandle {
         Ok(result)
     }
 
+    /// Retrieve the bitrate (in bits per second) the session encodes audio at, if it is known
+    pub async fn bitrate(&self) -> anyhow::Result<Option<u32>> {
+        let (tx, rx) = oneshot::channel();
+        self.commands.send(SessionCommand::GetBitrate(tx)).await?;
+
+        let result = rx.await?;
+        Ok(result)
+    }
+
+    /// Take the item that was played before the current one, if the session played one
+    pub async fn take_previous(&self) -> anyhow::Result<Option<String>> {
+        let (tx, rx) = oneshot::channel();
+        self.commands.send(SessionCommand::TakePrevious(tx)).await?;
+
+        let result = rx.await?;
+        Ok(result)
+    }
+
+    /// Retrieve the URIs of the items the bot added to the queue that haven't started playing yet
+    pub async fn queued(&self) -> anyhow::Result<Vec<String>> {
+        let (tx, rx) = oneshot::channel();
+        self.commands.send(SessionCommand::GetQueued(tx)).await?;
+
+        let result = rx.await?;
+        Ok(result)
+    }
+
+    /// Remember that the bot added an item to the queue, so it counts towards the maximum queue length
+    pub async fn record_queued(&self, uri: String) -> anyhow::Result<()> {
+        self.commands
+            .send(SessionCommand::RecordQueued(uri))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Forget items that are no longer in the queue, e.g. because the owner cleared it in Spotify
+    pub async fn forget_queued(&self, uris: Vec<String>) -> anyhow::Result<()> {
+        self.commands
+            .send(SessionCommand::ForgetQueued(uris))
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn active(&self) -> anyhow::Result<bool> {
         let (tx, rx) = oneshot::channel();
         self.commands.send(SessionCommand::GetActive(tx)).await?;
@@ -578,6 +965,28 @@ impl SessionHandle {
         Ok(result)
     }
 
+    /// Move the session to another voice channel in the same guild, without interrupting playback
+    pub async fn move_to(&self, channel: ChannelId) -> Result<()> {
+        // Update the channel beforehand, so the disconnect from the old channel is not mistaken for a lost connection
+        let previous = self.voice_channel.swap(channel.get(), Ordering::Relaxed);
+
+        let (tx, rx) = oneshot::channel();
+        let result = match self
+            .commands
+            .send(SessionCommand::MoveTo(channel, tx))
+            .await
+        {
+            Ok(()) => rx.await.unwrap_or(Err(Error::NoSession)),
+            Err(_) => Err(Error::NoSession),
+        };
+
+        if result.is_err() {
+            self.voice_channel.store(previous, Ordering::Relaxed);
+        }
+
+        result
+    }
+
     /// Instruct the session to make another user owner.
     ///
     /// This will fail if the session still has an active user assigned to it.
@@ -592,6 +1001,20 @@ impl SessionHandle {
         Ok(())
     }
 
+    /// Make another user the owner of the session, even if it is active
+    pub async fn transfer_ownership(&self, new_owner: UserId) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+
+        match self
+            .commands
+            .send(SessionCommand::TransferOwnership(new_owner, tx))
+            .await
+        {
+            Ok(()) => rx.await.unwrap_or(Err(Error::NoSession)),
+            Err(_) => Err(Error::NoSession),
+        }
+    }
+
     /// Create a playback embed as a response to an interaction
     ///
     /// This playback embed will automatically update when certain events happen
@@ -634,6 +1057,13 @@ impl SessionHandle {
         }
     }
 
+    /// Instruct the session to check whether it was left alone in its voice channel, after someone joined or left
+    pub async fn check_alone(&self) {
+        if let Err(why) = self.commands.send(SessionCommand::CheckAlone).await {
+            error!("Failed to send command: {why}");
+        }
+    }
+
     /// Instruct the session to destroy itself.
     ///
     /// This should also remove the player and the owner from the session manager.
@@ -654,7 +1084,15 @@ impl songbird::EventHandler for SessionHandle {
         match event {
             // NOTE: Discord can randomly make the driver disconnect when users join/leave the voice channel
             // Nothing we can do about it at this time since that is an issue with either Discord or Songbird
-            EventContext::DriverDisconnect(_) => {
+            EventContext::DriverDisconnect(data) => {
+                // Moving to another channel disconnects from the old one, which is expected
+                if data
+                    .channel_id
+                    .is_some_and(|channel| channel.0.get() != self.voice_channel().get())
+                {
+                    return None;
+                }
+
                 debug!("Bot disconnected from voice gateway, cleaning up");
 
                 self.disconnect().await;
@@ -668,9 +1106,11 @@ impl songbird::EventHandler for SessionHandle {
 
                 match self.owner().await {
                     Ok(id) if id.get() == user_id.0 => {
-                        debug!("Owner of session disconnected, stopping playback");
+                        debug!("Owner of session disconnected, transferring ownership");
 
-                        self.shutdown_player().await;
+                        if let Err(why) = self.commands.send(SessionCommand::OwnerLeft).await {
+                            error!("Failed to send command: {why}");
+                        }
                     }
                     _ => {}
                 }
@@ -1,11 +1,29 @@
 use super::{Session, SessionHandle};
-use crate::error::Result;
+use crate::{
+    error::{Error, Result},
+    queue::{QueueItem, QueueSnapshot},
+    remote::Remote,
+    uri::SpotifyUri,
+};
+use log::{error, info, warn};
+use rspotify::{
+    clients::{BaseClient, OAuthClient},
+    model::{
+        AdditionalType, Market, PlayableId, PlayableItem, PlaylistId, SearchResult, SearchType,
+        ShowId,
+    },
+    prelude::Id,
+    Token,
+};
 use serenity::all::{ChannelId, GuildId, UserId};
 use songbird::Songbird;
+use spoticord_config::with_retry;
 use spoticord_database::Database;
+use spoticord_player::{info::PlaybackInfo, PlayerHandle};
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 #[derive(Clone)]
@@ -22,6 +40,66 @@ pub enum SessionQuery {
     Owner(UserId),
 }
 
+/// How long playback commands wait for the session's Spotify device to become available
+const DEVICE_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The amount of recent episodes of a show that are checked for one that can be played
+const SHOW_EPISODE_LOOKAHEAD: u32 = 10;
+
+/// The amount of playlist items that are requested from Spotify at once (Spotify's maximum)
+const PLAYLIST_PAGE_SIZE: u32 = 100;
+
+/// A snapshot of the state of an active session
+#[derive(Debug, Clone)]
+pub struct SessionState {
+    pub owner: UserId,
+    pub voice_channel: ChannelId,
+
+    /// The item that is loaded in the player, if any
+    pub playback: Option<PlaybackInfo>,
+
+    /// The bitrate (in bits per second) audio is sent at, matching the voice channel
+    pub bitrate: Option<u32>,
+}
+
+/// The result of queueing a playlist
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PlaylistImport {
+    /// The amount of tracks that were added to the queue
+    pub added: usize,
+
+    /// The amount of items that were skipped because they are unavailable or not tracks
+    pub skipped: usize,
+
+    /// The amount of tracks that were skipped because they were already queued
+    pub duplicates: usize,
+
+    /// Whether the import stopped early because the playlist exceeds the import limit
+    pub truncated: bool,
+
+    /// Whether the import stopped early because the queue reached its maximum length
+    pub queue_full: bool,
+}
+
+/// A Spotify Connect device that is visible to a user's Spotify account
+#[derive(Debug, Clone)]
+pub struct SpotifyDevice {
+    pub id: Option<String>,
+    pub name: String,
+
+    /// The type of device, e.g. `Computer` or `Smartphone`
+    pub kind: String,
+
+    /// Whether the device is currently playing, or was the last to play
+    pub active: bool,
+
+    /// Whether the device refuses to be controlled through the Web API
+    pub restricted: bool,
+
+    /// Whether this is the device of the user's own Spoticord session
+    pub spoticord: bool,
+}
+
 impl SessionManager {
     pub fn new(songbird: Arc<Songbird>, database: Database) -> Self {
         Self {
@@ -91,6 +169,59 @@ impl SessionManager {
         };
     }
 
+    /// Forcibly evict the session in a guild and leave its voice channel, for sessions that got stuck.
+    ///
+    /// Unlike [`SessionHandle::disconnect`] this doesn't wait for the session to respond, and the voice connection is
+    /// dropped even if no session is tracked for the guild. Returns whether a session or voice connection was cleared.
+    pub async fn force_disconnect(&self, guild: GuildId) -> bool {
+        let session = self.sessions.lock().expect("mutex poisoned").remove(&guild);
+
+        self.owners
+            .lock()
+            .expect("mutex poisoned")
+            .retain(|_, handle| handle.guild() != guild);
+
+        // The session may not be processing commands anymore, so don't wait for it to shut down
+        if let Some(session) = &session {
+            let session = session.clone();
+            tokio::spawn(async move { session.disconnect().await });
+        }
+
+        let connected = self.songbird.get(guild).is_some();
+        if let Err(why) = self.songbird.remove(guild).await {
+            warn!("Failed to leave voice in guild {guild}: {why}");
+        }
+
+        session.is_some() || connected
+    }
+
+    /// Register the new owner of the session in a guild, after the session was reactivated by another user
+    pub(crate) fn register_owner(&self, guild: GuildId, owner: UserId) {
+        let Some(handle) = self.get_session(SessionQuery::Guild(guild)) else {
+            return;
+        };
+
+        self.owners
+            .lock()
+            .expect("mutex poisoned")
+            .insert(owner, handle);
+    }
+
+    /// Make another user the owner of the session in a guild.
+    ///
+    /// The new owner must have linked their Spotify account, and may not own a session in another server.
+    pub async fn transfer_ownership(&self, guild: GuildId, new_owner: UserId) -> Result<()> {
+        let session = self
+            .get_session(SessionQuery::Guild(guild))
+            .ok_or(Error::NoSession)?;
+
+        if self.get_session(SessionQuery::Owner(new_owner)).is_some() {
+            return Err(Error::OwnerBusy);
+        }
+
+        session.transfer_ownership(new_owner).await
+    }
+
     pub fn get_all_sessions(&self) -> Vec<SessionHandle> {
         self.sessions
             .lock()
@@ -100,6 +231,40 @@ impl SessionManager {
             .collect()
     }
 
+    /// Make sure every session still has a voice connection, which can silently drop when the Discord gateway reconnects.
+    ///
+    /// Sessions whose connection dropped rejoin their voice channel, and sessions whose channel no longer exists are disconnected.
+    pub async fn verify_sessions(&self, context: &serenity::all::Context) {
+        for session in self.get_all_sessions() {
+            let guild = session.guild();
+            let channel = session.voice_channel();
+
+            let connected = match self.songbird.get(guild) {
+                Some(call) => call.lock().await.current_connection().is_some(),
+                None => false,
+            };
+
+            if connected {
+                continue;
+            }
+
+            if channel.to_channel(context).await.is_err() {
+                warn!("Voice channel of session in guild {guild} is gone, disconnecting");
+
+                session.disconnect().await;
+                continue;
+            }
+
+            info!("Voice connection of session in guild {guild} was dropped, rejoining");
+
+            if let Err(why) = session.move_to(channel).await {
+                error!("Failed to rejoin voice channel in guild {guild}: {why}");
+
+                session.disconnect().await;
+            }
+        }
+    }
+
     /// Disconnects all active sessions and clears out all handles.
     ///
     /// The session manager can still create new sessions after all sessions have been shut down.
@@ -115,6 +280,672 @@ impl SessionManager {
         self.sessions.lock().expect("mutex poisoned").clear();
     }
 
+    /// Add a single track or episode to the queue of the session in a guild.
+    ///
+    /// Fails with [`Error::ExplicitContent`] if the item is explicit and the session owner doesn't allow explicit content,
+    /// or with [`Error::EpisodeUnavailable`] if an episode can't be played through Spotify Connect.
+    pub async fn enqueue(&self, guild: GuildId, item: PlayableId<'_>) -> Result<()> {
+        let mut remote = self.item_remote(guild, &item).await?;
+
+        remote.enqueue(item).await
+    }
+
+    /// Add a single track or episode to the front of the queue of the session in a guild.
+    ///
+    /// Returns the position the item landed at, where `0` means it is playing right away and `1` means it plays next.
+    /// The Web API can only append to the queue, so the item only plays next if nothing else has been queued.
+    /// Fails for the same reasons as [`SessionManager::enqueue`].
+    pub async fn enqueue_next(&self, guild: GuildId, item: PlayableId<'_>) -> Result<usize> {
+        let mut remote = self.item_remote(guild, &item).await?;
+
+        remote.enqueue_next(item).await
+    }
+
+    /// Skip the current item of the session in a guild, and play a track or episode right away.
+    ///
+    /// Fails for the same reasons as [`SessionManager::enqueue`].
+    pub async fn play_now(&self, guild: GuildId, item: PlayableId<'_>) -> Result<()> {
+        let mut remote = self.item_remote(guild, &item).await?;
+
+        remote.play_now(item, None).await
+    }
+
+    /// Resume playback that was saved when a previous session ended, at `position` milliseconds into the item.
+    ///
+    /// Fails with [`Error::TrackUnavailable`] or [`Error::EpisodeUnavailable`] if the item can no longer be played,
+    /// and for the same reasons as [`SessionManager::enqueue`].
+    pub async fn resume_saved_playback(
+        &self,
+        guild: GuildId,
+        uri: &str,
+        position: u32,
+    ) -> Result<()> {
+        let item = SpotifyUri::parse(uri)
+            .and_then(|uri| uri.playable())
+            .ok_or(Error::TrackUnavailable)?;
+
+        let mut remote = self.item_remote(guild, &item).await?;
+
+        if let PlayableId::Track(track) = &item {
+            let details = with_retry("track", || {
+                remote.spotify.track(track.clone(), Some(Market::FromToken))
+            })
+            .await?;
+
+            if details.is_playable == Some(false) {
+                return Err(Error::TrackUnavailable);
+            }
+        }
+
+        remote
+            .play_now(item, Some(chrono::Duration::milliseconds(position.into())))
+            .await
+    }
+
+    /// Go back to the item that was played before the current one in the session in a guild.
+    ///
+    /// The session's own history is used first, falling back to the owner's play history. Fails with
+    /// [`Error::NoPreviousTrack`] if neither has an item before the current one, and for the same reasons as
+    /// [`SessionManager::play_now`].
+    pub async fn play_previous(&self, guild: GuildId) -> Result<()> {
+        let session = self.active_session(guild).await?;
+
+        let uri = match session
+            .take_previous()
+            .await
+            .map_err(|_| Error::NoSession)?
+        {
+            Some(uri) => uri,
+            None => {
+                let owner = session.owner().await.map_err(|_| Error::NoSession)?;
+
+                // The most recent play is the current item
+                self.database
+                    .recent_plays(owner.to_string(), 2)
+                    .await?
+                    .into_iter()
+                    .nth(1)
+                    .map(|play| play.track_uri)
+                    .ok_or(Error::NoPreviousTrack)?
+            }
+        };
+
+        let item = SpotifyUri::parse(&uri)
+            .and_then(|uri| uri.playable())
+            .ok_or(Error::NoPreviousTrack)?;
+
+        self.play_now(guild, item).await
+    }
+
+    /// Find the latest episode of a show that can be played by the owner of the session in a guild.
+    ///
+    /// Fails with [`Error::EpisodeUnavailable`] if none of the recent episodes are playable.
+    pub async fn latest_episode(
+        &self,
+        guild: GuildId,
+        show: ShowId<'_>,
+    ) -> Result<PlayableId<'static>> {
+        let remote = self.remote(guild).await?;
+
+        let episodes = with_retry("show_episodes", || {
+            remote.spotify.get_shows_episodes_manual(
+                show.clone(),
+                Some(Market::FromToken),
+                Some(SHOW_EPISODE_LOOKAHEAD),
+                None,
+            )
+        })
+        .await?;
+
+        // Episodes are listed newest first
+        episodes
+            .items
+            .into_iter()
+            .find(|episode| episode.is_playable)
+            .map(|episode| PlayableId::Episode(episode.id))
+            .ok_or(Error::EpisodeUnavailable)
+    }
+
+    /// Create a remote for adding an item to the session in a guild, once the item has passed the explicit content
+    /// filter, episodes have been checked to be playable and the device is ready
+    async fn item_remote(&self, guild: GuildId, item: &PlayableId<'_>) -> Result<Remote> {
+        let remote = self.remote(guild).await?;
+        let allow_explicit = self.explicit_allowed(guild).await;
+
+        match item {
+            PlayableId::Track(track) if !allow_explicit => {
+                let details = with_retry("track", || {
+                    remote.spotify.track(track.clone(), Some(Market::FromToken))
+                })
+                .await?;
+
+                if details.explicit {
+                    return Err(Error::ExplicitContent);
+                }
+            }
+            PlayableId::Track(_) => {}
+            PlayableId::Episode(episode) => {
+                let details = with_retry("episode", || {
+                    remote
+                        .spotify
+                        .get_an_episode(episode.clone(), Some(Market::FromToken))
+                })
+                .await?;
+
+                if !details.is_playable {
+                    return Err(Error::EpisodeUnavailable);
+                }
+
+                if details.explicit && !allow_explicit {
+                    return Err(Error::ExplicitContent);
+                }
+            }
+        }
+
+        remote.wait_for_device(DEVICE_READY_TIMEOUT).await?;
+
+        Ok(remote)
+    }
+
+    /// Add all tracks of a playlist to the queue of the session in a guild.
+    ///
+    /// The playlist is retrieved one page at a time, and `progress` is called with the amount of
+    /// processed items and the total amount of items after every page.
+    /// Local files, unavailable tracks, (if the session owner doesn't allow them) explicit tracks and (if
+    /// [`spoticord_config::dedup_queue`] is enabled) tracks that are already queued are skipped,
+    /// and at most [`spoticord_config::playlist_import_limit`] tracks will be added.
+    /// Once the queue is full, the remaining tracks are skipped.
+    pub async fn enqueue_playlist(
+        &self,
+        guild: GuildId,
+        playlist: PlaylistId<'_>,
+        mut progress: impl FnMut(usize, usize) + Send,
+    ) -> Result<PlaylistImport> {
+        let mut remote = self.remote(guild).await?;
+        remote.wait_for_device(DEVICE_READY_TIMEOUT).await?;
+
+        let limit = spoticord_config::playlist_import_limit();
+        let allow_explicit = self.explicit_allowed(guild).await;
+
+        let mut import = PlaylistImport::default();
+        let mut offset = 0;
+
+        'pages: loop {
+            let page = with_retry("playlist_items", || {
+                remote.spotify.playlist_items_manual(
+                    playlist.clone(),
+                    None,
+                    Some(Market::FromToken),
+                    Some(PLAYLIST_PAGE_SIZE),
+                    Some(offset),
+                )
+            })
+            .await?;
+
+            for item in page.items {
+                let Some(PlayableItem::Track(track)) = item.track else {
+                    import.skipped += 1;
+                    continue;
+                };
+
+                let allowed = allow_explicit || !track.explicit;
+                let Some(id) = track
+                    .id
+                    .filter(|_| allowed && track.is_playable.unwrap_or(true))
+                else {
+                    import.skipped += 1;
+                    continue;
+                };
+
+                if limit != 0 && import.added >= limit {
+                    import.truncated = true;
+                    break 'pages;
+                }
+
+                match remote.enqueue(PlayableId::Track(id)).await {
+                    Ok(()) => import.added += 1,
+                    Err(Error::AlreadyQueued) => import.duplicates += 1,
+                    Err(Error::QueueFull) => {
+                        import.queue_full = true;
+                        break 'pages;
+                    }
+                    Err(why) => return Err(why),
+                }
+            }
+
+            progress(
+                import.added + import.skipped + import.duplicates,
+                page.total as usize,
+            );
+
+            if page.next.is_none() {
+                break;
+            }
+
+            offset += PLAYLIST_PAGE_SIZE;
+        }
+
+        Ok(import)
+    }
+
+    /// Search Spotify for tracks matching `query`, authenticated as `user`.
+    ///
+    /// Returns at most `limit` results, each with a track URI that can be passed to [`SessionManager::enqueue`].
+    pub async fn search_tracks(
+        &self,
+        user: UserId,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<QueueItem>> {
+        let access_token = self.database.get_access_token(user.to_string()).await?;
+        let spotify = spoticord_config::get_spotify(Token {
+            access_token,
+            ..Default::default()
+        });
+
+        let result = with_retry("search", || {
+            spotify.search(
+                query,
+                SearchType::Track,
+                Some(Market::FromToken),
+                None,
+                Some(limit),
+                None,
+            )
+        })
+        .await?;
+
+        let SearchResult::Tracks(page) = result else {
+            return Ok(vec![]);
+        };
+
+        Ok(page
+            .items
+            .into_iter()
+            .filter(|track| track.id.is_some() && track.is_playable.unwrap_or(true))
+            .map(|track| QueueItem::from(PlayableItem::Track(track)))
+            .collect())
+    }
+
+    /// Look up the details of tracks by their URIs, authenticated as `user`.
+    ///
+    /// Tracks that no longer exist, and URIs that aren't track URIs, are left out.
+    pub async fn lookup_tracks(&self, user: UserId, uris: &[String]) -> Result<Vec<QueueItem>> {
+        let ids = uris
+            .iter()
+            .filter_map(|uri| match SpotifyUri::parse(uri)? {
+                SpotifyUri::Track(track) => Some(track),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let access_token = self.database.get_access_token(user.to_string()).await?;
+        let spotify = spoticord_config::get_spotify(Token {
+            access_token,
+            ..Default::default()
+        });
+
+        let tracks = with_retry("tracks", || {
+            spotify.tracks(ids.iter().cloned(), Some(Market::FromToken))
+        })
+        .await?;
+
+        Ok(tracks
+            .into_iter()
+            .map(|track| QueueItem::from(PlayableItem::Track(track)))
+            .collect())
+    }
+
+    /// List the Spotify Connect devices that are visible to a user's Spotify account, authenticated as `user`
+    pub async fn devices(&self, user: UserId) -> Result<Vec<SpotifyDevice>> {
+        let access_token = self.database.get_access_token(user.to_string()).await?;
+        let spotify = spoticord_config::get_spotify(Token {
+            access_token,
+            ..Default::default()
+        });
+
+        let devices = with_retry("devices", || spotify.device()).await?;
+
+        let own_device = match self.get_session(SessionQuery::Owner(user)) {
+            Some(session) => session
+                .player()
+                .await
+                .ok()
+                .map(|player| player.device_id().to_string()),
+            None => None,
+        };
+
+        Ok(devices
+            .into_iter()
+            .map(|device| SpotifyDevice {
+                spoticord: own_device.is_some() && device.id == own_device,
+                id: device.id,
+                name: device.name,
+                kind: format!("{:?}", device._type),
+                active: device.is_active,
+                restricted: device.is_restricted,
+            })
+            .collect())
+    }
+
+    /// Retrieve the name of a user's Spotify account from the Web API, authenticated as `user`
+    pub async fn spotify_profile_name(&self, user: UserId) -> Result<String> {
+        let access_token = self.database.get_access_token(user.to_string()).await?;
+        let spotify = spoticord_config::get_spotify(Token {
+            access_token,
+            ..Default::default()
+        });
+
+        let profile = with_retry("profile", || spotify.me()).await?;
+
+        Ok(profile
+            .display_name
+            .unwrap_or_else(|| profile.id.id().to_string()))
+    }
+
+    /// Join a voice channel and leave it again right away, to verify that voice connections work.
+    ///
+    /// Fails with [`Error::AlreadyActive`] if the guild has a session, as leaving would end its voice connection.
+    pub async fn voice_dry_run(&self, guild: GuildId, channel: ChannelId) -> Result<()> {
+        if self.get_session(SessionQuery::Guild(guild)).is_some() {
+            return Err(Error::AlreadyActive);
+        }
+
+        let result = self.songbird.join(guild, channel).await;
+        _ = self.songbird.remove(guild).await;
+
+        result?;
+
+        Ok(())
+    }
+
+    /// Wait until the Spotify device that playback commands are sent to is available, or `timeout` elapses
+    pub async fn wait_for_device(&self, guild: GuildId, timeout: Duration) -> Result<()> {
+        self.remote(guild).await?.wait_for_device(timeout).await
+    }
+
+    /// Retrieve the currently playing item and upcoming items of the session in a guild
+    pub async fn queue_snapshot(&self, guild: GuildId) -> Result<QueueSnapshot> {
+        let remote = self.remote(guild).await?;
+        let queue = with_retry("queue", || remote.spotify.current_user_queue()).await?;
+
+        Ok(queue.into())
+    }
+
+    /// Skip ahead to the item at `index` in the upcoming queue (zero-based), dropping every item before it.
+    ///
+    /// Returns the item that will be played next.
+    pub async fn skip_to(&self, guild: GuildId, index: usize) -> Result<QueueItem> {
+        let remote = self.remote(guild).await?;
+        let mut queue =
+            QueueSnapshot::from(with_retry("queue", || remote.spotify.current_user_queue()).await?);
+
+        if index >= queue.upcoming.len() {
+            return Err(Error::InvalidQueuePosition);
+        }
+
+        // Skipping the current track moves to the first upcoming item, hence the inclusive range
+        for _ in 0..=index {
+            remote.player.next_track().await;
+        }
+
+        Ok(queue.upcoming.swap_remove(index))
+    }
+
+    /// Remove every upcoming item from the queue of the session in a guild, while the current item keeps playing.
+    ///
+    /// Returns the amount of items that were removed. See [`SessionManager::edit_queue`] for how the queue is edited.
+    pub async fn clear_queue(&self, guild: GuildId) -> Result<usize> {
+        let removed = self
+            .edit_queue(guild, |upcoming| Ok(std::mem::take(upcoming)))
+            .await?;
+
+        Ok(removed.len())
+    }
+
+    /// Remove the item at `index` in the upcoming queue (zero-based) of the session in a guild.
+    ///
+    /// Returns the item that was removed. See [`SessionManager::edit_queue`] for how the queue is edited.
+    pub async fn remove_from_queue(&self, guild: GuildId, index: usize) -> Result<QueueItem> {
+        let mut removed = self
+            .edit_queue(guild, |upcoming| {
+                if index >= upcoming.len() {
+                    return Err(Error::InvalidQueuePosition);
+                }
+
+                Ok(vec![upcoming.remove(index)])
+            })
+            .await?;
+
+        Ok(removed.remove(0))
+    }
+
+    /// Remove items from the upcoming queue of the session in a guild, returning the items `edit` took out of it.
+    ///
+    /// The Web API can't remove items from the queue, so the current item is started again at its current position,
+    /// followed by the items that are left. Spotify reports at most 20 upcoming items, so items further down the
+    /// queue are dropped as well, just like local files, which can't be started through the Web API.
+    async fn edit_queue(
+        &self,
+        guild: GuildId,
+        edit: impl FnOnce(&mut Vec<QueueItem>) -> Result<Vec<QueueItem>>,
+    ) -> Result<Vec<QueueItem>> {
+        let mut remote = self.remote(guild).await?;
+
+        let state = with_retry("playback_state", || {
+            remote
+                .spotify
+                .current_playback(None, None::<&[AdditionalType]>)
+        })
+        .await?
+        .ok_or(Error::NothingPlaying)?;
+        let mut queue =
+            QueueSnapshot::from(with_retry("queue", || remote.spotify.current_user_queue()).await?);
+
+        let current = queue
+            .current
+            .as_ref()
+            .and_then(|item| SpotifyUri::parse(item.uri.as_deref()?))
+            .and_then(|uri| uri.playable())
+            .ok_or(Error::NothingPlaying)?;
+
+        let removed = edit(&mut queue.upcoming)?;
+        let upcoming = queue
+            .upcoming
+            .iter()
+            .filter_map(|item| SpotifyUri::parse(item.uri.as_deref()?)?.playable())
+            .collect();
+
+        remote
+            .replace_queue(
+                current,
+                state.progress,
+                upcoming,
+                removed.iter().filter_map(|item| item.uri.clone()).collect(),
+            )
+            .await?;
+
+        // Starting the current item again also resumes it
+        if !state.is_playing {
+            let device = remote.device_id();
+            with_retry("pause", || remote.spotify.pause_playback(Some(device))).await?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Move the session in a guild to another voice channel, keeping its playback state and queue
+    pub async fn move_to(&self, guild: GuildId, channel: ChannelId) -> Result<()> {
+        let session = self
+            .get_session(SessionQuery::Guild(guild))
+            .ok_or(Error::NoSession)?;
+
+        if session.voice_channel() == channel {
+            return Ok(());
+        }
+
+        session.move_to(channel).await
+    }
+
+    /// The owner of the session in a guild, if there is one
+    pub async fn session_owner(&self, guild: GuildId) -> Option<UserId> {
+        let session = self.get_session(SessionQuery::Guild(guild))?;
+
+        session.owner().await.ok()
+    }
+
+    /// Retrieve the owner, voice channel and current playback of the active session in a guild
+    pub async fn session_state(&self, guild: GuildId) -> Result<SessionState> {
+        let session = self.active_session(guild).await?;
+
+        let owner = session.owner().await.map_err(|_| Error::NoSession)?;
+        let player = session.player().await.map_err(|_| Error::NoSession)?;
+
+        Ok(SessionState {
+            owner,
+            voice_channel: session.voice_channel(),
+            playback: player.playback_info().await.ok().flatten(),
+            bitrate: session.bitrate().await.ok().flatten(),
+        })
+    }
+
+    /// Retrieve the state of every active session, across all guilds
+    pub async fn session_states(&self) -> Vec<SessionState> {
+        let mut states = vec![];
+
+        for session in self.get_all_sessions() {
+            if let Ok(state) = self.session_state(session.guild()).await {
+                states.push(state);
+            }
+        }
+
+        states
+    }
+
+    /// Whether playback of the session in a guild is currently paused
+    pub async fn is_paused(&self, guild: GuildId) -> Result<bool> {
+        let player = self.active_player(guild).await?;
+
+        match player.playback_info().await {
+            Ok(Some(info)) => Ok(!info.playing()),
+            _ => Err(Error::NothingPlaying),
+        }
+    }
+
+    /// The elapsed and total duration of the track that is playing in a guild
+    pub async fn playback_position(&self, guild: GuildId) -> Option<(Duration, Duration)> {
+        let player = self.active_player(guild).await.ok()?;
+        let info = player.playback_info().await.ok()??;
+
+        Some((
+            Duration::from_millis(u64::from(info.current_position())),
+            Duration::from_millis(u64::from(info.duration())),
+        ))
+    }
+
+    /// Seek to `position` in the track that is playing in a guild
+    pub async fn seek(&self, guild: GuildId, position: Duration) -> Result<()> {
+        self.is_paused(guild).await?;
+
+        let remote = self.remote(guild).await?;
+        let position = chrono::Duration::from_std(position).unwrap_or_default();
+
+        with_retry("seek", || {
+            remote
+                .spotify
+                .seek_track(position, Some(remote.device_id()))
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stop playback of the session in a guild, while staying connected to its voice channel.
+    ///
+    /// The player is shut down, which ends the current track and removes the Spotify device, so nothing
+    /// keeps playing in the background. `/join` starts a new player in the same call.
+    pub async fn stop(&self, guild: GuildId) -> Result<()> {
+        let session = self.active_session(guild).await?;
+        session.shutdown_player().await;
+
+        Ok(())
+    }
+
+    /// Pause playback of the session in a guild.
+    ///
+    /// The player is a Spotify Connect device, so this pauses playback on Spotify as well.
+    pub async fn pause(&self, guild: GuildId) -> Result<()> {
+        self.is_paused(guild).await?;
+        self.active_player(guild).await?.pause().await;
+
+        Ok(())
+    }
+
+    /// Resume playback of the session in a guild
+    pub async fn resume(&self, guild: GuildId) -> Result<()> {
+        self.is_paused(guild).await?;
+        self.active_player(guild).await?.play().await;
+
+        Ok(())
+    }
+
+    async fn active_session(&self, guild: GuildId) -> Result<SessionHandle> {
+        let session = self
+            .get_session(SessionQuery::Guild(guild))
+            .ok_or(Error::NoSession)?;
+
+        if !session.active().await.unwrap_or(false) {
+            return Err(Error::NoSession);
+        }
+
+        Ok(session)
+    }
+
+    async fn active_player(&self, guild: GuildId) -> Result<PlayerHandle> {
+        let session = self.active_session(guild).await?;
+
+        session.player().await.map_err(|_| Error::NoSession)
+    }
+
+    /// Whether the owner of the session in a guild allows explicit tracks to be queued
+    async fn explicit_allowed(&self, guild: GuildId) -> bool {
+        let Some(owner) = self.session_owner(guild).await else {
+            return true;
+        };
+
+        self.database
+            .get_allow_explicit(owner.to_string())
+            .await
+            .unwrap_or(true)
+    }
+
+    /// Create a Web API remote for the active session in a guild, authenticated as the session owner
+    async fn remote(&self, guild: GuildId) -> Result<Remote> {
+        let session = self.active_session(guild).await?;
+
+        let owner = session.owner().await.map_err(|_| Error::NoSession)?;
+        let player = session.player().await.map_err(|_| Error::NoSession)?;
+
+        let access_token = self.database.get_access_token(owner.to_string()).await?;
+        let spotify = spoticord_config::get_spotify(Token {
+            access_token,
+            ..Default::default()
+        });
+
+        let preferred_device = self
+            .database
+            .get_preferred_device(owner.to_string())
+            .await
+            .ok()
+            .flatten();
+
+        Ok(Remote::new(session, spotify, player, preferred_device).await)
+    }
+
     pub fn songbird(&self) -> Arc<Songbird> {
         self.songbird.clone()
     }
@@ -3,13 +3,15 @@ use log::{error, trace};
 use poise::ChoiceParameter;
 use serenity::{
     all::{
-        ButtonStyle, CommandInteraction, ComponentInteraction, ComponentInteractionCollector,
-        Context, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter,
-        CreateInteractionResponse, CreateInteractionResponseFollowup,
-        CreateInteractionResponseMessage, CreateMessage, EditMessage, Message, User,
+        ButtonStyle, ChannelId, CommandInteraction, ComponentInteraction,
+        ComponentInteractionCollector, Context, CreateActionRow, CreateButton, CreateEmbed,
+        CreateEmbedAuthor, CreateEmbedFooter, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage,
+        EditMessage, Message, MessageId, User,
     },
     futures::StreamExt,
 };
+use spoticord_database::Database;
 use spoticord_player::{info::PlaybackInfo, PlayerHandle};
 use spoticord_utils::discord::Colors;
 use std::{ops::ControlFlow, time::Duration};
@@ -17,6 +19,9 @@ use tokio::{sync::mpsc, time::Instant};
 
 use crate::{Session, SessionHandle};
 
+/// How often the progress bar of a playing track is refreshed
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Debug)]
 pub enum Command {
     InvokeUpdate(bool),
@@ -48,6 +53,7 @@ impl UpdateBehavior {
 pub struct PlaybackEmbed {
     id: u64,
     ctx: Context,
+    database: Database,
     session: SessionHandle,
     message: Message,
 
@@ -83,26 +89,71 @@ impl PlaybackEmbed {
         };
 
         let ctx_id = interaction.id.get();
+        let database = session.session_manager.database();
+        let guild = session.guild_id.get();
 
-        // Send initial reply
-        interaction
-            .create_response(
-                &ctx,
-                CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .embed(build_embed(&playback_info, &owner))
-                        .components(vec![build_buttons(ctx_id, playback_info.playing())]),
-                ),
-            )
-            .await?;
+        // Take over the embed that was sent before (possibly before a restart) instead of sending another one
+        let existing = if update_behavior.is_static() {
+            None
+        } else {
+            stored_message(&ctx, &database, guild, interaction.channel_id).await
+        };
 
-        // If this is a static embed, we don't need to return any handles
-        if update_behavior.is_static() {
-            return Ok(None);
-        }
+        let message = match existing {
+            Some(mut message) => {
+                message
+                    .edit(
+                        &ctx,
+                        EditMessage::new()
+                            .embed(build_embed(&playback_info, &owner))
+                            .components(vec![build_buttons(ctx_id, playback_info.playing())]),
+                    )
+                    .await?;
+
+                interaction
+                    .create_response(
+                        &ctx,
+                        CreateInteractionResponse::Message(
+                            CreateInteractionResponseMessage::new()
+                                .embed(
+                                    CreateEmbed::new()
+                                        .description(format!(
+                                            "The playback embed has been updated: {}",
+                                            message.link()
+                                        ))
+                                        .color(Colors::Info),
+                                )
+                                .ephemeral(true),
+                        ),
+                    )
+                    .await?;
+
+                message
+            }
+            None => {
+                // Send initial reply
+                interaction
+                    .create_response(
+                        &ctx,
+                        CreateInteractionResponse::Message(
+                            CreateInteractionResponseMessage::new()
+                                .embed(build_embed(&playback_info, &owner))
+                                .components(vec![build_buttons(ctx_id, playback_info.playing())]),
+                        ),
+                    )
+                    .await?;
+
+                // If this is a static embed, we don't need to return any handles
+                if update_behavior.is_static() {
+                    return Ok(None);
+                }
+
+                // Retrieve message instead of editing interaction response, as those tokens are only valid for 15 minutes
+                interaction.get_response(&ctx).await?
+            }
+        };
 
-        // Retrieve message instead of editing interaction response, as those tokens are only valid for 15 minutes
-        let message = interaction.get_response(&ctx).await?;
+        remember_message(&database, guild, &message).await;
 
         let collector = ComponentInteractionCollector::new(&ctx)
             .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
@@ -112,6 +163,7 @@ impl PlaybackEmbed {
         let this = Self {
             id: ctx_id,
             ctx,
+            database,
             session: handle,
             message,
             last_update: Instant::now(),
@@ -128,9 +180,16 @@ impl PlaybackEmbed {
 
     async fn run(mut self, collector: ComponentInteractionCollector) {
         let mut stream = collector.stream();
+        let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
 
         loop {
             tokio::select! {
+                _ = interval.tick() => {
+                    if self.handle_tick().await.is_break() {
+                        break;
+                    }
+                }
+
                 opt_command = self.rx.recv() => {
                     let Some(command) = opt_command else {
                         break;
@@ -184,6 +243,22 @@ impl PlaybackEmbed {
         ControlFlow::Continue(())
     }
 
+    /// Refresh the progress bar, but only while the track is playing and no other update happened recently
+    async fn handle_tick(&mut self) -> ControlFlow<(), ()> {
+        if self.last_update.elapsed() < Duration::from_secs(2) {
+            return ControlFlow::Continue(());
+        }
+
+        // If the player is gone, let `update_embed` report that the session has ended
+        if let Ok(player) = self.session.player().await {
+            if !matches!(player.playback_info().await, Ok(Some(info)) if info.playing()) {
+                return ControlFlow::Continue(());
+            }
+        }
+
+        self.update_embed(true).await
+    }
+
     async fn handle_press(&self, press: ComponentInteraction) {
         trace!("Received button press: {press:?}");
 
@@ -297,7 +372,10 @@ impl PlaybackEmbed {
                 )
                 .await
             {
-                Ok(message) => self.message = message,
+                Ok(message) => {
+                    remember_message(&self.database, self.session.guild().get(), &message).await;
+                    self.message = message;
+                }
                 Err(why) => {
                     error!("Failed to update playback embed: {why}");
 
@@ -333,6 +411,7 @@ impl PlaybackEmbed {
                 .channel_id
                 .send_message(&self.ctx, CreateMessage::new().embed(not_playing_embed()))
                 .await?;
+            remember_message(&self.database, self.session.guild().get(), &self.message).await;
 
             return Ok(());
         }
@@ -361,6 +440,40 @@ impl PlaybackEmbedHandle {
     }
 }
 
+/// Find the playback embed that was last sent in a guild, if it still exists in `channel`
+async fn stored_message(
+    ctx: &Context,
+    database: &Database,
+    guild: u64,
+    channel: ChannelId,
+) -> Option<Message> {
+    let (stored_channel, message) = database.get_playback_message(guild).await.ok()??;
+    if stored_channel != channel.get() {
+        return None;
+    }
+
+    match ctx.http.get_message(channel, MessageId::new(message)).await {
+        Ok(message) => Some(message),
+        Err(why) => {
+            // Most likely the message was deleted, either way it can't be updated anymore
+            trace!("Stored playback embed is gone: {why}");
+            _ = database.clear_playback_message(guild).await;
+
+            None
+        }
+    }
+}
+
+/// Store the playback embed of a guild, so it can be taken over after a restart
+async fn remember_message(database: &Database, guild: u64, message: &Message) {
+    if let Err(why) = database
+        .set_playback_message(guild, message.channel_id.get(), message.id.get())
+        .await
+    {
+        error!("Failed to store playback embed: {why}");
+    }
+}
+
 async fn respond_not_playing(context: &Context, interaction: CommandInteraction) -> Result<()> {
     interaction
         .create_response(
@@ -413,43 +526,49 @@ fn build_embed(playback_info: &PlaybackInfo, owner: &User) -> CreateEmbed {
     }
 
     description += "\n";
+    description += &progress_bar(playback_info);
+
+    CreateEmbed::new()
+        .author(
+            CreateEmbedAuthor::new("Currently Playing")
+                .icon_url("https://spoticord.com/spotify-logo.png"),
+        )
+        .description(description)
+        .thumbnail(playback_info.thumbnail())
+        .footer(
+            CreateEmbedFooter::new(owner.global_name.as_ref().unwrap_or(&owner.name))
+                .icon_url(owner.face()),
+        )
+        .color(Colors::Info)
+}
 
+/// Render the play state, a progress bar and the elapsed and total time of the current track
+pub fn progress_bar(playback_info: &PlaybackInfo) -> String {
     let position = playback_info.current_position();
     let index = position * 20 / playback_info.duration();
 
-    description += if playback_info.playing() {
+    let mut bar = String::from(if playback_info.playing() {
         "▶️ "
     } else {
         "⏸️ "
-    };
+    });
 
     for i in 0..20 {
         if i == index {
-            description.push('🔵');
+            bar.push('🔵');
         } else {
-            description.push('▬');
+            bar.push('▬');
         }
     }
 
-    description += "\n:alarm_clock: ";
-    description += &format!(
+    bar += "\n:alarm_clock: ";
+    bar += &format!(
         "{} / {}",
         spoticord_utils::time_to_string(position / 1000),
         spoticord_utils::time_to_string(playback_info.duration() / 1000)
     );
 
-    CreateEmbed::new()
-        .author(
-            CreateEmbedAuthor::new("Currently Playing")
-                .icon_url("https://spoticord.com/spotify-logo.png"),
-        )
-        .description(description)
-        .thumbnail(playback_info.thumbnail())
-        .footer(
-            CreateEmbedFooter::new(owner.global_name.as_ref().unwrap_or(&owner.name))
-                .icon_url(owner.face()),
-        )
-        .color(Colors::Info)
+    bar
 }
 
 fn build_buttons(id: u64, playing: bool) -> CreateActionRow {
@@ -0,0 +1,68 @@
+use rspotify::model::{CurrentUserQueue, PlayableItem};
+
+/// A track or episode in a session's queue
+#[derive(Debug, Clone)]
+pub struct QueueItem {
+    pub name: String,
+
+    /// The artists of a track, or the show of an episode
+    pub artists: Vec<String>,
+
+    /// The Spotify URI of the item, or `None` for local files
+    pub uri: Option<String>,
+}
+
+impl QueueItem {
+    /// Format the item for use in an embed, e.g. `**Never Gonna Give You Up** by Rick Astley`
+    pub fn display(&self) -> String {
+        let name = spoticord_utils::discord::escape(&self.name);
+
+        if self.artists.is_empty() {
+            format!("**{name}**")
+        } else {
+            format!(
+                "**{name}** by {}",
+                spoticord_utils::discord::escape(self.artists.join(", "))
+            )
+        }
+    }
+}
+
+impl From<PlayableItem> for QueueItem {
+    fn from(item: PlayableItem) -> Self {
+        use rspotify::prelude::Id;
+
+        match item {
+            PlayableItem::Track(track) => Self {
+                name: track.name,
+                artists: track
+                    .artists
+                    .into_iter()
+                    .map(|artist| artist.name)
+                    .collect(),
+                uri: track.id.map(|id| id.uri()),
+            },
+            PlayableItem::Episode(episode) => Self {
+                name: episode.name,
+                artists: vec![episode.show.name],
+                uri: Some(episode.id.uri()),
+            },
+        }
+    }
+}
+
+/// The current state of a session's queue, as reported by Spotify
+#[derive(Debug, Clone, Default)]
+pub struct QueueSnapshot {
+    pub current: Option<QueueItem>,
+    pub upcoming: Vec<QueueItem>,
+}
+
+impl From<CurrentUserQueue> for QueueSnapshot {
+    fn from(queue: CurrentUserQueue) -> Self {
+        Self {
+            current: queue.currently_playing.map(QueueItem::from),
+            upcoming: queue.queue.into_iter().map(QueueItem::from).collect(),
+        }
+    }
+}
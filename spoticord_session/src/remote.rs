@@ -0,0 +1,288 @@
+use rspotify::{
+    clients::OAuthClient,
+    model::{AdditionalType, PlayableId},
+    prelude::Id,
+    AuthCodeSpotify, ClientResult,
+};
+use spoticord_config::{record_spotify_call, with_retry};
+use spoticord_player::PlayerHandle;
+use std::{collections::HashSet, time::Duration};
+use tokio::time::Instant;
+
+use crate::{
+    error::{Error, Result},
+    SessionHandle,
+};
+
+/// How often the devices endpoint is polled while waiting for a device to appear
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The maximum amount of upcoming items Spotify reports, longer queues are cut off
+const QUEUE_REPORT_LIMIT: usize = 20;
+
+/// Controls a session's player through the Spotify Web API, on behalf of the session owner
+pub(crate) struct Remote {
+    pub spotify: AuthCodeSpotify,
+    pub player: PlayerHandle,
+
+    /// The session the items are added to, which keeps track of the items the bot queued
+    session: SessionHandle,
+
+    /// The owner's preferred Spotify device, if it is available. Items are sent here instead of the player.
+    preferred_device: Option<String>,
+
+    /// Whether the target device has nothing loaded, in which case items must be played instead of queued
+    idle: bool,
+
+    /// The amount of items the bot added to the queue that haven't started playing yet
+    queued: usize,
+
+    /// The URIs of the upcoming items in the queue, only tracked if duplicate items are rejected
+    queued_uris: HashSet<String>,
+}
+
+impl Remote {
+    pub async fn new(
+        session: SessionHandle,
+        spotify: AuthCodeSpotify,
+        player: PlayerHandle,
+        preferred_device: Option<String>,
+    ) -> Self {
+        let preferred_device = match preferred_device {
+            Some(device) if Self::device_available(&spotify, &device).await => Some(device),
+            _ => None,
+        };
+
+        let dedup = spoticord_config::dedup_queue();
+        let idle = match &preferred_device {
+            Some(device) => !matches!(
+                with_retry("playback_state", || {
+                    spotify.current_playback(None, None::<&[AdditionalType]>)
+                })
+                .await,
+                Ok(Some(playback)) if playback.device.id.as_ref() == Some(device)
+            ),
+            None => !matches!(player.playback_info().await, Ok(Some(_))),
+        };
+
+        let queue = if idle || (spoticord_config::max_queue_length() == 0 && !dedup) {
+            None
+        } else {
+            with_retry("queue", || spotify.current_user_queue())
+                .await
+                .ok()
+        };
+
+        // Items only leave the session's list once they start playing on the bot's own player, so items that were
+        // played on another device or removed from the queue in Spotify are dropped here
+        let mut tracked = session.queued().await.unwrap_or_default();
+        let upcoming = match &queue {
+            _ if idle => Some(HashSet::new()),
+            Some(queue) if queue.queue.len() < QUEUE_REPORT_LIMIT => Some(
+                queue
+                    .queue
+                    .iter()
+                    .filter_map(|item| item.id().map(|id| id.uri()))
+                    .collect::<HashSet<_>>(),
+            ),
+            // A cut off queue may still hold the items further down
+            _ => None,
+        };
+
+        if let Some(upcoming) = upcoming {
+            let (kept, stale) = tracked
+                .into_iter()
+                .partition::<Vec<_>, _>(|uri| upcoming.contains(uri));
+
+            if !stale.is_empty() {
+                _ = session.forget_queued(stale).await;
+            }
+
+            tracked = kept;
+        }
+
+        let queued = tracked.len();
+        let queued_uris = queue
+            .filter(|_| dedup)
+            .map(|queue| {
+                queue
+                    .queue
+                    .iter()
+                    .filter_map(|item| item.id().map(|id| id.uri()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            spotify,
+            player,
+            session,
+            preferred_device,
+            idle,
+            queued,
+            queued_uris,
+        }
+    }
+
+    /// The ID of the device that items are sent to
+    pub fn device_id(&self) -> &str {
+        self.preferred_device
+            .as_deref()
+            .unwrap_or(self.player.device_id())
+    }
+
+    /// Wait until the target device is visible to Spotify, as librespot may still be connecting
+    pub async fn wait_for_device(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+
+        while !Self::device_available(&self.spotify, self.device_id()).await {
+            if Instant::now() >= deadline {
+                return Err(Error::DeviceNotReady);
+            }
+
+            tokio::time::sleep(DEVICE_POLL_INTERVAL).await;
+        }
+
+        Ok(())
+    }
+
+    /// Add an item to the end of the queue, or start playing it right away if nothing is loaded yet.
+    ///
+    /// Starting playback on the preferred device transfers playback there.
+    /// Fails with [`Error::QueueFull`] if the bot already queued [`spoticord_config::max_queue_length`] items that
+    /// haven't started playing yet,
+    /// or with [`Error::AlreadyQueued`] if the item is queued already and [`spoticord_config::dedup_queue`] is enabled.
+    pub async fn enqueue(&mut self, item: PlayableId<'_>) -> Result<()> {
+        let limit = spoticord_config::max_queue_length();
+        if limit != 0 && self.queued >= limit {
+            return Err(Error::QueueFull);
+        }
+
+        let uri = item.uri();
+        if spoticord_config::dedup_queue() && self.queued_uris.contains(&uri) {
+            return Err(Error::AlreadyQueued);
+        }
+
+        if self.idle {
+            let (spotify, device) = (&self.spotify, self.device_id());
+
+            playback(
+                with_retry("play", move || {
+                    spotify.start_uris_playback([item.clone()], Some(device), None, None)
+                })
+                .await,
+            )
+            .await?;
+
+            self.idle = false;
+        } else {
+            let result = self
+                .spotify
+                .add_item_to_queue(item, Some(self.device_id()))
+                .await;
+            record_spotify_call("enqueue", &result);
+
+            playback(result).await?;
+
+            self.queued += 1;
+            _ = self.session.record_queued(uri.clone()).await;
+            self.queued_uris.insert(uri);
+        }
+
+        Ok(())
+    }
+
+    /// Add an item to the front of the queue, or start playing it right away if nothing is loaded yet.
+    ///
+    /// The Web API can only append to the queue, so the item only plays next if nothing else has been queued.
+    /// Returns the position the item landed at: `0` if it is playing, `1` if it plays next, and so on.
+    pub async fn enqueue_next(&mut self, item: PlayableId<'_>) -> Result<usize> {
+        if self.idle {
+            self.enqueue(item).await?;
+            return Ok(0);
+        }
+
+        let upcoming = with_retry("queue", || self.spotify.current_user_queue())
+            .await?
+            .queue
+            .len();
+
+        self.enqueue(item).await?;
+
+        Ok(upcoming + 1)
+    }
+
+    /// Start playing an item right away, replacing the current track. Queued items will play after it.
+    ///
+    /// Playback starts at `position` if given, or at the start of the item otherwise.
+    pub async fn play_now(
+        &mut self,
+        item: PlayableId<'_>,
+        position: Option<chrono::Duration>,
+    ) -> Result<()> {
+        let (spotify, device) = (&self.spotify, self.device_id());
+
+        playback(
+            with_retry("play", move || {
+                spotify.start_uris_playback([item.clone()], Some(device), None, position)
+            })
+            .await,
+        )
+        .await?;
+
+        self.idle = false;
+
+        Ok(())
+    }
+
+    /// Start `current` again at `position`, followed by `upcoming`, which replaces the queue of the player.
+    ///
+    /// The Web API can't remove items from the queue, so this is how items are removed from it. The URIs of the items
+    /// that were dropped are given in `removed`, so they no longer count towards the queue limit.
+    pub async fn replace_queue<'a>(
+        &mut self,
+        current: PlayableId<'a>,
+        position: Option<chrono::Duration>,
+        upcoming: Vec<PlayableId<'a>>,
+        removed: Vec<String>,
+    ) -> Result<()> {
+        let items = [current].into_iter().chain(upcoming).collect::<Vec<_>>();
+        let (spotify, device) = (&self.spotify, self.device_id());
+
+        playback(
+            with_retry("play", move || {
+                spotify.start_uris_playback(items.clone(), Some(device), None, position)
+            })
+            .await,
+        )
+        .await?;
+
+        self.idle = false;
+        self.queued = self.queued.saturating_sub(removed.len());
+        for uri in &removed {
+            self.queued_uris.remove(uri);
+        }
+
+        _ = self.session.forget_queued(removed).await;
+
+        Ok(())
+    }
+
+    /// Whether a device is currently available to the owner's Spotify account
+    async fn device_available(spotify: &AuthCodeSpotify, device: &str) -> bool {
+        match with_retry("devices", || spotify.device()).await {
+            Ok(devices) => devices
+                .iter()
+                .any(|available| available.id.as_deref() == Some(device)),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Wait for the outcome of a playback call, explaining why Spotify refused it if it failed
+async fn playback<T>(result: ClientResult<T>) -> Result<T> {
+    match result {
+        Ok(value) => Ok(value),
+        Err(why) => Err(Error::from_playback(why).await),
+    }
+}
@@ -0,0 +1,163 @@
+use rspotify::model::{EpisodeId, PlayableId, PlaylistId, ShowId, TrackId};
+
+/// A Spotify item that can be added to a session's queue
+#[derive(Debug, Clone)]
+pub enum SpotifyUri {
+    Track(TrackId<'static>),
+    Playlist(PlaylistId<'static>),
+    Episode(EpisodeId<'static>),
+
+    /// A podcast, of which the latest episode is played
+    Show(ShowId<'static>),
+}
+
+impl SpotifyUri {
+    /// Parse an `open.spotify.com` link or a `spotify:<type>:<id>` URI.
+    ///
+    /// Returns `None` if the input isn't a link to a supported Spotify item.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+
+        let (kind, id) = match input.strip_prefix("spotify:") {
+            Some(uri) => uri.split_once(':')?,
+            None => {
+                let link = input
+                    .strip_prefix("https://")
+                    .or_else(|| input.strip_prefix("http://"))
+                    .unwrap_or(input)
+                    .strip_prefix("open.spotify.com/")?;
+                let path = link.split(['?', '#']).next()?;
+
+                // Localized links look like `open.spotify.com/intl-nl/track/<id>`
+                let mut segments = path
+                    .split('/')
+                    .filter(|segment| !segment.is_empty())
+                    .skip_while(|segment| segment.starts_with("intl-"));
+
+                (segments.next()?, segments.next()?)
+            }
+        };
+
+        match kind {
+            "track" => TrackId::from_id(id)
+                .ok()
+                .map(|id| Self::Track(id.into_static())),
+            "playlist" => PlaylistId::from_id(id)
+                .ok()
+                .map(|id| Self::Playlist(id.into_static())),
+            "episode" => EpisodeId::from_id(id)
+                .ok()
+                .map(|id| Self::Episode(id.into_static())),
+            "show" => ShowId::from_id(id)
+                .ok()
+                .map(|id| Self::Show(id.into_static())),
+            _ => None,
+        }
+    }
+
+    /// The item that is played for this URI, if it is a single track or episode
+    pub fn playable(&self) -> Option<PlayableId<'static>> {
+        match self {
+            Self::Track(track) => Some(PlayableId::Track(track.clone())),
+            Self::Episode(episode) => Some(PlayableId::Episode(episode.clone())),
+            Self::Playlist(_) | Self::Show(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rspotify::prelude::Id;
+
+    use super::*;
+
+    const TRACK: &str = "4cOdK2wGLETKBW3PvgPWqT";
+
+    fn track_id(input: &str) -> Option<String> {
+        match SpotifyUri::parse(input)? {
+            SpotifyUri::Track(track) => Some(track.id().to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parses_links() {
+        for link in [
+            format!("https://open.spotify.com/track/{TRACK}"),
+            format!("http://open.spotify.com/track/{TRACK}"),
+            format!("open.spotify.com/track/{TRACK}"),
+            format!(" https://open.spotify.com/track/{TRACK} "),
+        ] {
+            assert_eq!(track_id(&link).as_deref(), Some(TRACK), "{link}");
+        }
+    }
+
+    #[test]
+    fn parses_localized_links() {
+        for link in [
+            format!("https://open.spotify.com/intl-nl/track/{TRACK}"),
+            format!("https://open.spotify.com/intl-pt-BR/track/{TRACK}"),
+        ] {
+            assert_eq!(track_id(&link).as_deref(), Some(TRACK), "{link}");
+        }
+    }
+
+    #[test]
+    fn ignores_query_strings() {
+        for link in [
+            format!("https://open.spotify.com/track/{TRACK}?si=0123456789abcdef"),
+            format!("https://open.spotify.com/intl-nl/track/{TRACK}?si=0123456789abcdef"),
+            format!("https://open.spotify.com/track/{TRACK}#details"),
+        ] {
+            assert_eq!(track_id(&link).as_deref(), Some(TRACK), "{link}");
+        }
+    }
+
+    #[test]
+    fn parses_uris() {
+        assert_eq!(
+            track_id(&format!("spotify:track:{TRACK}")).as_deref(),
+            Some(TRACK)
+        );
+
+        assert!(matches!(
+            SpotifyUri::parse("spotify:playlist:37i9dQZF1DXcBWIGoYBM5M"),
+            Some(SpotifyUri::Playlist(_))
+        ));
+        assert!(matches!(
+            SpotifyUri::parse("spotify:episode:512ojhOuo1ktJprKbVcKyQ"),
+            Some(SpotifyUri::Episode(_))
+        ));
+        assert!(matches!(
+            SpotifyUri::parse("spotify:show:2mTUnDkuKUkhiueKcVWoP0"),
+            Some(SpotifyUri::Show(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unsupported_input() {
+        for input in [
+            "",
+            "never gonna give you up",
+            "https://example.com/track/4cOdK2wGLETKBW3PvgPWqT",
+            "https://open.spotify.com/artist/0gxyHStUsqpMadRV0Di1Qt",
+            "https://open.spotify.com/intl-nl/",
+            "spotify:album:4aawyAB9vmqN3uQ7FjRGTy",
+            "spotify:track:not-an-id",
+        ] {
+            assert!(
+                SpotifyUri::parse(input).is_none(),
+                "'{input}' should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn only_tracks_and_episodes_are_playable() {
+        let track = SpotifyUri::parse(&format!("spotify:track:{TRACK}")).unwrap();
+        assert!(matches!(track.playable(), Some(PlayableId::Track(_))));
+
+        let playlist = SpotifyUri::parse("spotify:playlist:37i9dQZF1DXcBWIGoYBM5M").unwrap();
+        assert!(playlist.playable().is_none());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod discord;
+pub mod metrics;
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
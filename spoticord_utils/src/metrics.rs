@@ -0,0 +1,209 @@
+//! A small in-process metrics registry, rendered in the Prometheus text exposition format.
+//!
+//! Metrics are created the first time they are recorded. Use [`describe`] to attach a help text.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{LazyLock, Mutex},
+};
+
+/// Histogram buckets (in seconds) used for every histogram
+const BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Default)]
+struct Family {
+    help: Option<&'static str>,
+    series: BTreeMap<Labels, Series>,
+}
+
+enum Series {
+    Counter(u64),
+    Gauge(f64),
+    Histogram {
+        buckets: Vec<u64>,
+        sum: f64,
+        count: u64,
+    },
+}
+
+impl Series {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Counter(_) => "counter",
+            Self::Gauge(_) => "gauge",
+            Self::Histogram { .. } => "histogram",
+        }
+    }
+}
+
+static REGISTRY: LazyLock<Mutex<BTreeMap<&'static str, Family>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// Attach a help text to a metric
+pub fn describe(name: &'static str, help: &'static str) {
+    let mut registry = REGISTRY.lock().expect("mutex poisoned");
+    registry.entry(name).or_default().help = Some(help);
+}
+
+/// Increment a counter by one
+pub fn increment_counter(name: &'static str, labels: &[(&'static str, &str)]) {
+    update(
+        name,
+        labels,
+        || Series::Counter(0),
+        |series| {
+            if let Series::Counter(value) = series {
+                *value += 1;
+            }
+        },
+    );
+}
+
+/// Set a gauge to a value
+pub fn set_gauge(name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+    update(
+        name,
+        labels,
+        || Series::Gauge(0.0),
+        |series| {
+            if let Series::Gauge(current) = series {
+                *current = value;
+            }
+        },
+    );
+}
+
+/// Record an observation (in seconds) in a histogram
+pub fn observe_histogram(name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+    let empty = || Series::Histogram {
+        buckets: vec![0; BUCKETS.len()],
+        sum: 0.0,
+        count: 0,
+    };
+
+    update(name, labels, empty, |series| {
+        if let Series::Histogram {
+            buckets,
+            sum,
+            count,
+        } = series
+        {
+            for (bucket, bound) in buckets.iter_mut().zip(BUCKETS) {
+                if value <= *bound {
+                    *bucket += 1;
+                }
+            }
+
+            *sum += value;
+            *count += 1;
+        }
+    });
+}
+
+fn update(
+    name: &'static str,
+    labels: &[(&'static str, &str)],
+    empty: impl FnOnce() -> Series,
+    apply: impl FnOnce(&mut Series),
+) {
+    let labels = labels
+        .iter()
+        .map(|(key, value)| (*key, value.to_string()))
+        .collect();
+
+    let mut registry = REGISTRY.lock().expect("mutex poisoned");
+    let series = registry
+        .entry(name)
+        .or_default()
+        .series
+        .entry(labels)
+        .or_insert_with(empty);
+
+    apply(series);
+}
+
+/// Render all metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    let registry = REGISTRY.lock().expect("mutex poisoned");
+    let mut output = String::new();
+
+    for (name, family) in registry.iter() {
+        let Some(kind) = family.series.values().next().map(Series::kind) else {
+            continue;
+        };
+
+        if let Some(help) = family.help {
+            _ = writeln!(output, "# HELP {name} {help}");
+        }
+
+        _ = writeln!(output, "# TYPE {name} {kind}");
+
+        for (labels, series) in &family.series {
+            match series {
+                Series::Counter(value) => {
+                    _ = writeln!(output, "{name}{} {value}", format_labels(labels, None));
+                }
+                Series::Gauge(value) => {
+                    _ = writeln!(output, "{name}{} {value}", format_labels(labels, None));
+                }
+                Series::Histogram {
+                    buckets,
+                    sum,
+                    count,
+                } => {
+                    for (bucket, bound) in buckets.iter().zip(BUCKETS) {
+                        let le = bound.to_string();
+                        _ = writeln!(
+                            output,
+                            "{name}_bucket{} {bucket}",
+                            format_labels(labels, Some(&le))
+                        );
+                    }
+
+                    _ = writeln!(
+                        output,
+                        "{name}_bucket{} {count}",
+                        format_labels(labels, Some("+Inf"))
+                    );
+                    _ = writeln!(output, "{name}_sum{} {sum}", format_labels(labels, None));
+                    _ = writeln!(
+                        output,
+                        "{name}_count{} {count}",
+                        format_labels(labels, None)
+                    );
+                }
+            }
+        }
+    }
+
+    output
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut pairs = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+        .collect::<Vec<_>>();
+
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{le}\""));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}